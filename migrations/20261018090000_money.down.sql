@@ -0,0 +1,8 @@
+DROP TRIGGER IF EXISTS default_budget_item_currency ON item;
+DROP FUNCTION IF EXISTS default_item_currency;
+
+ALTER TABLE item
+    DROP COLUMN currency,
+    ALTER COLUMN amount TYPE INT;
+
+ALTER TABLE budget DROP COLUMN currency;
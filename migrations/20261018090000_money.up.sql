@@ -0,0 +1,29 @@
+ALTER TABLE budget
+    ADD COLUMN currency TEXT NOT NULL DEFAULT 'DKK'
+        CONSTRAINT budget_currency_code CHECK (currency ~ '^[A-Z]{3}$');
+
+ALTER TABLE item
+    ALTER COLUMN amount TYPE BIGINT,
+    ADD COLUMN currency TEXT
+        CONSTRAINT item_currency_code CHECK (currency ~ '^[A-Z]{3}$');
+
+-- Existing items are in the currency of their budget.
+UPDATE item SET currency = budget.currency
+FROM budget
+WHERE budget.id = item.budget_id;
+
+ALTER TABLE item ALTER COLUMN currency SET NOT NULL;
+
+-- Items created without a currency use the currency of their budget.
+CREATE OR REPLACE FUNCTION default_item_currency()
+RETURNS TRIGGER AS $$
+BEGIN
+   IF NEW.currency IS NULL THEN
+      SELECT currency INTO NEW.currency FROM budget WHERE id = NEW.budget_id;
+   END IF;
+   RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER default_budget_item_currency BEFORE INSERT
+ON item FOR EACH ROW EXECUTE PROCEDURE default_item_currency();
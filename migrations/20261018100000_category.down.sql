@@ -0,0 +1,11 @@
+ALTER TABLE item ADD COLUMN category TEXT;
+
+UPDATE item SET category = category.name
+FROM category
+WHERE category.id = item.category_id;
+
+ALTER TABLE item
+    ALTER COLUMN category SET NOT NULL,
+    DROP COLUMN category_id;
+
+DROP TABLE IF EXISTS category;
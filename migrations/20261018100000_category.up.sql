@@ -0,0 +1,53 @@
+CREATE TABLE category (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL CONSTRAINT category_name_not_blank CHECK (trim(name) <> ''),
+    color TEXT CONSTRAINT category_color_hex CHECK (color ~ '^#[0-9a-fA-F]{6}$'),
+    icon TEXT,
+    kind TEXT NOT NULL DEFAULT 'expense' CONSTRAINT category_kind CHECK (kind IN ('income', 'expense')),
+    parent_id UUID,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    modified_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+
+    CONSTRAINT fk_parent FOREIGN KEY(parent_id) REFERENCES category(id)
+        ON DELETE SET NULL
+);
+
+-- Names differing only in case or surrounding whitespace are the same category.
+CREATE UNIQUE INDEX category_user_name ON category (user_id, lower(trim(name)));
+
+CREATE TRIGGER update_category_modified_timestamp BEFORE UPDATE
+ON category FOR EACH ROW EXECUTE PROCEDURE updated_modified_timestamp();
+
+-- Create a category for each distinct category name a user has used on their items.
+INSERT INTO category (user_id, name, kind)
+SELECT DISTINCT ON (budget.user_id, lower(trim(item.category)))
+    budget.user_id,
+    trim(item.category),
+    CASE WHEN lower(trim(item.category)) = 'income' THEN 'income' ELSE 'expense' END
+FROM item
+JOIN budget ON budget.id = item.budget_id
+WHERE trim(item.category) <> ''
+ORDER BY budget.user_id, lower(trim(item.category)), item.category;
+
+INSERT INTO category (user_id, name)
+SELECT DISTINCT budget.user_id, 'Uncategorized'
+FROM item
+JOIN budget ON budget.id = item.budget_id
+WHERE trim(item.category) = ''
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE item ADD COLUMN category_id UUID;
+
+UPDATE item SET category_id = category.id
+FROM budget, category
+WHERE budget.id = item.budget_id
+  AND category.user_id = budget.user_id
+  AND lower(trim(category.name)) = lower(COALESCE(NULLIF(trim(item.category), ''), 'Uncategorized'));
+
+ALTER TABLE item
+    ALTER COLUMN category_id SET NOT NULL,
+    ADD CONSTRAINT fk_category FOREIGN KEY(category_id) REFERENCES category(id),
+    DROP COLUMN category;
+
+CREATE INDEX item_category_id ON item (category_id);
@@ -0,0 +1,29 @@
+-- Actual spending and income, recorded against a budget.
+CREATE TABLE transaction (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    budget_id UUID NOT NULL,
+    -- The planned item the transaction counts against, if any.
+    item_id UUID,
+    -- Only set if the transaction is not in the category of its item.
+    category_id UUID,
+    date DATE NOT NULL,
+    amount BIGINT NOT NULL,
+    currency TEXT NOT NULL CONSTRAINT transaction_currency_code CHECK (currency ~ '^[A-Z]{3}$'),
+    payee TEXT,
+    memo TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    modified_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+
+    CONSTRAINT fk_budget FOREIGN KEY(budget_id) REFERENCES budget(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_item FOREIGN KEY(item_id) REFERENCES item(id)
+        ON DELETE SET NULL,
+    CONSTRAINT fk_category FOREIGN KEY(category_id) REFERENCES category(id)
+);
+
+CREATE INDEX transaction_budget_date ON transaction (budget_id, date);
+CREATE INDEX transaction_item ON transaction (item_id);
+CREATE INDEX transaction_category ON transaction (category_id);
+
+CREATE TRIGGER update_transaction_modified_timestamp BEFORE UPDATE
+ON transaction FOR EACH ROW EXECUTE PROCEDURE updated_modified_timestamp();
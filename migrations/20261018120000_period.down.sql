@@ -0,0 +1,5 @@
+ALTER TABLE budget
+    DROP COLUMN previous_id,
+    DROP COLUMN recurrence,
+    DROP COLUMN period_end,
+    DROP COLUMN period_start;
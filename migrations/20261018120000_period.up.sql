@@ -0,0 +1,14 @@
+-- The dates covered by a budget, and how often it repeats.
+ALTER TABLE budget
+    ADD COLUMN period_start DATE,
+    ADD COLUMN period_end DATE,
+    ADD COLUMN recurrence TEXT
+        CONSTRAINT budget_recurrence CHECK (recurrence IN ('weekly', 'biweekly', 'monthly', 'yearly')),
+    -- The budget of the previous period, which this budget was created from.
+    ADD COLUMN previous_id UUID
+        CONSTRAINT budget_previous_once UNIQUE,
+    ADD CONSTRAINT fk_previous FOREIGN KEY(previous_id) REFERENCES budget(id)
+        ON DELETE SET NULL,
+    ADD CONSTRAINT budget_period_complete CHECK ((period_start IS NULL) = (period_end IS NULL)),
+    ADD CONSTRAINT budget_period_order CHECK (period_end >= period_start),
+    ADD CONSTRAINT budget_recurrence_period CHECK (recurrence IS NULL OR period_start IS NOT NULL);
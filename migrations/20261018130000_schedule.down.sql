@@ -0,0 +1,3 @@
+ALTER TABLE item
+    DROP COLUMN schedule_rule,
+    DROP COLUMN schedule_start;
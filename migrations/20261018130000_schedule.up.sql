@@ -0,0 +1,5 @@
+-- When a recurring item occurs: the first date, and an iCalendar RRULE for the following dates.
+ALTER TABLE item
+    ADD COLUMN schedule_start DATE,
+    ADD COLUMN schedule_rule TEXT,
+    ADD CONSTRAINT item_schedule_complete CHECK ((schedule_start IS NULL) = (schedule_rule IS NULL));
@@ -0,0 +1,2 @@
+DROP VIEW budget_access;
+DROP TABLE budget_member;
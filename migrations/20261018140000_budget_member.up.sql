@@ -0,0 +1,23 @@
+-- Users a budget is shared with, besides the user who created it.
+CREATE TABLE budget_member (
+    budget_id UUID NOT NULL,
+    user_id TEXT NOT NULL,
+    role TEXT NOT NULL CONSTRAINT budget_member_role CHECK (role IN ('owner', 'editor', 'viewer')),
+    invited_by TEXT NOT NULL,
+    -- NULL until the user accepts the invitation.
+    accepted_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+
+    PRIMARY KEY (budget_id, user_id),
+    CONSTRAINT fk_budget FOREIGN KEY(budget_id) REFERENCES budget(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX budget_member_user ON budget_member (user_id);
+
+-- The role of every user with access to a budget. The user who created a
+-- budget is always an owner of it.
+CREATE VIEW budget_access AS
+    SELECT id AS budget_id, user_id, 'owner' AS role FROM budget
+    UNION ALL
+    SELECT budget_id, user_id, role FROM budget_member WHERE accepted_at IS NOT NULL;
@@ -0,0 +1,9 @@
+DROP TRIGGER touch_budget_of_item ON item;
+DROP FUNCTION touch_budget_of_item();
+DROP TRIGGER increment_item_version ON item;
+DROP TRIGGER increment_budget_version ON budget;
+DROP TRIGGER update_budget_modified_timestamp ON budget;
+DROP FUNCTION increment_version();
+
+ALTER TABLE item DROP COLUMN version;
+ALTER TABLE budget DROP COLUMN version, DROP COLUMN modified_at;
@@ -0,0 +1,50 @@
+-- Versions of budgets and items, which are their ETags, so changes made at
+-- the same time by different clients can be detected.
+ALTER TABLE budget
+    ADD COLUMN modified_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+UPDATE budget SET modified_at = created_at;
+
+ALTER TABLE item ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+
+-- An update that changes nothing does not make a new version. A budget is
+-- given a new version by its items with `version = version + 1`, which is kept.
+CREATE OR REPLACE FUNCTION increment_version()
+RETURNS TRIGGER AS $$
+BEGIN
+   IF to_jsonb(NEW) - 'version' - 'modified_at' IS DISTINCT FROM to_jsonb(OLD) - 'version' - 'modified_at' THEN
+      NEW.version = OLD.version + 1;
+   END IF;
+   RETURN NEW;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER update_budget_modified_timestamp BEFORE UPDATE
+ON budget FOR EACH ROW EXECUTE PROCEDURE updated_modified_timestamp();
+
+CREATE TRIGGER increment_budget_version BEFORE UPDATE
+ON budget FOR EACH ROW EXECUTE PROCEDURE increment_version();
+
+CREATE TRIGGER increment_item_version BEFORE UPDATE
+ON item FOR EACH ROW EXECUTE PROCEDURE increment_version();
+
+-- A budget is returned with its items, so any change to its items is also a
+-- new version of the budget.
+CREATE OR REPLACE FUNCTION touch_budget_of_item()
+RETURNS TRIGGER AS $$
+BEGIN
+   IF TG_OP = 'UPDATE' AND to_jsonb(NEW) - 'version' - 'modified_at' = to_jsonb(OLD) - 'version' - 'modified_at' THEN
+      RETURN NULL;
+   END IF;
+   IF TG_OP <> 'INSERT' THEN
+      UPDATE budget SET version = version + 1 WHERE id = OLD.budget_id;
+   END IF;
+   IF TG_OP = 'INSERT' OR (TG_OP = 'UPDATE' AND NEW.budget_id <> OLD.budget_id) THEN
+      UPDATE budget SET version = version + 1 WHERE id = NEW.budget_id;
+   END IF;
+   RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER touch_budget_of_item AFTER INSERT OR UPDATE OR DELETE
+ON item FOR EACH ROW EXECUTE PROCEDURE touch_budget_of_item();
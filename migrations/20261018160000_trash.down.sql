@@ -0,0 +1,12 @@
+DROP VIEW trashed_budget_access;
+
+CREATE OR REPLACE VIEW budget_access AS
+    SELECT id AS budget_id, user_id, 'owner' AS role FROM budget
+    UNION ALL
+    SELECT budget_id, user_id, role FROM budget_member WHERE accepted_at IS NOT NULL;
+
+DROP INDEX item_deleted_at;
+DROP INDEX budget_deleted_at;
+
+ALTER TABLE item DROP COLUMN deleted_at;
+ALTER TABLE budget DROP COLUMN deleted_at;
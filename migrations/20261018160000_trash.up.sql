@@ -0,0 +1,23 @@
+-- Deleted budgets and items are kept in the trash until they are restored, or
+-- purged after the retention period.
+ALTER TABLE budget ADD COLUMN deleted_at TIMESTAMP;
+ALTER TABLE item ADD COLUMN deleted_at TIMESTAMP;
+
+CREATE INDEX budget_deleted_at ON budget (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX item_deleted_at ON item (deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Budgets in the trash cannot be accessed until they are restored.
+CREATE OR REPLACE VIEW budget_access AS
+    SELECT id AS budget_id, user_id, 'owner' AS role FROM budget WHERE deleted_at IS NULL
+    UNION ALL
+    SELECT m.budget_id, m.user_id, m.role FROM budget_member AS m
+    JOIN budget AS b ON b.id = m.budget_id
+    WHERE m.accepted_at IS NOT NULL AND b.deleted_at IS NULL;
+
+-- The role of every user with access to a budget in the trash.
+CREATE VIEW trashed_budget_access AS
+    SELECT id AS budget_id, user_id, 'owner' AS role FROM budget WHERE deleted_at IS NOT NULL
+    UNION ALL
+    SELECT m.budget_id, m.user_id, m.role FROM budget_member AS m
+    JOIN budget AS b ON b.id = m.budget_id
+    WHERE m.accepted_at IS NOT NULL AND b.deleted_at IS NOT NULL;
@@ -0,0 +1,4 @@
+DROP TRIGGER audit_item ON item;
+DROP TRIGGER audit_budget ON budget;
+DROP FUNCTION audit_change;
+DROP TABLE audit_event;
@@ -0,0 +1,103 @@
+-- Audit log of every change to budgets and items. Events are written by
+-- triggers in the transaction of the change, with the actor and request id
+-- that the repositories set in `audit.actor` and `audit.request_id`.
+CREATE TABLE audit_event (
+    id BIGSERIAL PRIMARY KEY,
+    budget_id UUID NOT NULL,
+    entity TEXT NOT NULL,
+    entity_id UUID NOT NULL,
+    action TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    request_id TEXT,
+    -- The fields that were changed, with their values before and after the
+    -- change. A created row has no before, and a deleted row no after.
+    before JSONB,
+    after JSONB,
+    -- The versions of the changed row and of its budget before the change, so
+    -- a past version can be restored exactly. A created row has no version.
+    version BIGINT,
+    budget_version BIGINT,
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp
+);
+
+CREATE INDEX audit_event_budget_id_idx ON audit_event (budget_id, id);
+
+CREATE OR REPLACE FUNCTION audit_change()
+RETURNS TRIGGER AS $$
+DECLARE
+   actor TEXT := nullif(current_setting('audit.actor', true), '');
+   old_row JSONB;
+   new_row JSONB;
+   before JSONB;
+   after JSONB;
+   action TEXT;
+   old_version BIGINT;
+   old_budget_version BIGINT;
+BEGIN
+   -- Every change made by the API has an actor, including purging the trash,
+   -- so only changes made directly in the database, e.g. by fixtures, are not recorded.
+   IF actor IS NULL THEN
+      RETURN NULL;
+   END IF;
+   -- The version and modification time change with every update, so they are left out.
+   IF TG_OP <> 'INSERT' THEN
+      old_row := to_jsonb(OLD) - 'version' - 'modified_at';
+   END IF;
+   IF TG_OP <> 'DELETE' THEN
+      new_row := to_jsonb(NEW) - 'version' - 'modified_at';
+   END IF;
+
+   IF TG_OP = 'INSERT' THEN
+      action := 'create';
+      after := new_row;
+   ELSIF TG_OP = 'DELETE' THEN
+      action := 'delete';
+      before := old_row;
+   ELSE
+      SELECT jsonb_object_agg(key, value) INTO before
+      FROM jsonb_each(old_row) WHERE new_row -> key IS DISTINCT FROM value;
+      SELECT jsonb_object_agg(key, value) INTO after
+      FROM jsonb_each(new_row) WHERE old_row -> key IS DISTINCT FROM value;
+      -- e.g. a new version of a budget because one of its items changed.
+      IF before IS NULL THEN
+         RETURN NULL;
+      END IF;
+      action := CASE
+         WHEN NOT after ? 'deleted_at' THEN 'update'
+         WHEN after -> 'deleted_at' = 'null' THEN 'restore'
+         ELSE 'delete'
+      END;
+   END IF;
+
+   IF TG_OP <> 'INSERT' THEN
+      old_version := OLD.version;
+   END IF;
+   -- The budget of an item is only given its new version by a later trigger.
+   IF TG_TABLE_NAME = 'item' THEN
+      SELECT b.version INTO old_budget_version FROM budget AS b WHERE b.id = (coalesce(new_row, old_row) ->> 'budget_id')::uuid;
+   ELSE
+      old_budget_version := old_version;
+   END IF;
+
+   INSERT INTO audit_event (budget_id, entity, entity_id, action, actor, request_id, before, after, version, budget_version)
+   VALUES (
+      (coalesce(new_row, old_row) ->> CASE TG_TABLE_NAME WHEN 'item' THEN 'budget_id' ELSE 'id' END)::uuid,
+      TG_TABLE_NAME,
+      (coalesce(new_row, old_row) ->> 'id')::uuid,
+      action,
+      actor,
+      nullif(current_setting('audit.request_id', true), ''),
+      before,
+      after,
+      old_version,
+      old_budget_version
+   );
+   RETURN NULL;
+END;
+$$ language 'plpgsql';
+
+CREATE TRIGGER audit_budget AFTER INSERT OR UPDATE OR DELETE
+ON budget FOR EACH ROW EXECUTE PROCEDURE audit_change();
+
+CREATE TRIGGER audit_item AFTER INSERT OR UPDATE OR DELETE
+ON item FOR EACH ROW EXECUTE PROCEDURE audit_change();
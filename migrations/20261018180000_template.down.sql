@@ -0,0 +1,4 @@
+ALTER TABLE item DROP CONSTRAINT item_amount_range;
+
+DROP TABLE template_item;
+DROP TABLE template;
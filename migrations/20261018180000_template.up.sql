@@ -0,0 +1,38 @@
+-- Templates of the budgets a user creates again and again, with the items to
+-- add to every budget created from them.
+CREATE TABLE template (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    currency TEXT NOT NULL CONSTRAINT template_currency_code CHECK (currency ~ '^[A-Z]{3}$'),
+    created_at TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    modified_at TIMESTAMP NOT NULL DEFAULT current_timestamp
+);
+
+CREATE INDEX template_user_id ON template (user_id);
+
+CREATE TRIGGER update_template_modified_timestamp BEFORE UPDATE
+ON template FOR EACH ROW EXECUTE PROCEDURE updated_modified_timestamp();
+
+-- Items without a currency use the currency of the budget they are added to.
+CREATE TABLE template_item (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    template_id UUID NOT NULL,
+    position INT NOT NULL,
+    category_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    amount BIGINT NOT NULL,
+    currency TEXT CONSTRAINT template_item_currency_code CHECK (currency ~ '^[A-Z]{3}$'),
+
+    CONSTRAINT fk_template FOREIGN KEY(template_id) REFERENCES template(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_category FOREIGN KEY(category_id) REFERENCES category(id),
+    CONSTRAINT template_item_position UNIQUE (template_id, position)
+);
+
+CREATE INDEX template_item_category_id ON template_item (category_id);
+
+-- Amounts are validated in requests, but budgets duplicated with scaled
+-- amounts are only checked here. Same as `MAX_AMOUNT` in the API.
+ALTER TABLE item ADD CONSTRAINT item_amount_range
+    CHECK (amount BETWEEN -1000000000000 AND 1000000000000);
@@ -1,6 +1,13 @@
 use crate::{
+    audit::repository::AuditRepository,
     auth::{config::AuthConfig, jwk::JwkRepository},
-    budget::{item_repository::ItemRepository, repository::BudgetRepository},
+    budget::{
+        item_repository::ItemRepository, member_repository::MemberRepository,
+        repository::BudgetRepository, transaction_repository::TransactionRepository,
+    },
+    category::repository::CategoryRepository,
+    template::repository::TemplateRepository,
+    trash::{self, repository::TrashRepository},
 };
 use anyhow::Result;
 use axum::extract::FromRef;
@@ -16,6 +23,12 @@ pub struct AppState {
     jwks_repository: Arc<JwkRepository>,
     budget_repository: Arc<BudgetRepository>,
     item_repository: Arc<ItemRepository>,
+    transaction_repository: Arc<TransactionRepository>,
+    member_repository: Arc<MemberRepository>,
+    category_repository: Arc<CategoryRepository>,
+    template_repository: Arc<TemplateRepository>,
+    trash_repository: Arc<TrashRepository>,
+    audit_repository: Arc<AuditRepository>,
 }
 
 impl AppState {
@@ -28,20 +41,43 @@ impl AppState {
 
         let auth_config = AuthConfig::from_env();
         let jwks_repository = Arc::new(JwkRepository::new(auth_config).await?);
+        jwks_repository.spawn_refresh();
 
-        Ok(Self {
+        let state = Self::new(pool, jwks_repository);
+        state
+            .trash_repository
+            .spawn_purge(trash::retention_days_from_env());
+
+        Ok(state)
+    }
+
+    /// Create the state from already initialized connections.
+    pub fn new(pool: Arc<PgPool>, jwks_repository: Arc<JwkRepository>) -> Self {
+        Self {
             jwks_repository,
             budget_repository: Arc::new(BudgetRepository::new(pool.clone())),
             item_repository: Arc::new(ItemRepository::new(pool.clone())),
-        })
+            transaction_repository: Arc::new(TransactionRepository::new(pool.clone())),
+            member_repository: Arc::new(MemberRepository::new(pool.clone())),
+            category_repository: Arc::new(CategoryRepository::new(pool.clone())),
+            template_repository: Arc::new(TemplateRepository::new(pool.clone())),
+            trash_repository: Arc::new(TrashRepository::new(pool.clone())),
+            audit_repository: Arc::new(AuditRepository::new(pool)),
+        }
     }
 }
 
 #[duplicate_item(
-    service_type         field;
-    [ BudgetRepository ] [ budget_repository ];
-    [ ItemRepository ]   [ item_repository ];
-    [ JwkRepository ]    [ jwks_repository ];
+    service_type              field;
+    [ BudgetRepository ]      [ budget_repository ];
+    [ ItemRepository ]        [ item_repository ];
+    [ TransactionRepository ] [ transaction_repository ];
+    [ MemberRepository ]      [ member_repository ];
+    [ CategoryRepository ]    [ category_repository ];
+    [ TemplateRepository ]    [ template_repository ];
+    [ TrashRepository ]       [ trash_repository ];
+    [ AuditRepository ]       [ audit_repository ];
+    [ JwkRepository ]         [ jwks_repository ];
 )]
 impl FromRef<AppState> for Arc<service_type> {
     fn from_ref(app_state: &AppState) -> Self {
@@ -0,0 +1,126 @@
+//! Audit log of the changes to budgets and items.
+//!
+//! Every insert, update, and delete of a budget or item is recorded in
+//! `audit_event` by a trigger, in the same transaction as the change. The
+//! budget and item repositories make their changes in a transaction from
+//! [`begin`], which tells the trigger who made the change and in which request.
+
+pub mod dto;
+mod model;
+pub(crate) mod repository;
+
+use crate::text_type::text_type;
+use axum::{http::Request, middleware::Next, response::Response};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+use crate::error::REQUEST_ID_HEADER;
+
+tokio::task_local! {
+    /// The `x-request-id` of the request being handled.
+    static REQUEST_ID: Option<String>;
+}
+
+/// Middleware that makes the id of the request available to [`begin`] while
+/// the request is handled.
+pub async fn with_request_id<B>(request: Request<B>, next: Next<B>) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .map(|id| id.to_string());
+
+    REQUEST_ID.scope(request_id, next.run(request)).await
+}
+
+/// Begin a transaction in which the changes to budgets and items are recorded
+/// as made by `actor`, in the request being handled if there is one.
+pub(crate) async fn begin(
+    db_pool: &PgPool,
+    actor: &str,
+) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let request_id = REQUEST_ID.try_with(Clone::clone).ok().flatten();
+
+    let mut transaction = db_pool.begin().await?;
+    sqlx::query!(
+        r#"SELECT set_config('audit.actor', $1, true) as actor,
+    set_config('audit.request_id', $2, true) as request_id"#,
+        actor,
+        request_id.unwrap_or_default()
+    )
+    .fetch_one(&mut transaction)
+    .await?;
+
+    Ok(transaction)
+}
+
+/// What was changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Entity {
+    Budget,
+    Item,
+}
+
+impl Entity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Entity::Budget => "budget",
+            Entity::Item => "item",
+        }
+    }
+}
+
+impl FromStr for Entity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "budget" => Ok(Entity::Budget),
+            "item" => Ok(Entity::Item),
+            _ => Err(format!("Unknown entity '{value}'")),
+        }
+    }
+}
+
+text_type!(Entity);
+
+/// How it was changed. Deleting moves a budget or item to the trash, and
+/// restoring moves it back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+            Action::Restore => "restore",
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "create" => Ok(Action::Create),
+            "update" => Ok(Action::Update),
+            "delete" => Ok(Action::Delete),
+            "restore" => Ok(Action::Restore),
+            _ => Err(format!("Unknown action '{value}'")),
+        }
+    }
+}
+
+text_type!(Action);
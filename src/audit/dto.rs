@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::{model, Action, Entity};
+
+/// A change to a budget or one of its items.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub entity: Entity,
+    /// Id of the budget or item that was changed.
+    pub entity_id: Uuid,
+    pub action: Action,
+    /// The user who made the change.
+    pub actor: String,
+    /// The `x-request-id` of the request that made the change.
+    pub request_id: Option<String>,
+    /// The changed fields before the change, or `null` when created.
+    pub before: Option<Value>,
+    /// The changed fields after the change, or `null` when permanently deleted.
+    pub after: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&model::AuditEvent> for AuditEvent {
+    fn from(from: &model::AuditEvent) -> Self {
+        Self {
+            id: from.id,
+            entity: from.entity,
+            entity_id: from.entity_id,
+            action: from.action,
+            actor: from.actor.to_owned(),
+            request_id: from.request_id.to_owned(),
+            before: from.before.to_owned(),
+            after: from.after.to_owned(),
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+        }
+    }
+}
+
+/// Query parameters for the history of a budget, e.g. `?limit=10`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListHistory {
+    /// Number of events in a page, defaults to 20.
+    pub limit: Option<u32>,
+    /// The `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+}
@@ -0,0 +1,13 @@
+-- A budget of Alice that Bob can view, with three changes by Alice.
+INSERT INTO budget (id, user_id, title)
+VALUES ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Alice', 'Household');
+
+INSERT INTO budget_member (budget_id, user_id, role, invited_by, accepted_at)
+VALUES ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Bob', 'viewer', 'Alice', '2024-01-01 12:00:00');
+
+INSERT INTO audit_event (budget_id, entity, entity_id, action, actor, request_id, before, after, created_at)
+VALUES
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'budget', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'create', 'Alice', 'r-1', NULL, '{"title": "Home"}', '2024-01-01 12:00:00'),
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'item', '5e666f18-de95-4513-abd8-1f09ed5ff98f', 'create', 'Alice', 'r-2', NULL, '{"name": "Groceries"}', '2024-01-02 12:00:00'),
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'budget', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'update', 'Alice', 'r-3', '{"title": "Home"}', '{"title": "Household"}', '2024-01-03 12:00:00')
+;
@@ -0,0 +1,19 @@
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{Action, Entity};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub budget_id: Uuid,
+    pub entity: Entity,
+    pub entity_id: Uuid,
+    pub action: Action,
+    pub actor: String,
+    pub request_id: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{dto, model, Action, Entity};
+use crate::{
+    budget::member::role_in_budget,
+    error::ApiError,
+    pagination::{check_limit, decode_cursor, Page},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AuditRepositoryError {
+    Database,
+    NotFound,
+    Validation(String),
+}
+
+impl From<sqlx::Error> for AuditRepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        tracing::error!("Database error: {error:?}");
+        AuditRepositoryError::Database
+    }
+}
+
+impl From<AuditRepositoryError> for ApiError {
+    fn from(error: AuditRepositoryError) -> Self {
+        match error {
+            AuditRepositoryError::Database => ApiError::Internal,
+            AuditRepositoryError::NotFound => ApiError::NotFound("Budget not found".to_string()),
+            AuditRepositoryError::Validation(message) => ApiError::BadRequest(message),
+        }
+    }
+}
+
+/// Repository to read the audit log. The events are written by the database
+/// when budgets and items are changed in a transaction from [`begin`](super::begin).
+#[derive(Debug)]
+pub struct AuditRepository {
+    db_pool: Arc<PgPool>,
+}
+
+impl AuditRepository {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Get a page of the changes to a budget and its items, newest first,
+    /// for a user with any role in the budget.
+    pub async fn get_history(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        request: &dto::ListHistory,
+    ) -> Result<Page<model::AuditEvent>, AuditRepositoryError> {
+        let limit = check_limit(request.limit).map_err(AuditRepositoryError::Validation)?;
+        let cursor = request
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                decode_cursor::<i64>(cursor)
+                    .ok_or_else(|| AuditRepositoryError::Validation("Invalid cursor".to_string()))
+            })
+            .transpose()?;
+        if role_in_budget(self.db_pool.as_ref(), budget_id, user_id)
+            .await?
+            .is_none()
+        {
+            return Err(AuditRepositoryError::NotFound);
+        }
+
+        let events = sqlx::query_as!(
+            model::AuditEvent,
+            r#"SELECT id, budget_id, entity as "entity: Entity", entity_id, action as "action: Action",
+    actor, request_id, before, after, created_at
+FROM audit_event
+WHERE budget_id = $1 AND ($2::bigint IS NULL OR id < $2)
+ORDER BY id DESC
+LIMIT $3"#,
+            budget_id,
+            cursor,
+            i64::from(limit) + 1
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        Ok(Page::from_overfetched(events, limit, |last| last.id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::budget::{dto::PatchBudget, repository::BudgetRepository};
+    use crate::pagination::encode_cursor;
+
+    const HOUSEHOLD: &str = "b8d6ff4e-c12f-416b-a611-8ad0c90669fe";
+
+    fn id(id: &str) -> Uuid {
+        Uuid::parse_str(id).unwrap()
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn history_is_paged_newest_first(pool: PgPool) -> sqlx::Result<()> {
+        let repo = AuditRepository::new(Arc::new(pool));
+        let first = dto::ListHistory {
+            limit: Some(2),
+            cursor: None,
+        };
+
+        let page = repo
+            .get_history("Bob", &id(HOUSEHOLD), &first)
+            .await
+            .unwrap();
+        let next = dto::ListHistory {
+            limit: Some(2),
+            cursor: page.next_cursor.clone(),
+        };
+        let last = repo
+            .get_history("Bob", &id(HOUSEHOLD), &next)
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = page.items.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [3, 2]);
+        assert_eq!(page.next_cursor, Some(encode_cursor(&2)));
+        let ids: Vec<_> = last.items.iter().map(|e| e.id).collect();
+        assert_eq!(ids, [1]);
+        assert_eq!(last.next_cursor, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn history_of_other_budgets_is_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let repo = AuditRepository::new(Arc::new(pool));
+
+        let history = repo
+            .get_history("Mallory", &id(HOUSEHOLD), &Default::default())
+            .await;
+        let invalid = repo
+            .get_history(
+                "Alice",
+                &id(HOUSEHOLD),
+                &dto::ListHistory {
+                    limit: None,
+                    cursor: Some("not a cursor".to_string()),
+                },
+            )
+            .await;
+
+        assert_eq!(history, Err(AuditRepositoryError::NotFound));
+        assert!(matches!(invalid, Err(AuditRepositoryError::Validation(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("history"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn changes_are_recorded_with_only_the_changed_fields(pool: PgPool) -> sqlx::Result<()> {
+        let pool = Arc::new(pool);
+        let budgets = BudgetRepository::new(pool.clone());
+        let repo = AuditRepository::new(pool);
+        let patch = PatchBudget {
+            title: Some("Home".to_string()),
+            ..Default::default()
+        };
+
+        budgets
+            .patch_budget("Alice", &id(HOUSEHOLD), &patch, None)
+            .await
+            .unwrap();
+        let history = repo
+            .get_history("Alice", &id(HOUSEHOLD), &Default::default())
+            .await
+            .unwrap();
+
+        let event = &history.items[0];
+        assert_eq!(event.entity, Entity::Budget);
+        assert_eq!(event.entity_id, id(HOUSEHOLD));
+        assert_eq!(event.action, Action::Update);
+        assert_eq!(event.actor, "Alice");
+        assert_eq!(event.request_id, None);
+        assert_eq!(
+            event.before,
+            Some(serde_json::json!({ "title": "Household" }))
+        );
+        assert_eq!(event.after, Some(serde_json::json!({ "title": "Home" })));
+
+        Ok(())
+    }
+}
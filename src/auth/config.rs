@@ -1,39 +1,214 @@
+use super::scope::Scope;
 use derive_getters::Getters;
 use jsonwebtoken::{Algorithm, Validation};
+use std::{collections::HashMap, str::FromStr};
 
 pub(crate) static ISSUER: &str = "https://oliverflecke.eu.auth0.com/";
 pub(crate) static AUDIENCE: &str = "https://finance.oliverflecke.me/";
 
+/// Algorithms accepted if none are configured.
+const DEFAULT_ALGORITHMS: [Algorithm; 1] = [Algorithm::RS256];
+/// Leeway in seconds when validating `exp` and `nbf`, same as the default in `jsonwebtoken`.
+const DEFAULT_LEEWAY: u64 = 60;
+
 #[derive(Debug, Getters, Clone)]
 pub struct AuthConfig {
-    issuer: String,
+    /// Issuers that are trusted to sign tokens. Each issuer's keys are located
+    /// with OpenID Connect discovery.
+    issuers: Vec<String>,
     audience: String,
+    /// Signing algorithms accepted for tokens.
+    algorithms: Vec<Algorithm>,
+    /// Clock skew in seconds allowed when validating `exp` and `nbf`.
+    leeway: u64,
+    /// Whether to reject tokens that are not valid yet, according to their `nbf` claim.
+    validate_nbf: bool,
+    /// Names of the scopes in the tokens. Only contains the scopes that are renamed.
+    scopes: HashMap<Scope, String>,
 }
 
 impl AuthConfig {
+    /// Configuration trusting `issuers` to sign tokens for `audience`, with
+    /// default values for everything else.
+    pub fn new(issuers: Vec<String>, audience: String) -> Self {
+        Self {
+            issuers,
+            audience,
+            ..Default::default()
+        }
+    }
+
+    /// Read the configuration from the environment.
+    ///
+    /// - `ISSUER`: comma separated list of trusted issuers.
+    /// - `AUDIENCE`: the audience tokens must be issued for.
+    /// - `AUTH_ALGORITHMS`: comma separated list of accepted algorithms. Defaults to `RS256`.
+    /// - `AUTH_LEEWAY`: allowed clock skew in seconds. Defaults to 60 seconds.
+    /// - `AUTH_VALIDATE_NBF`: whether to validate the `nbf` claim. Defaults to `true`.
+    /// - `SCOPE_*`: rename a scope, e.g. `SCOPE_BUDGET_READ=read:budgets`. See [`Scope::env_var`].
     pub fn from_env() -> Self {
+        let issuers = parse_list(&std::env::var("ISSUER").expect("variable 'ISSUER' to be set"));
+        assert!(!issuers.is_empty(), "variable 'ISSUER' to not be empty");
+
+        let algorithms = std::env::var("AUTH_ALGORITHMS")
+            .map(|algorithms| {
+                parse_algorithms(&algorithms)
+                    .expect("variable 'AUTH_ALGORITHMS' to only contain supported algorithms")
+            })
+            .unwrap_or_else(|_| DEFAULT_ALGORITHMS.to_vec());
+        let leeway = std::env::var("AUTH_LEEWAY")
+            .map(|leeway| {
+                leeway
+                    .parse()
+                    .expect("variable 'AUTH_LEEWAY' to be a number of seconds")
+            })
+            .unwrap_or(DEFAULT_LEEWAY);
+        let validate_nbf = std::env::var("AUTH_VALIDATE_NBF")
+            .map(|validate| {
+                validate
+                    .parse()
+                    .expect("variable 'AUTH_VALIDATE_NBF' to be 'true' or 'false'")
+            })
+            .unwrap_or(true);
+        let scopes = Scope::ALL
+            .iter()
+            .filter_map(|scope| Some((*scope, std::env::var(scope.env_var()).ok()?)))
+            .collect();
+
         Self {
-            issuer: std::env::var("ISSUER").expect("variable 'ISSUER' to be set"),
+            issuers,
             audience: std::env::var("AUDIENCE").expect("variable 'AUDIENCE' to be set"),
+            algorithms,
+            leeway,
+            validate_nbf,
+            scopes,
         }
     }
+
+    /// Name of the scope in the tokens.
+    pub fn scope_name(&self, scope: Scope) -> &str {
+        self.scopes
+            .get(&scope)
+            .map(String::as_str)
+            .unwrap_or_else(|| scope.default_name())
+    }
 }
 
 impl Default for AuthConfig {
     fn default() -> Self {
         Self {
-            issuer: ISSUER.to_string(),
+            issuers: vec![ISSUER.to_string()],
             audience: AUDIENCE.to_string(),
+            algorithms: DEFAULT_ALGORITHMS.to_vec(),
+            leeway: DEFAULT_LEEWAY,
+            validate_nbf: true,
+            scopes: HashMap::new(),
         }
     }
 }
 
 impl From<&AuthConfig> for Validation {
     fn from(value: &AuthConfig) -> Self {
-        let mut validation = Validation::new(Algorithm::RS256);
-        validation.set_issuer(&[value.issuer()]);
+        let mut validation = Validation::new(value.algorithms[0]);
+        validation.algorithms = value.algorithms.clone();
+        validation.set_issuer(value.issuers());
         validation.set_audience(&[value.audience()]);
+        validation.leeway = value.leeway;
+        validation.validate_nbf = value.validate_nbf;
 
         validation
     }
 }
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parse a comma separated list of algorithms. Only asymmetric algorithms are
+/// supported, as the keys are fetched from the issuers.
+fn parse_algorithms(value: &str) -> Result<Vec<Algorithm>, String> {
+    let algorithms = parse_list(value)
+        .iter()
+        .map(|name| match Algorithm::from_str(name) {
+            Ok(Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) | Err(_) => {
+                Err(format!("Unsupported algorithm '{name}'"))
+            }
+            Ok(algorithm) => Ok(algorithm),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match algorithms.is_empty() {
+        true => Err("No algorithms configured".to_string()),
+        false => Ok(algorithms),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_list_of_issuers() {
+        assert_eq!(
+            parse_list("https://a.example.com/, https://b.example.com/realms/budget,"),
+            vec![
+                "https://a.example.com/".to_string(),
+                "https://b.example.com/realms/budget".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_supported_algorithms() {
+        assert_eq!(
+            parse_algorithms("RS256,RS384, ES256,EdDSA"),
+            Ok(vec![
+                Algorithm::RS256,
+                Algorithm::RS384,
+                Algorithm::ES256,
+                Algorithm::EdDSA
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_algorithms_rejects_symmetric_and_unknown_algorithms() {
+        assert!(parse_algorithms("RS256,HS256").is_err());
+        assert!(parse_algorithms("XY123").is_err());
+        assert!(parse_algorithms("").is_err());
+    }
+
+    #[test]
+    fn scope_names_can_be_renamed() {
+        let config = AuthConfig {
+            scopes: HashMap::from([(Scope::BudgetRead, "read:budgets".to_string())]),
+            ..Default::default()
+        };
+
+        assert_eq!(config.scope_name(Scope::BudgetRead), "read:budgets");
+        assert_eq!(config.scope_name(Scope::BudgetWrite), "budget:write");
+    }
+
+    #[test]
+    fn validation_from_config() {
+        let config = AuthConfig {
+            issuers: vec!["a".to_string(), "b".to_string()],
+            algorithms: vec![Algorithm::ES256, Algorithm::EdDSA],
+            leeway: 5,
+            validate_nbf: false,
+            ..Default::default()
+        };
+
+        let validation = Validation::from(&config);
+
+        assert_eq!(validation.algorithms, config.algorithms);
+        assert_eq!(validation.leeway, 5);
+        assert!(!validation.validate_nbf);
+        assert_eq!(validation.iss.unwrap().len(), 2);
+    }
+}
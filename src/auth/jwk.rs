@@ -1,62 +1,162 @@
 use super::config::AuthConfig;
 use derive_getters::Getters;
-use jsonwebtoken::DecodingKey;
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey};
+use reqwest::header::CACHE_CONTROL;
 use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, RwLock, Weak},
+    time::{Duration, Instant},
+};
+use tokio::{sync::Mutex, task::JoinHandle};
 
+static DISCOVERY_ENDPOINT: &str = ".well-known/openid-configuration";
+/// Used if the issuer does not support OpenID Connect discovery.
 static JWKS_ENDPOINT: &str = ".well-known/jwks.json";
 
+/// How often keys are refreshed if the authority does not specify a `max-age`.
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Lower bound on how often the keys are refreshed in the background.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// Minimum time between two refreshes triggered by an unknown key id.
+const MIN_ON_DEMAND_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The part of the OpenID Connect discovery document we need.
+#[derive(Debug, Deserialize)]
+struct OpenIdConfiguration {
+    jwks_uri: String,
+}
+
+impl OpenIdConfiguration {
+    async fn fetch(issuer: &str) -> anyhow::Result<OpenIdConfiguration, reqwest::Error> {
+        reqwest::get(well_known_url(issuer, DISCOVERY_ENDPOINT))
+            .await?
+            .error_for_status()?
+            .json::<OpenIdConfiguration>()
+            .await
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct JwksResponse {
     keys: Vec<Jwk>,
 }
 
 impl JwksResponse {
-    /// Fetch JWKS keys from the and endpoint
-    async fn fetch(auth_server: &str) -> anyhow::Result<JwksResponse, reqwest::Error> {
-        reqwest::get(format!("{auth_server}{JWKS_ENDPOINT}"))
-            .await?
-            .json::<JwksResponse>()
-            .await
+    /// Fetch JWKS keys from the and endpoint, along with the `max-age` the
+    /// authority allows them to be cached for.
+    async fn fetch(
+        jwks_uri: &str,
+    ) -> anyhow::Result<(JwksResponse, Option<Duration>), reqwest::Error> {
+        let response = reqwest::get(jwks_uri).await?.error_for_status()?;
+        let max_age = response
+            .headers()
+            .get(CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_max_age);
+
+        Ok((response.json::<JwksResponse>().await?, max_age))
     }
 }
 
+/// Build the url of a well-known endpoint of an issuer, which might not end with a `/`.
+fn well_known_url(issuer: &str, endpoint: &str) -> String {
+    format!("{}/{endpoint}", issuer.trim_end_matches('/'))
+}
+
+/// Parse the `max-age` directive from a `Cache-Control` header.
+fn parse_max_age(cache_control: &str) -> Option<Duration> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().split_once('='))
+        .find(|(name, _)| name.eq_ignore_ascii_case("max-age"))
+        .and_then(|(_, value)| value.trim_matches('"').parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// A public key in the JWK format. Supports RSA, elliptic curve (`EC`), and
+/// Edwards curve (`OKP`) keys.
 #[derive(Debug, Clone, Deserialize, Getters, PartialEq, Eq)]
 pub struct Jwk {
-    n: String,
-    e: String,
+    kty: String,
+    kid: Option<String>,
+    alg: Option<String>,
+    #[serde(rename = "use")]
+    key_use: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    crv: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
 }
 
-impl From<Jwk> for DecodingKey {
-    fn from(value: Jwk) -> Self {
-        DecodingKey::from_rsa_components(value.n(), value.e()).unwrap()
+impl Jwk {
+    /// Whether this key can be used to verify a token signed with `algorithm`.
+    fn can_verify(&self, algorithm: Algorithm) -> bool {
+        use Algorithm::*;
+
+        let kty_matches = match algorithm {
+            RS256 | RS384 | RS512 | PS256 | PS384 | PS512 => self.kty == "RSA",
+            ES256 | ES384 => self.kty == "EC",
+            EdDSA => self.kty == "OKP",
+            HS256 | HS384 | HS512 => false,
+        };
+        let use_matches = self.key_use.as_deref().unwrap_or("sig") == "sig";
+        let alg_matches = match self.alg.as_deref() {
+            Some(alg) => Algorithm::from_str(alg) == Ok(algorithm),
+            None => true,
+        };
+
+        kty_matches && use_matches && alg_matches
+    }
+}
+
+impl TryFrom<Jwk> for DecodingKey {
+    type Error = jsonwebtoken::errors::Error;
+
+    fn try_from(value: Jwk) -> Result<Self, Self::Error> {
+        match (value.kty.as_str(), value.n, value.e, value.x, value.y) {
+            ("RSA", Some(n), Some(e), _, _) => DecodingKey::from_rsa_components(&n, &e),
+            ("EC", _, _, Some(x), Some(y)) => DecodingKey::from_ec_components(&x, &y),
+            ("OKP", _, _, Some(x), _) => DecodingKey::from_ed_components(&x),
+            _ => Err(ErrorKind::InvalidKeyFormat.into()),
+        }
     }
 }
 
 /// Represents a repository for storing and managing JWKs locally.
-/// This includes fetching them from a remote authority and updating them reguarly.
-#[derive(Debug, Clone)]
+/// This includes fetching them from the trusted authorities and updating them reguarly.
+#[derive(Debug)]
 pub struct JwkRepository {
     auth_config: AuthConfig,
-    keys: Vec<Jwk>,
+    /// Keys for each of the trusted issuers.
+    issuers: HashMap<String, Arc<IssuerKeys>>,
 }
 
 impl From<AuthConfig> for JwkRepository {
     fn from(auth_config: AuthConfig) -> Self {
+        let issuers = auth_config
+            .issuers()
+            .iter()
+            .map(|issuer| (issuer.to_owned(), Arc::new(IssuerKeys::new(issuer))))
+            .collect();
+
         Self {
             auth_config,
-            keys: vec![],
+            issuers,
         }
     }
 }
 
 impl JwkRepository {
     pub async fn new(auth_config: AuthConfig) -> anyhow::Result<Self> {
-        let jwks = JwksResponse::fetch(auth_config.issuer()).await?;
+        let repository = Self::from(auth_config);
+        for issuer in repository.issuers.values() {
+            issuer.update_keys().await?;
+        }
 
-        Ok(Self {
-            auth_config,
-            keys: jwks.keys,
-        })
+        Ok(repository)
     }
 
     /// Get the configuration of the authority that JWKs are fetched from.
@@ -64,53 +164,388 @@ impl JwkRepository {
         &self.auth_config
     }
 
-    /// Get the current JWK to use.
-    pub fn get_key(&self) -> Option<Jwk> {
-        self.keys.first().cloned()
+    /// Get the key from `issuer` to verify a token signed with `algorithm` by the key with `kid`.
+    ///
+    /// Tokens without a key id can only be verified if the issuer has a single
+    /// key that can be used for the algorithm.
+    pub fn get_key(&self, issuer: &str, kid: Option<&str>, algorithm: Algorithm) -> Option<Jwk> {
+        self.issuers.get(issuer)?.get_key(kid, algorithm)
+    }
+
+    /// Get the key with the given `kid`. If it is not known, the keys of the issuer
+    /// are refreshed, unless this was done very recently.
+    pub async fn get_key_with_refresh(
+        &self,
+        issuer: &str,
+        kid: Option<&str>,
+        algorithm: Algorithm,
+    ) -> Option<Jwk> {
+        self.issuers
+            .get(issuer)?
+            .get_key_with_refresh(kid, algorithm)
+            .await
     }
 
-    /// Refresh JWKs and returns the current one.
-    pub async fn get_key_with_refresh(&mut self) -> anyhow::Result<Jwk> {
-        // TODO: Should be update at a certain frequency.
-        if self.keys.is_empty() {
-            self.update_keys().await?;
+    /// Spawn a task for each issuer, which keeps its keys updated in the background,
+    /// honoring the `max-age` returned by the issuer. The tasks stop once the repository is dropped.
+    pub fn spawn_refresh(&self) -> Vec<JoinHandle<()>> {
+        self.issuers
+            .values()
+            .map(IssuerKeys::spawn_refresh)
+            .collect()
+    }
+}
+
+/// The keys of a single trusted issuer.
+#[derive(Debug)]
+struct IssuerKeys {
+    issuer: String,
+    keys: RwLock<Keys>,
+    /// Held while refreshing, so concurrent requests with an unknown key id
+    /// only triggers a single fetch.
+    refresh_state: Mutex<RefreshState>,
+}
+
+#[derive(Debug, Default)]
+struct Keys {
+    /// Keys indexed by their key id (`kid`).
+    by_id: HashMap<String, Jwk>,
+    /// Keys without a key id, which can only be used for tokens without one.
+    without_id: Vec<Jwk>,
+}
+
+#[derive(Debug, Default)]
+struct RefreshState {
+    /// Location of the keys, found through discovery.
+    jwks_uri: Option<String>,
+    /// When the keys were last fetched.
+    fetched_at: Option<Instant>,
+    /// How long the authority allows the keys to be cached.
+    max_age: Option<Duration>,
+}
+
+impl RefreshState {
+    fn next_refresh_in(&self) -> Duration {
+        self.max_age
+            .unwrap_or(DEFAULT_REFRESH_INTERVAL)
+            .max(MIN_REFRESH_INTERVAL)
+    }
+}
+
+impl IssuerKeys {
+    fn new(issuer: &str) -> Self {
+        Self {
+            issuer: issuer.to_owned(),
+            keys: RwLock::default(),
+            refresh_state: Mutex::default(),
         }
+    }
 
-        Ok(self.get_key().expect("a key to have been fetched"))
+    fn get_key(&self, kid: Option<&str>, algorithm: Algorithm) -> Option<Jwk> {
+        let keys = self.keys.read().expect("lock to not be poisoned");
+        match kid {
+            Some(kid) => keys
+                .by_id
+                .get(kid)
+                .filter(|k| k.can_verify(algorithm))
+                .cloned(),
+            None => {
+                let mut candidates = keys
+                    .by_id
+                    .values()
+                    .chain(&keys.without_id)
+                    .filter(|k| k.can_verify(algorithm));
+                match (candidates.next(), candidates.next()) {
+                    (Some(key), None) => Some(key.clone()),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    async fn get_key_with_refresh(&self, kid: Option<&str>, algorithm: Algorithm) -> Option<Jwk> {
+        if let Some(key) = self.get_key(kid, algorithm) {
+            return Some(key);
+        }
+
+        let mut state = self.refresh_state.lock().await;
+        // Another request might have refreshed the keys while waiting for the lock.
+        if let Some(key) = self.get_key(kid, algorithm) {
+            return Some(key);
+        }
+        if state
+            .fetched_at
+            .is_some_and(|at| at.elapsed() < MIN_ON_DEMAND_REFRESH_INTERVAL)
+        {
+            tracing::debug!("Unknown key id {kid:?}, but keys were refreshed recently");
+            return None;
+        }
+
+        tracing::info!(
+            "Unknown key id {kid:?} for '{}', refreshing keys",
+            self.issuer
+        );
+        if let Err(err) = self.fetch_and_store(&mut state).await {
+            tracing::error!("Unable to refresh JWKs for '{}': {err:?}", self.issuer);
+        }
+
+        self.get_key(kid, algorithm)
+    }
+
+    fn spawn_refresh(self: &Arc<Self>) -> JoinHandle<()> {
+        let issuer: Weak<Self> = Arc::downgrade(self);
+
+        tokio::spawn(async move {
+            while let Some(current) = issuer.upgrade() {
+                let interval = current.refresh_state.lock().await.next_refresh_in();
+                // Don't keep the keys alive while sleeping.
+                drop(current);
+
+                tokio::time::sleep(interval).await;
+                let Some(issuer) = issuer.upgrade() else {
+                    break;
+                };
+
+                let mut state = issuer.refresh_state.lock().await;
+                if let Err(err) = issuer.fetch_and_store(&mut state).await {
+                    tracing::error!("Unable to refresh JWKs for '{}': {err:?}", issuer.issuer);
+                }
+            }
+        })
     }
 
     /// Updates the internal, local storage of the JWKs.
-    async fn update_keys(&mut self) -> anyhow::Result<()> {
-        tracing::trace!("Fetching jwk from identity host");
-        self.keys = JwksResponse::fetch(self.auth_config.issuer()).await?.keys;
+    async fn update_keys(&self) -> anyhow::Result<()> {
+        let mut state = self.refresh_state.lock().await;
+        self.fetch_and_store(&mut state).await
+    }
+
+    /// Fetch the keys from the issuer and replace the ones currently stored.
+    /// Requires the refresh lock to be held.
+    async fn fetch_and_store(&self, state: &mut RefreshState) -> anyhow::Result<()> {
+        tracing::trace!("Fetching jwk from '{}'", self.issuer);
+        // Also counts failed attempts, so an unavailable authority is not hammered.
+        state.fetched_at = Some(Instant::now());
+
+        let jwks_uri = match &state.jwks_uri {
+            Some(jwks_uri) => jwks_uri.to_owned(),
+            None => match OpenIdConfiguration::fetch(&self.issuer).await {
+                Ok(configuration) => {
+                    state.jwks_uri = Some(configuration.jwks_uri.clone());
+                    configuration.jwks_uri
+                }
+                Err(err) => {
+                    tracing::warn!("Discovery failed for '{}': {err:?}", self.issuer);
+                    well_known_url(&self.issuer, JWKS_ENDPOINT)
+                }
+            },
+        };
+
+        let (jwks, max_age) = JwksResponse::fetch(&jwks_uri).await?;
+        state.max_age = max_age;
+        self.store_keys(jwks.keys);
 
         Ok(())
     }
+
+    fn store_keys(&self, jwks: Vec<Jwk>) {
+        let mut keys = Keys::default();
+        for key in jwks {
+            match key.kid.clone() {
+                Some(kid) => {
+                    keys.by_id.insert(kid, key);
+                }
+                None => keys.without_id.push(key),
+            }
+        }
+
+        *self.keys.write().expect("lock to not be poisoned") = keys;
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::auth::config::AuthConfig;
+    use crate::auth::{
+        config::{AuthConfig, ISSUER},
+        test_utils::{TestIdentityProvider, KEY_ID},
+    };
 
     use super::*;
 
+    fn jwk(kid: &str, alg: Option<&str>, key_use: Option<&str>) -> Jwk {
+        Jwk {
+            kid: Some(kid.to_string()),
+            ..jwk_without_kid(alg, key_use)
+        }
+    }
+
+    fn jwk_without_kid(alg: Option<&str>, key_use: Option<&str>) -> Jwk {
+        Jwk {
+            kty: "RSA".to_string(),
+            kid: None,
+            alg: alg.map(str::to_string),
+            key_use: key_use.map(str::to_string),
+            n: Some("n".to_string()),
+            e: Some("AQAB".to_string()),
+            crv: None,
+            x: None,
+            y: None,
+        }
+    }
+
+    #[test]
+    fn parse_max_age_from_cache_control() {
+        assert_eq!(
+            parse_max_age("public, max-age=15000, must-revalidate"),
+            Some(Duration::from_secs(15000))
+        );
+        assert_eq!(parse_max_age("no-cache"), None);
+        assert_eq!(parse_max_age("max-age=abc"), None);
+    }
+
+    #[test]
+    fn well_known_url_with_and_without_trailing_slash() {
+        assert_eq!(
+            well_known_url("https://example.com/", DISCOVERY_ENDPOINT),
+            "https://example.com/.well-known/openid-configuration"
+        );
+        assert_eq!(
+            well_known_url("https://example.com/realms/budget", JWKS_ENDPOINT),
+            "https://example.com/realms/budget/.well-known/jwks.json"
+        );
+    }
+
+    #[test]
+    fn refresh_interval_is_bounded() {
+        let state = RefreshState {
+            max_age: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        assert_eq!(state.next_refresh_in(), MIN_REFRESH_INTERVAL);
+        assert_eq!(
+            RefreshState::default().next_refresh_in(),
+            DEFAULT_REFRESH_INTERVAL
+        );
+    }
+
+    #[test]
+    fn select_key_by_kid() {
+        let issuer = IssuerKeys::new(ISSUER);
+        issuer.store_keys(vec![
+            jwk("first", Some("RS256"), Some("sig")),
+            jwk("second", Some("RS256"), Some("sig")),
+        ]);
+
+        let key = issuer.get_key(Some("second"), Algorithm::RS256);
+
+        assert_eq!(key.unwrap().kid.as_deref(), Some("second"));
+        assert_eq!(issuer.get_key(Some("third"), Algorithm::RS256), None);
+    }
+
+    #[test]
+    fn select_key_ignores_keys_for_other_algorithms_and_uses() {
+        let issuer = IssuerKeys::new(ISSUER);
+        issuer.store_keys(vec![
+            jwk("rs384", Some("RS384"), Some("sig")),
+            jwk("enc", None, Some("enc")),
+        ]);
+
+        assert_eq!(issuer.get_key(Some("rs384"), Algorithm::RS256), None);
+        assert_eq!(issuer.get_key(Some("enc"), Algorithm::RS256), None);
+        // An RSA key can never be used for elliptic curve algorithms.
+        assert_eq!(issuer.get_key(Some("rs384"), Algorithm::ES256), None);
+    }
+
+    #[test]
+    fn select_key_without_kid_requires_a_single_candidate() {
+        let issuer = IssuerKeys::new(ISSUER);
+        issuer.store_keys(vec![jwk("first", None, None)]);
+        assert!(issuer.get_key(None, Algorithm::RS256).is_some());
+
+        issuer.store_keys(vec![jwk("first", None, None), jwk("second", None, None)]);
+        assert_eq!(issuer.get_key(None, Algorithm::RS256), None);
+    }
+
+    #[test]
+    fn keys_without_kid_are_only_used_for_tokens_without_kid() {
+        let issuer = IssuerKeys::new(ISSUER);
+        issuer.store_keys(vec![jwk_without_kid(None, None)]);
+        assert!(issuer.get_key(None, Algorithm::RS256).is_some());
+
+        issuer.store_keys(vec![
+            jwk_without_kid(None, None),
+            jwk("first", Some("RS384"), None),
+        ]);
+        assert!(issuer.get_key(None, Algorithm::RS256).is_some());
+        assert_eq!(issuer.get_key(Some("other"), Algorithm::RS256), None);
+
+        issuer.store_keys(vec![jwk_without_kid(None, None), jwk("first", None, None)]);
+        assert_eq!(issuer.get_key(None, Algorithm::RS256), None);
+    }
+
+    #[test]
+    fn keys_from_untrusted_issuers_are_never_returned() {
+        let repository = JwkRepository::from(AuthConfig::default());
+        repository.issuers[ISSUER].store_keys(vec![jwk("first", None, None)]);
+
+        assert!(repository
+            .get_key(ISSUER, Some("first"), Algorithm::RS256)
+            .is_some());
+        assert_eq!(
+            repository.get_key("https://evil.example.com/", Some("first"), Algorithm::RS256),
+            None
+        );
+    }
+
+    #[test]
+    fn decoding_key_requires_parameters_for_key_type() {
+        let mut key = jwk("first", None, None);
+        key.kty = "EC".to_string();
+
+        assert!(DecodingKey::try_from(key).is_err());
+    }
+
     #[tokio::test]
     async fn repository_update_keys() {
-        let mut repository = JwkRepository::from(AuthConfig::default());
+        let identity_provider = TestIdentityProvider::spawn().await;
+        let issuer = IssuerKeys::new(identity_provider.issuer());
 
-        repository.update_keys().await.expect("update to work");
+        issuer.update_keys().await.expect("update to work");
 
-        let jwk = repository.get_key().expect("key to be there");
-        assert_eq!(jwk.e, "AQAB");
-        assert_ne!(jwk.n, ""); // Don't want to test the exact value of the key here, so it's enough to just verify that its not empty.
+        let keys = issuer.keys.read().unwrap();
+        let jwk = keys.by_id.get(KEY_ID).expect("key to be there");
+        assert_eq!(jwk.e.as_deref(), Some("AQAB"));
+        assert_ne!(jwk.n, None); // Don't want to test the exact value of the key here, so it's enough to just verify that its there.
+    }
+
+    #[tokio::test]
+    async fn repository_update_keys_uses_discovery_and_max_age() {
+        let identity_provider = TestIdentityProvider::spawn().await;
+        let issuer = IssuerKeys::new(identity_provider.issuer());
+
+        issuer.update_keys().await.expect("update to work");
+
+        let state = issuer.refresh_state.lock().await;
+        assert_eq!(
+            state.jwks_uri.as_deref(),
+            Some(well_known_url(identity_provider.issuer(), JWKS_ENDPOINT).as_str())
+        );
+        assert_eq!(state.max_age, Some(Duration::from_secs(300)));
     }
 
     #[tokio::test]
     async fn repository_fetch_on_create() {
-        let repository = JwkRepository::new(AuthConfig::default())
+        let identity_provider = TestIdentityProvider::spawn().await;
+
+        let repository = JwkRepository::new(identity_provider.auth_config())
             .await
             .expect("to be able to create the repository");
 
-        assert_ne!(repository.get_key(), None);
+        assert!(!repository.issuers[identity_provider.issuer()]
+            .keys
+            .read()
+            .unwrap()
+            .by_id
+            .is_empty());
     }
 }
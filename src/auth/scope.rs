@@ -0,0 +1,118 @@
+use super::Claims;
+use crate::{app_state::AppState, error::ApiError};
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use duplicate::duplicate;
+use std::{marker::PhantomData, ops::Deref};
+
+/// Permissions that can be required to access a route.
+///
+/// The name of the scope in the token can be configured per deployment,
+/// see [`AuthConfig`](super::config::AuthConfig).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    BudgetRead,
+    BudgetWrite,
+    ItemWrite,
+    CategoryRead,
+    CategoryWrite,
+    TransactionWrite,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 6] = [
+        Scope::BudgetRead,
+        Scope::BudgetWrite,
+        Scope::ItemWrite,
+        Scope::CategoryRead,
+        Scope::CategoryWrite,
+        Scope::TransactionWrite,
+    ];
+
+    /// Name of the scope used if nothing else is configured.
+    pub fn default_name(&self) -> &'static str {
+        match self {
+            Scope::BudgetRead => "budget:read",
+            Scope::BudgetWrite => "budget:write",
+            Scope::ItemWrite => "item:write",
+            Scope::CategoryRead => "category:read",
+            Scope::CategoryWrite => "category:write",
+            Scope::TransactionWrite => "transaction:write",
+        }
+    }
+
+    /// Environment variable that can be used to rename the scope.
+    pub fn env_var(&self) -> &'static str {
+        match self {
+            Scope::BudgetRead => "SCOPE_BUDGET_READ",
+            Scope::BudgetWrite => "SCOPE_BUDGET_WRITE",
+            Scope::ItemWrite => "SCOPE_ITEM_WRITE",
+            Scope::CategoryRead => "SCOPE_CATEGORY_READ",
+            Scope::CategoryWrite => "SCOPE_CATEGORY_WRITE",
+            Scope::TransactionWrite => "SCOPE_TRANSACTION_WRITE",
+        }
+    }
+}
+
+/// Type level representation of a [`Scope`], used to declare the scope
+/// required by a handler with [`Authorized`].
+pub trait RequiredScope: Send + Sync {
+    const SCOPE: Scope;
+}
+
+duplicate! {
+    [
+        marker               scope;
+        [ BudgetRead ]       [ Scope::BudgetRead ];
+        [ BudgetWrite ]      [ Scope::BudgetWrite ];
+        [ ItemWrite ]        [ Scope::ItemWrite ];
+        [ CategoryRead ]     [ Scope::CategoryRead ];
+        [ CategoryWrite ]    [ Scope::CategoryWrite ];
+        [ TransactionWrite ] [ Scope::TransactionWrite ];
+    ]
+    #[derive(Debug)]
+    pub struct marker;
+
+    impl RequiredScope for marker {
+        const SCOPE: Scope = scope;
+    }
+}
+
+/// Claims of the current user, which are verified to have the scope `S`.
+///
+/// If the token does not have the scope, the request is rejected with a
+/// `FORBIDDEN` status code, naming the missing scope.
+#[derive(Debug)]
+pub struct Authorized<S>(Claims, PhantomData<S>);
+
+impl<S> Deref for Authorized<S> {
+    type Target = Claims;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl<S: RequiredScope> FromRequestParts<AppState> for Authorized<S> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+        let scope = state
+            .jwks_repository()
+            .get_auth_config()
+            .scope_name(S::SCOPE);
+
+        if !claims.has_scope(scope) {
+            tracing::warn!("User '{}' is missing the scope '{scope}'", claims.user_id());
+            return Err(ApiError::Forbidden(format!(
+                "Missing required scope '{scope}'"
+            )));
+        }
+
+        Ok(Self(claims, PhantomData))
+    }
+}
@@ -0,0 +1,143 @@
+//! A local identity provider, which allows testing authentication without network access.
+//!
+//! It serves the OpenID Connect discovery document and the JWKS of a static test key
+//! on a random local port, and signs tokens with that key.
+
+use super::{
+    config::{AuthConfig, AUDIENCE},
+    jwk::JwkRepository,
+    scope::Scope,
+    Claims,
+};
+use axum::{http::header::CACHE_CONTROL, routing::get, Json, Router, Server};
+use jsonwebtoken::{encode, get_current_timestamp, Algorithm, EncodingKey, Header};
+use std::net::TcpListener;
+
+static TRUSTED_KEY: &[u8] = include_bytes!("fixtures/test_key.pem");
+/// Key that is not published by the identity provider, used to create tokens with bad signatures.
+static UNTRUSTED_KEY: &[u8] = include_bytes!("fixtures/untrusted_key.pem");
+static JWKS: &str = include_str!("fixtures/jwks.json");
+pub static KEY_ID: &str = "test-key";
+
+#[derive(Debug, Clone)]
+pub struct TestIdentityProvider {
+    issuer: String,
+}
+
+impl TestIdentityProvider {
+    /// Start the identity provider on a random local port.
+    pub async fn spawn() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind address");
+        let issuer = format!("http://{}/", listener.local_addr().unwrap());
+
+        let discovery = serde_json::json!({
+            "issuer": issuer,
+            "jwks_uri": format!("{issuer}.well-known/jwks.json"),
+        });
+        let router = Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                get(|| async { Json(discovery) }),
+            )
+            .route(
+                "/.well-known/jwks.json",
+                get(|| async { ([(CACHE_CONTROL, "max-age=300")], JWKS) }),
+            );
+        let server = Server::from_tcp(listener)
+            .expect("Failed to create server")
+            .serve(router.into_make_service());
+        tokio::spawn(server);
+
+        Self { issuer }
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Configuration which only trusts this identity provider.
+    pub fn auth_config(&self) -> AuthConfig {
+        AuthConfig::new(vec![self.issuer.clone()], AUDIENCE.to_string())
+    }
+
+    /// Repository with the keys fetched from this identity provider.
+    pub async fn jwks_repository(&self) -> JwkRepository {
+        JwkRepository::new(self.auth_config())
+            .await
+            .expect("keys to be fetched from the test identity provider")
+    }
+
+    /// Start building a token for `user_id`, which by default is valid for an
+    /// hour and has all the scopes used by the application.
+    pub fn token(&self, user_id: &str) -> TokenBuilder {
+        let now = get_current_timestamp() as usize;
+        let scope = Scope::ALL
+            .iter()
+            .map(Scope::default_name)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        TokenBuilder {
+            claims: Claims {
+                aud: vec![AUDIENCE.to_string()],
+                exp: now + 60 * 60,
+                iat: now,
+                iss: self.issuer.clone(),
+                sub: user_id.to_string(),
+                scope,
+            },
+            key: TRUSTED_KEY,
+        }
+    }
+}
+
+/// Builder for signed tokens. Created with [`TestIdentityProvider::token`].
+#[derive(Debug, Clone)]
+pub struct TokenBuilder {
+    claims: Claims,
+    key: &'static [u8],
+}
+
+impl TokenBuilder {
+    /// Space separated list of scopes in the token.
+    pub fn scope(mut self, scope: &str) -> Self {
+        self.claims.scope = scope.to_string();
+        self
+    }
+
+    pub fn audience(mut self, audience: &str) -> Self {
+        self.claims.aud = vec![audience.to_string()];
+        self
+    }
+
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        self.claims.iss = issuer.to_string();
+        self
+    }
+
+    /// Seconds since the epoch when the token expires.
+    pub fn expires_at(mut self, exp: usize) -> Self {
+        self.claims.exp = exp;
+        self
+    }
+
+    /// Make the token expire well beyond the allowed leeway.
+    pub fn expired(self) -> Self {
+        let exp = get_current_timestamp() as usize - 60 * 60;
+        self.expires_at(exp)
+    }
+
+    /// Sign the token with a key that is not trusted by the identity provider.
+    pub fn untrusted_signature(mut self) -> Self {
+        self.key = UNTRUSTED_KEY;
+        self
+    }
+
+    pub fn sign(self) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(KEY_ID.to_string());
+        let key = EncodingKey::from_rsa_pem(self.key).expect("test key to be valid");
+
+        encode(&header, &self.claims, &key).expect("token to be signed")
+    }
+}
@@ -1,41 +1,151 @@
-mod dto;
+pub mod dto;
+pub mod export;
+mod history;
+pub mod import;
 pub(crate) mod item_repository;
+pub mod member;
+pub(crate) mod member_repository;
 mod model;
+pub mod period;
 pub(crate) mod repository;
+pub mod schedule;
+pub(crate) mod transaction_repository;
 
-use crate::app_state::AppState;
+use crate::{app_state::AppState, pagination::SortOrder};
 use axum::{
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
     Router,
 };
+use export::ExportFormat;
+use utoipa::OpenApi;
+
+/// Documentation of the routes in [`create_router`].
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        endpoints::get_all_budgets,
+        endpoints::create_budget,
+        endpoints::get_invitations,
+        endpoints::export_budgets,
+        endpoints::get_budget,
+        endpoints::update_budget,
+        endpoints::patch_budget,
+        endpoints::delete_budget,
+        endpoints::restore_budget,
+        endpoints::revert_budget,
+        endpoints::get_history,
+        endpoints::get_budget_summary,
+        endpoints::get_budget_progress,
+        endpoints::get_occurrences,
+        endpoints::export_budget,
+        endpoints::create_next_period,
+        endpoints::duplicate_budget,
+        endpoints::accept_invitation,
+        endpoints::import_items,
+        endpoints::add_item_to_budget,
+        endpoints::get_item,
+        endpoints::update_item,
+        endpoints::patch_item,
+        endpoints::delete_item,
+        endpoints::restore_item,
+        endpoints::get_transactions,
+        endpoints::add_transaction,
+        endpoints::get_transaction,
+        endpoints::update_transaction,
+        endpoints::delete_transaction,
+        endpoints::get_members,
+        endpoints::invite_member,
+        endpoints::remove_member,
+    ),
+    components(schemas(dto::BudgetSort, SortOrder, ExportFormat)),
+    tags(
+        (name = "budget", description = "Budgets, their periods, and exports"),
+        (name = "item", description = "Planned items of a budget"),
+        (name = "transaction", description = "Actual spending and income of a budget"),
+        (name = "member", description = "Sharing a budget with other users"),
+    ),
+)]
+pub struct BudgetApi;
 
 pub fn create_router(state: AppState) -> Router {
     Router::new()
         .route("/", get(endpoints::get_all_budgets))
         .route("/", post(endpoints::create_budget))
+        .route("/invitation", get(endpoints::get_invitations))
+        .route("/export", get(endpoints::export_budgets))
         .route("/:id", delete(endpoints::delete_budget))
         .route("/:id", get(endpoints::get_budget))
         .route("/:id", put(endpoints::update_budget))
+        .route("/:id", patch(endpoints::patch_budget))
+        .route("/:id/history", get(endpoints::get_history))
+        .route("/:id/summary", get(endpoints::get_budget_summary))
+        .route("/:id/progress", get(endpoints::get_budget_progress))
+        .route("/:id/occurrences", get(endpoints::get_occurrences))
+        .route("/:id/export", get(endpoints::export_budget))
+        .route("/:id/next", post(endpoints::create_next_period))
+        .route("/:id/duplicate", post(endpoints::duplicate_budget))
+        .route("/:id/restore", post(endpoints::restore_budget))
+        .route("/:id/revert", post(endpoints::revert_budget))
+        .route("/:id/accept", post(endpoints::accept_invitation))
+        .route("/:id/import", post(endpoints::import_items))
         .with_state(state.clone())
         .nest(
             "/:id/item",
             Router::new()
                 .route("/", post(endpoints::add_item_to_budget))
+                .route("/:item_id", get(endpoints::get_item))
                 .route("/:item_id", put(endpoints::update_item))
+                .route("/:item_id", patch(endpoints::patch_item))
                 .route("/:item_id", delete(endpoints::delete_item))
+                .route("/:item_id/restore", post(endpoints::restore_item))
+                .with_state(state.clone()),
+        )
+        .nest(
+            "/:id/transaction",
+            Router::new()
+                .route("/", get(endpoints::get_transactions))
+                .route("/", post(endpoints::add_transaction))
+                .route("/:transaction_id", delete(endpoints::delete_transaction))
+                .route("/:transaction_id", get(endpoints::get_transaction))
+                .route("/:transaction_id", put(endpoints::update_transaction))
+                .with_state(state.clone()),
+        )
+        .nest(
+            "/:id/member",
+            Router::new()
+                .route("/", get(endpoints::get_members))
+                .route("/", post(endpoints::invite_member))
+                .route("/:user_id", delete(endpoints::remove_member))
                 .with_state(state),
         )
 }
 
 mod endpoints {
     use super::{
-        dto::AddItemToBudgetRequest, item_repository::ItemRepository, repository::BudgetRepository,
+        dto::AddItemToBudgetRequest, item_repository::ItemRepository,
+        member_repository::MemberRepository, repository::BudgetRepository,
+        transaction_repository::TransactionRepository,
+    };
+    use crate::{
+        app_state::AppState,
+        audit::{self, repository::AuditRepository},
+        auth::scope::{Authorized, BudgetRead, BudgetWrite, ItemWrite, TransactionWrite},
+        budget::{
+            dto,
+            export::{self, BudgetExport},
+        },
+        error::{ApiError, ProblemDetails},
+        etag::{etag, IfMatch, IfNoneMatch},
+        openapi::File,
+        pagination::Page,
+        response::{created, Accept},
+        validation::{OptionalJson, ValidatedJson},
     };
-    use crate::{app_state::AppState, auth::Claims, budget::dto};
     use axum::{
         debug_handler,
-        extract::{Path, State},
-        http::StatusCode,
+        extract::{Path, Query, State},
+        http::{header, StatusCode},
+        response::{IntoResponse, Response},
         Json,
     };
     use std::sync::Arc;
@@ -43,151 +153,1524 @@ mod endpoints {
 
     /// Create a new budget.
     #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "",
+        tag = "budget",
+        request_body = dto::CreateBudget,
+        responses(
+            (status = 201, description = "The new budget, or only its id with `Accept: text/plain`", content((dto::Budget = "application/json"), (String = "text/plain")), headers(("Location" = String, description = "Path of the new budget"))),
+            (status = 400, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
     pub async fn create_budget(
         State(repository): State<Arc<BudgetRepository>>,
-        claims: Claims,
-        Json(payload): Json<dto::CreateBudget>,
-    ) -> Result<String, StatusCode> {
+        claims: Authorized<BudgetWrite>,
+        accept: Accept,
+        ValidatedJson(payload): ValidatedJson<dto::CreateBudget>,
+    ) -> Result<Response, ApiError> {
         tracing::info!("Creating budget");
 
-        match repository
-            .create_budget(claims.user_id(), &payload.title)
-            .await
-        {
-            Ok(id) => Ok(id.to_string()),
-            Err(_) => Err(StatusCode::BAD_REQUEST),
-        }
+        let id = repository.create_budget(claims.user_id(), &payload).await?;
+        let budget = repository.get_budget(claims.user_id(), &id).await?;
+
+        Ok(created(
+            accept,
+            format!("/budget/{id}"),
+            id,
+            dto::Budget::from(&budget),
+        ))
     }
 
-    /// Delete a budget for a user
+    /// Move a budget to the trash.
     #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        delete,
+        path = "/{id}",
+        tag = "budget",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("If-Match" = Option<String>, Header, description = "Only change the budget if its `ETag` is one of these"),
+        ),
+        responses(
+            (status = 204, description = "The budget was moved to the trash"),
+            (status = 404, response = ProblemDetails),
+            (status = 412, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
     pub async fn delete_budget(
         State(repository): State<Arc<BudgetRepository>>,
-        claims: Claims,
+        claims: Authorized<BudgetWrite>,
         Path(budget_id): Path<Uuid>,
-    ) -> Result<(), StatusCode> {
+        if_match: IfMatch,
+    ) -> Result<StatusCode, ApiError> {
         tracing::info!(
             "Deleting budget '{budget_id}' for user '{}'",
             claims.user_id()
         );
 
-        match repository.delete_budget(claims.user_id(), &budget_id).await {
-            Ok(_) => Ok(()),
-            Err(_) => Err(StatusCode::BAD_REQUEST),
-        }
+        repository
+            .delete_budget(claims.user_id(), &budget_id, if_match.versions())
+            .await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Restore a budget from the trash.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/restore",
+        tag = "budget",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        responses(
+            (status = 200, body = dto::BudgetWithItems, headers(("ETag" = String, description = "The version of the budget"))),
+            (status = 403, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn restore_budget(
+        State(repository): State<Arc<BudgetRepository>>,
+        claims: Authorized<BudgetWrite>,
+        Path(budget_id): Path<Uuid>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        tracing::info!(
+            "Restoring budget '{budget_id}' for user '{}'",
+            claims.user_id()
+        );
+
+        let budget = repository
+            .restore_budget(claims.user_id(), &budget_id)
+            .await?;
+
+        Ok((
+            [(header::ETAG, etag(budget.version))],
+            Json(dto::BudgetWithItems::from(&budget)),
+        ))
+    }
+
+    /// Revert a budget and its items to how they were at a previous time.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/revert",
+        tag = "budget",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("If-Match" = Option<String>, Header, description = "Only revert the budget if its `ETag` is one of these"),
+        ),
+        request_body = dto::RevertBudget,
+        responses(
+            (status = 200, body = dto::BudgetWithItems, headers(("ETag" = String, description = "The new version of the budget"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+            (status = 412, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn revert_budget(
+        State(repository): State<Arc<BudgetRepository>>,
+        claims: Authorized<BudgetWrite>,
+        Path(budget_id): Path<Uuid>,
+        if_match: IfMatch,
+        ValidatedJson(payload): ValidatedJson<dto::RevertBudget>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        tracing::info!(
+            "User '{}' reverting budget {budget_id} to {}",
+            claims.user_id(),
+            payload.as_of
+        );
+
+        let budget = repository
+            .revert_budget(
+                claims.user_id(),
+                &budget_id,
+                payload.as_of.naive_utc(),
+                if_match.versions(),
+            )
+            .await?;
+
+        Ok((
+            [(header::ETAG, etag(budget.version))],
+            Json(dto::BudgetWithItems::from(&budget)),
+        ))
     }
 
     /// Get a budget from a given ID.
     #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}",
+        tag = "budget",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("If-None-Match" = Option<String>, Header, description = "The `ETag` of the budget the client already has"),
+            dto::GetBudget,
+        ),
+        responses(
+            (status = 200, body = dto::BudgetWithItems, headers(("ETag" = String, description = "The version of the budget, unless it is a past state"))),
+            (status = 304, description = "The budget has not changed"),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
     pub async fn get_budget(
         State(repository): State<Arc<BudgetRepository>>,
         Path(budget_id): Path<Uuid>,
-        claims: Claims,
-    ) -> Result<Json<dto::BudgetWithItems>, StatusCode> {
+        claims: Authorized<BudgetRead>,
+        if_none_match: IfNoneMatch,
+        Query(query): Query<dto::GetBudget>,
+    ) -> Result<Response, ApiError> {
         tracing::info!("Get budget {budget_id} and user: {}", claims.user_id());
 
-        match repository.get_budget(claims.user_id(), &budget_id).await {
-            Some(budget) => Ok(Json((&budget).into())),
-            None => Err(StatusCode::NOT_FOUND),
+        if let Some(as_of) = query.as_of {
+            let budget = repository
+                .get_budget_as_of(claims.user_id(), &budget_id, as_of.naive_utc())
+                .await?;
+            return Ok(Json(dto::BudgetWithItems::from(&budget)).into_response());
         }
+        let budget = repository.get_budget(claims.user_id(), &budget_id).await?;
+
+        Ok(if_none_match.respond(budget.version, Json(dto::BudgetWithItems::from(&budget))))
     }
 
-    /// Get all budgets in the database.
+    /// Get a page of the changes to a budget and its items, newest first.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/history",
+        tag = "budget",
+        params(("id" = Uuid, Path, description = "Id of the budget"), audit::dto::ListHistory),
+        responses(
+            (status = 200, body = Page<audit::dto::AuditEvent>),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_history(
+        State(repository): State<Arc<AuditRepository>>,
+        claims: Authorized<BudgetRead>,
+        Path(budget_id): Path<Uuid>,
+        Query(query): Query<audit::dto::ListHistory>,
+    ) -> Result<Json<Page<audit::dto::AuditEvent>>, ApiError> {
+        tracing::info!(
+            "Get history of budget {budget_id} for user {}",
+            claims.user_id()
+        );
+
+        let page = repository
+            .get_history(claims.user_id(), &budget_id, &query)
+            .await?;
+
+        Ok(Json(page.map(|x| (&x).into())))
+    }
+
+    /// Get the totals of a budget, per currency and category.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/summary",
+        tag = "budget",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        responses(
+            (status = 200, body = dto::BudgetSummary),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_budget_summary(
+        State(repository): State<Arc<BudgetRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetRead>,
+    ) -> Result<Json<dto::BudgetSummary>, ApiError> {
+        tracing::info!(
+            "Get summary of budget {budget_id} for user: {}",
+            claims.user_id()
+        );
+
+        let summary = repository.get_summary(claims.user_id(), &budget_id).await?;
+
+        Ok(Json((&summary).into()))
+    }
+
+    /// Create the budget of the next period, copying the items of the current one.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/next",
+        tag = "budget",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        request_body = dto::NextPeriod,
+        responses(
+(status = 201, description = "The budget of the next period, or only its id with `Accept: text/plain`", content((dto::Budget = "application/json"), (String = "text/plain")), headers(("Location" = String, description = "Path of the new budget"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+            (status = 409, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn create_next_period(
+        State(repository): State<Arc<BudgetRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetWrite>,
+        accept: Accept,
+        OptionalJson(payload): OptionalJson<dto::NextPeriod>,
+    ) -> Result<Response, ApiError> {
+        tracing::info!(
+            "Creating next period of budget {budget_id} for user '{}'",
+            claims.user_id()
+        );
+
+        let request = payload.unwrap_or_default();
+        let id = repository
+            .create_next_period(claims.user_id(), &budget_id, &request)
+            .await?;
+        let budget = repository.get_budget(claims.user_id(), &id).await?;
+
+        Ok(created(
+            accept,
+            format!("/budget/{id}"),
+            id,
+            dto::Budget::from(&budget),
+        ))
+    }
+
+    /// Create a copy of a budget and its items, optionally scaling their amounts.
     ///
-    /// NOTE: This will not continue to be exposed to end users.
+    /// The copy is owned by the user, who only needs to be able to view the
+    /// budget. The items are put in the user's own categories with the same
+    /// names, and categories the user does not have yet are created for them.
     #[debug_handler(state = AppState)]
-    pub async fn get_all_budgets(
+    #[utoipa::path(
+        post,
+        path = "/{id}/duplicate",
+        tag = "budget",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        request_body = dto::DuplicateBudget,
+        responses(
+            (status = 201, description = "The copy of the budget, or only its id with `Accept: text/plain`", content((dto::Budget = "application/json"), (String = "text/plain")), headers(("Location" = String, description = "Path of the new budget"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn duplicate_budget(
         State(repository): State<Arc<BudgetRepository>>,
-        claims: Claims,
-    ) -> Json<Vec<dto::Budget>> {
-        tracing::info!("Get all budgets for user {}", claims.user_id());
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetWrite>,
+        accept: Accept,
+        ValidatedJson(payload): ValidatedJson<dto::DuplicateBudget>,
+    ) -> Result<Response, ApiError> {
+        tracing::info!(
+            "Duplicating budget {budget_id} for user '{}'",
+            claims.user_id()
+        );
 
-        Json(
-            repository
-                .get_all_budgets_for_user(claims.user_id())
-                .await
-                .iter()
-                .map(|x| x.into())
-                .collect::<Vec<dto::Budget>>(),
+        let id = repository
+            .duplicate_budget(claims.user_id(), &budget_id, &payload)
+            .await?;
+        let budget = repository.get_budget(claims.user_id(), &id).await?;
+
+        Ok(created(
+            accept,
+            format!("/budget/{id}"),
+            id,
+            dto::Budget::from(&budget),
+        ))
+    }
+
+    /// Compare the planned amounts of a budget with its transactions.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/progress",
+        tag = "budget",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        responses(
+            (status = 200, body = dto::BudgetProgress),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_budget_progress(
+        State(repository): State<Arc<BudgetRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetRead>,
+    ) -> Result<Json<dto::BudgetProgress>, ApiError> {
+        tracing::info!(
+            "Get progress of budget {budget_id} for user: {}",
+            claims.user_id()
+        );
+
+        let progress = repository
+            .get_progress(claims.user_id(), &budget_id)
+            .await?;
+
+        Ok(Json((&progress).into()))
+    }
+
+    /// Get the dates that the recurring items of a budget occur on.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/occurrences",
+        tag = "budget",
+        params(("id" = Uuid, Path, description = "Id of the budget"), dto::ListOccurrences),
+        responses(
+            (status = 200, body = Vec<dto::Occurrence>),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_occurrences(
+        State(repository): State<Arc<BudgetRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetRead>,
+        Query(query): Query<dto::ListOccurrences>,
+    ) -> Result<Json<Vec<dto::Occurrence>>, ApiError> {
+        tracing::info!(
+            "Get occurrences of budget {budget_id} for user: {}",
+            claims.user_id()
+        );
+
+        let occurrences = repository
+            .get_occurrences(claims.user_id(), &budget_id, &query)
+            .await?;
+
+        Ok(Json(occurrences.iter().map(|x| x.into()).collect()))
+    }
+
+    /// Download a budget with its items as CSV, JSON, or XLSX.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/export",
+        tag = "budget",
+        params(("id" = Uuid, Path, description = "Id of the budget"), dto::Export),
+        responses(
+            (
+                status = 200,
+                description = "The budget as a file in the requested format",
+                content(
+                    (String = "text/csv"),
+                    (dto::BudgetWithItems = "application/json"),
+                    (File = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+                ),
+            ),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn export_budget(
+        State(repository): State<Arc<BudgetRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetRead>,
+        Query(query): Query<dto::Export>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        tracing::info!(
+            "Export budget {budget_id} as {:?} for user: {}",
+            query.format,
+            claims.user_id()
+        );
+
+        let export = budget_export(&repository, claims.user_id(), &budget_id).await?;
+        let body = export.write(query.format)?;
+
+        Ok(attachment(
+            query.format.content_type(),
+            &export.file_name(query.format),
+            body,
+        ))
+    }
+
+    /// Download all the user's budgets as a ZIP archive, with a file per budget.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/export",
+        tag = "budget",
+        params(dto::Export),
+        responses(
+            (status = 200, description = "ZIP archive with a file per budget", body = File, content_type = "application/zip"),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn export_budgets(
+        State(repository): State<Arc<BudgetRepository>>,
+        claims: Authorized<BudgetRead>,
+        Query(query): Query<dto::Export>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        tracing::info!(
+            "Export all budgets as {:?} for user: {}",
+            query.format,
+            claims.user_id()
+        );
+
+        let mut exports = vec![];
+        for budget_id in repository.get_budget_ids_for_user(claims.user_id()).await? {
+            exports.push(budget_export(&repository, claims.user_id(), &budget_id).await?);
+        }
+        let body = export::archive(&exports, query.format)?;
+
+        Ok(attachment("application/zip", "budgets.zip", body))
+    }
+
+    async fn budget_export(
+        repository: &BudgetRepository,
+        user_id: &str,
+        budget_id: &Uuid,
+    ) -> Result<BudgetExport, ApiError> {
+        let budget = repository.get_budget(user_id, budget_id).await?;
+        let summary = repository.get_summary(user_id, budget_id).await?;
+
+        Ok(BudgetExport {
+            budget: (&budget).into(),
+            summary: (&summary).into(),
+        })
+    }
+
+    /// A response that is downloaded as a file named `file_name`.
+    fn attachment(content_type: &'static str, file_name: &str, body: Vec<u8>) -> impl IntoResponse {
+        (
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{file_name}\""),
+                ),
+            ],
+            body,
         )
     }
 
-    /// Update the name of a budget.
+    /// Get a page of the user's budgets, optionally filtered by title.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "",
+        tag = "budget",
+        params(dto::ListBudgets),
+        responses(
+            (status = 200, body = Page<dto::Budget>),
+            (status = 400, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_all_budgets(
+        State(repository): State<Arc<BudgetRepository>>,
+        claims: Authorized<BudgetRead>,
+        Query(query): Query<dto::ListBudgets>,
+    ) -> Result<Json<Page<dto::Budget>>, ApiError> {
+        tracing::info!("Get budgets for user {}", claims.user_id());
+
+        let page = repository
+            .get_budgets_for_user(claims.user_id(), &query)
+            .await?;
+
+        Ok(Json(page.map(|x| (&x).into())))
+    }
+
+    /// Update the name, currency, and period of a budget.
+    #[utoipa::path(
+        put,
+        path = "/{id}",
+        tag = "budget",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("If-Match" = Option<String>, Header, description = "Only change the budget if its `ETag` is one of these"),
+        ),
+        request_body = dto::UpdateBudget,
+        responses(
+            (status = 200, body = dto::BudgetWithItems, headers(("ETag" = String, description = "The new version of the budget"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+            (status = 412, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
     pub async fn update_budget(
         State(repository): State<Arc<BudgetRepository>>,
-        claims: Claims,
+        claims: Authorized<BudgetWrite>,
         Path(budget_id): Path<Uuid>,
-        Json(payload): Json<dto::UpdateBudget>,
-    ) -> Result<(), StatusCode> {
+        if_match: IfMatch,
+        ValidatedJson(payload): ValidatedJson<dto::UpdateBudget>,
+    ) -> Result<impl IntoResponse, ApiError> {
         tracing::info!("Updating budget for user '{}'", claims.user_id());
 
-        repository
-            .update_budget(claims.user_id(), &budget_id, &payload.title)
-            .await
-            .map_err(|_| StatusCode::NOT_FOUND)
+        let budget = repository
+            .update_budget(claims.user_id(), &budget_id, &payload, if_match.versions())
+            .await?;
+
+        Ok((
+            [(header::ETAG, etag(budget.version))],
+            Json(dto::BudgetWithItems::from(&budget)),
+        ))
+    }
+
+    /// Change some of the fields of a budget, and get the updated budget.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        patch,
+        path = "/{id}",
+        tag = "budget",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("If-Match" = Option<String>, Header, description = "Only change the budget if its `ETag` is one of these"),
+        ),
+        request_body = dto::PatchBudget,
+        responses(
+            (status = 200, body = dto::BudgetWithItems, headers(("ETag" = String, description = "The new version of the budget"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+            (status = 412, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn patch_budget(
+        State(repository): State<Arc<BudgetRepository>>,
+        claims: Authorized<BudgetWrite>,
+        Path(budget_id): Path<Uuid>,
+        if_match: IfMatch,
+        ValidatedJson(payload): ValidatedJson<dto::PatchBudget>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        tracing::info!(
+            "User '{}' patch budget {budget_id}. Payload: {payload:?}",
+            claims.user_id()
+        );
+
+        let budget = repository
+            .patch_budget(claims.user_id(), &budget_id, &payload, if_match.versions())
+            .await?;
+
+        Ok((
+            [(header::ETAG, etag(budget.version))],
+            Json(dto::BudgetWithItems::from(&budget)),
+        ))
     }
 
     /// Add a new item to a budget.
     #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/item",
+        tag = "item",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        request_body = dto::AddItemToBudgetRequest,
+        responses(
+            (status = 201, description = "The new item, or only its id with `Accept: text/plain`", content((dto::Item = "application/json"), (String = "text/plain")), headers(("Location" = String, description = "Path of the new item"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["item:write"])),
+    )]
     pub async fn add_item_to_budget(
         State(repository): State<Arc<ItemRepository>>,
         Path(budget_id): Path<Uuid>,
-        claims: Claims,
-        Json(payload): Json<AddItemToBudgetRequest>,
-    ) -> Result<String, StatusCode> {
+        claims: Authorized<ItemWrite>,
+        accept: Accept,
+        ValidatedJson(payload): ValidatedJson<AddItemToBudgetRequest>,
+    ) -> Result<Response, ApiError> {
         tracing::info!(
             "User '{}' add item to budget {budget_id}. Payload: {payload:?}",
             claims.user_id()
         );
-        match repository
+        let item = repository
             .add_item_to_budget(claims.user_id(), budget_id, payload)
-            .await
-        {
-            Ok(id) => Ok(id.to_string()),
-            Err(_) => Err(StatusCode::BAD_REQUEST),
-        }
+            .await?;
+
+        Ok(created(
+            accept,
+            format!("/budget/{budget_id}/item/{}", item.id),
+            item.id,
+            dto::Item::from(&item),
+        ))
+    }
+
+    /// Import items into a budget from a CSV body.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/import",
+        tag = "item",
+        params(("id" = Uuid, Path, description = "Id of the budget"), dto::ImportItems),
+        request_body(content = String, content_type = "text/csv", description = "Rows with a header"),
+        responses(
+            (status = 200, body = dto::ImportResult),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["item:write"])),
+    )]
+    pub async fn import_items(
+        State(repository): State<Arc<ItemRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<ItemWrite>,
+        Query(options): Query<dto::ImportItems>,
+        csv: String,
+    ) -> Result<Json<dto::ImportResult>, ApiError> {
+        tracing::info!(
+            "User '{}' import items to budget {budget_id}. Options: {options:?}",
+            claims.user_id()
+        );
+
+        let result = repository
+            .import_items(claims.user_id(), budget_id, &csv, &options)
+            .await?;
+
+        Ok(Json((&result).into()))
+    }
+
+    /// Get an item of a budget.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/item/{item_id}",
+        tag = "item",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("item_id" = Uuid, Path, description = "Id of the item"),
+            ("If-None-Match" = Option<String>, Header, description = "The `ETag` of the item the client already has"),
+        ),
+        responses(
+            (status = 200, body = dto::Item, headers(("ETag" = String, description = "The version of the item"))),
+            (status = 304, description = "The item has not changed"),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_item(
+        State(repository): State<Arc<ItemRepository>>,
+        Path((budget_id, item_id)): Path<(Uuid, Uuid)>,
+        claims: Authorized<BudgetRead>,
+        if_none_match: IfNoneMatch,
+    ) -> Result<Response, ApiError> {
+        tracing::info!(
+            "User '{}' get item {item_id} on budget {budget_id}",
+            claims.user_id()
+        );
+
+        let item = repository
+            .get_item_for_user(claims.user_id(), budget_id, item_id)
+            .await?;
+
+        Ok(if_none_match.respond(item.version, Json(dto::Item::from(&item))))
     }
 
     /// Update an item on a budget
     #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        put,
+        path = "/{id}/item/{item_id}",
+        tag = "item",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("item_id" = Uuid, Path, description = "Id of the item"),
+            ("If-Match" = Option<String>, Header, description = "Only change the item if its `ETag` is one of these"),
+        ),
+        request_body = dto::AddItemToBudgetRequest,
+        responses(
+            (status = 200, body = dto::Item, headers(("ETag" = String, description = "The new version of the item"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+            (status = 412, response = ProblemDetails),
+        ),
+        security(("bearer" = ["item:write"])),
+    )]
     pub async fn update_item(
         State(repository): State<Arc<ItemRepository>>,
         Path((budget_id, item_id)): Path<(Uuid, Uuid)>,
-        claims: Claims,
-        Json(payload): Json<AddItemToBudgetRequest>,
-    ) -> StatusCode {
+        claims: Authorized<ItemWrite>,
+        if_match: IfMatch,
+        ValidatedJson(payload): ValidatedJson<AddItemToBudgetRequest>,
+    ) -> Result<impl IntoResponse, ApiError> {
         tracing::info!(
             "User '{}' update item {item_id} on budget {budget_id}. Payload: {payload:?}",
             claims.user_id()
         );
 
-        match repository
-            .update_item(claims.user_id(), budget_id, item_id, payload)
-            .await
-        {
-            Ok(_) => StatusCode::ACCEPTED,
-            Err(_) => StatusCode::BAD_REQUEST,
-        }
+        let item = repository
+            .update_item(
+                claims.user_id(),
+                budget_id,
+                item_id,
+                payload,
+                if_match.versions(),
+            )
+            .await?;
+
+        Ok((
+            [(header::ETAG, etag(item.version))],
+            Json(dto::Item::from(&item)),
+        ))
     }
 
-    /// Delete an item.
+    /// Change some of the fields of an item, and get the updated item.
     #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        patch,
+        path = "/{id}/item/{item_id}",
+        tag = "item",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("item_id" = Uuid, Path, description = "Id of the item"),
+            ("If-Match" = Option<String>, Header, description = "Only change the item if its `ETag` is one of these"),
+        ),
+        request_body = dto::PatchItem,
+        responses(
+            (status = 200, body = dto::Item, headers(("ETag" = String, description = "The new version of the item"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+            (status = 412, response = ProblemDetails),
+        ),
+        security(("bearer" = ["item:write"])),
+    )]
+    pub async fn patch_item(
+        State(repository): State<Arc<ItemRepository>>,
+        Path((budget_id, item_id)): Path<(Uuid, Uuid)>,
+        claims: Authorized<ItemWrite>,
+        if_match: IfMatch,
+        ValidatedJson(payload): ValidatedJson<dto::PatchItem>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        tracing::info!(
+            "User '{}' patch item {item_id} on budget {budget_id}. Payload: {payload:?}",
+            claims.user_id()
+        );
+
+        let item = repository
+            .patch_item(
+                claims.user_id(),
+                budget_id,
+                item_id,
+                &payload,
+                if_match.versions(),
+            )
+            .await?;
+
+        Ok((
+            [(header::ETAG, etag(item.version))],
+            Json(dto::Item::from(&item)),
+        ))
+    }
+
+    /// Move an item to the trash.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        delete,
+        path = "/{id}/item/{item_id}",
+        tag = "item",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("item_id" = Uuid, Path, description = "Id of the item"),
+            ("If-Match" = Option<String>, Header, description = "Only change the item if its `ETag` is one of these"),
+        ),
+        responses(
+            (status = 204, description = "The item was moved to the trash"),
+            (status = 404, response = ProblemDetails),
+            (status = 412, response = ProblemDetails),
+        ),
+        security(("bearer" = ["item:write"])),
+    )]
     pub async fn delete_item(
         State(repository): State<Arc<ItemRepository>>,
-        claims: Claims,
+        claims: Authorized<ItemWrite>,
         Path((budget_id, item_id)): Path<(Uuid, Uuid)>,
-    ) -> StatusCode {
+        if_match: IfMatch,
+    ) -> Result<StatusCode, ApiError> {
         tracing::info!(
             "User '{}' delete item {item_id} on budget {budget_id}",
             claims.user_id()
         );
 
-        match repository
-            .delete_item(claims.user_id(), budget_id, item_id)
+        repository
+            .delete_item(claims.user_id(), budget_id, item_id, if_match.versions())
+            .await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Restore an item from the trash.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/item/{item_id}/restore",
+        tag = "item",
+        params(
+            ("id" = Uuid, Path, description = "Id of the budget"),
+            ("item_id" = Uuid, Path, description = "Id of the item"),
+        ),
+        responses(
+            (status = 200, body = dto::Item, headers(("ETag" = String, description = "The version of the item"))),
+            (status = 403, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["item:write"])),
+    )]
+    pub async fn restore_item(
+        State(repository): State<Arc<ItemRepository>>,
+        claims: Authorized<ItemWrite>,
+        Path((budget_id, item_id)): Path<(Uuid, Uuid)>,
+    ) -> Result<impl IntoResponse, ApiError> {
+        tracing::info!(
+            "User '{}' restore item {item_id} on budget {budget_id}",
+            claims.user_id()
+        );
+
+        let item = repository
+            .restore_item(claims.user_id(), budget_id, item_id)
+            .await?;
+
+        Ok((
+            [(header::ETAG, etag(item.version))],
+            Json(dto::Item::from(&item)),
+        ))
+    }
+
+    /// Get the transactions of a budget, optionally within a range of dates.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/transaction",
+        tag = "transaction",
+        params(("id" = Uuid, Path, description = "Id of the budget"), dto::ListTransactions),
+        responses(
+            (status = 200, body = Vec<dto::Transaction>),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_transactions(
+        State(repository): State<Arc<TransactionRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetRead>,
+        Query(query): Query<dto::ListTransactions>,
+    ) -> Result<Json<Vec<dto::Transaction>>, ApiError> {
+        tracing::info!(
+            "User '{}' get transactions of budget {budget_id}",
+            claims.user_id()
+        );
+
+        Ok(Json(
+            repository
+                .get_transactions(claims.user_id(), budget_id, &query)
+                .await?
+                .iter()
+                .map(|x| x.into())
+                .collect(),
+        ))
+    }
+
+    /// Get a transaction from its id.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/transaction/{transaction_id}",
+        tag = "transaction",
+        params(("id" = Uuid, Path, description = "Id of the budget"), ("transaction_id" = Uuid, Path, description = "Id of the transaction")),
+        responses(
+            (status = 200, body = dto::Transaction),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_transaction(
+        State(repository): State<Arc<TransactionRepository>>,
+        Path((budget_id, transaction_id)): Path<(Uuid, Uuid)>,
+        claims: Authorized<BudgetRead>,
+    ) -> Result<Json<dto::Transaction>, ApiError> {
+        tracing::info!(
+            "User '{}' get transaction {transaction_id} of budget {budget_id}",
+            claims.user_id()
+        );
+
+        let transaction = repository
+            .get_transaction(claims.user_id(), budget_id, transaction_id)
+            .await?;
+
+        Ok(Json((&transaction).into()))
+    }
+
+    /// Record a transaction against a budget.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/transaction",
+        tag = "transaction",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        request_body = dto::TransactionRequest,
+        responses(
+            (status = 201, description = "The new transaction, or only its id with `Accept: text/plain`", content((dto::Transaction = "application/json"), (String = "text/plain")), headers(("Location" = String, description = "Path of the new transaction"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["transaction:write"])),
+    )]
+    pub async fn add_transaction(
+        State(repository): State<Arc<TransactionRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<TransactionWrite>,
+        accept: Accept,
+        ValidatedJson(payload): ValidatedJson<dto::TransactionRequest>,
+    ) -> Result<Response, ApiError> {
+        tracing::info!(
+            "User '{}' add transaction to budget {budget_id}. Payload: {payload:?}",
+            claims.user_id()
+        );
+
+        let id = repository
+            .add_transaction(claims.user_id(), budget_id, &payload)
+            .await?;
+        let transaction = repository
+            .get_transaction(claims.user_id(), budget_id, id)
+            .await?;
+
+        Ok(created(
+            accept,
+            format!("/budget/{budget_id}/transaction/{id}"),
+            id,
+            dto::Transaction::from(&transaction),
+        ))
+    }
+
+    /// Update a transaction on a budget.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        put,
+        path = "/{id}/transaction/{transaction_id}",
+        tag = "transaction",
+        params(("id" = Uuid, Path, description = "Id of the budget"), ("transaction_id" = Uuid, Path, description = "Id of the transaction")),
+        request_body = dto::TransactionRequest,
+        responses(
+            (status = 200, body = dto::Transaction),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["transaction:write"])),
+    )]
+    pub async fn update_transaction(
+        State(repository): State<Arc<TransactionRepository>>,
+        Path((budget_id, transaction_id)): Path<(Uuid, Uuid)>,
+        claims: Authorized<TransactionWrite>,
+        ValidatedJson(payload): ValidatedJson<dto::TransactionRequest>,
+    ) -> Result<Json<dto::Transaction>, ApiError> {
+        tracing::info!(
+            "User '{}' update transaction {transaction_id} on budget {budget_id}. Payload: {payload:?}",
+            claims.user_id()
+        );
+
+        repository
+            .update_transaction(claims.user_id(), budget_id, transaction_id, &payload)
+            .await?;
+        let transaction = repository
+            .get_transaction(claims.user_id(), budget_id, transaction_id)
+            .await?;
+
+        Ok(Json((&transaction).into()))
+    }
+
+    /// Delete a transaction.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        delete,
+        path = "/{id}/transaction/{transaction_id}",
+        tag = "transaction",
+        params(("id" = Uuid, Path, description = "Id of the budget"), ("transaction_id" = Uuid, Path, description = "Id of the transaction")),
+        responses(
+            (status = 204, description = "The transaction was deleted"),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["transaction:write"])),
+    )]
+    pub async fn delete_transaction(
+        State(repository): State<Arc<TransactionRepository>>,
+        Path((budget_id, transaction_id)): Path<(Uuid, Uuid)>,
+        claims: Authorized<TransactionWrite>,
+    ) -> Result<StatusCode, ApiError> {
+        tracing::info!(
+            "User '{}' delete transaction {transaction_id} on budget {budget_id}",
+            claims.user_id()
+        );
+
+        repository
+            .delete_transaction(claims.user_id(), budget_id, transaction_id)
+            .await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Get the members of a budget, and the users invited to it.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}/member",
+        tag = "member",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        responses(
+            (status = 200, body = Vec<dto::Member>),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_members(
+        State(repository): State<Arc<MemberRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetRead>,
+    ) -> Result<Json<Vec<dto::Member>>, ApiError> {
+        tracing::info!(
+            "Get members of budget {budget_id} for user: {}",
+            claims.user_id()
+        );
+
+        let members = repository.get_members(claims.user_id(), &budget_id).await?;
+
+        Ok(Json(members.iter().map(|x| x.into()).collect()))
+    }
+
+    /// Invite a user to a budget.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/member",
+        tag = "member",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        request_body = dto::InviteMember,
+        responses(
+            (status = 200, description = "The user was invited"),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+            (status = 409, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn invite_member(
+        State(repository): State<Arc<MemberRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetWrite>,
+        ValidatedJson(payload): ValidatedJson<dto::InviteMember>,
+    ) -> Result<(), ApiError> {
+        tracing::info!(
+            "User '{}' invite '{}' to budget {budget_id} as {}",
+            claims.user_id(),
+            payload.user_id,
+            payload.role
+        );
+
+        Ok(repository
+            .invite_member(claims.user_id(), &budget_id, &payload)
+            .await?)
+    }
+
+    /// Remove a member from a budget, revoke an invitation, or leave a budget.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        delete,
+        path = "/{id}/member/{user_id}",
+        tag = "member",
+        params(("id" = Uuid, Path, description = "Id of the budget"), ("user_id" = String, Path, description = "Id of the member")),
+        responses(
+            (status = 204, description = "The member was removed"),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn remove_member(
+        State(repository): State<Arc<MemberRepository>>,
+        Path((budget_id, member_id)): Path<(Uuid, String)>,
+        claims: Authorized<BudgetWrite>,
+    ) -> Result<StatusCode, ApiError> {
+        tracing::info!(
+            "User '{}' remove '{member_id}' from budget {budget_id}",
+            claims.user_id()
+        );
+
+        repository
+            .remove_member(claims.user_id(), &budget_id, &member_id)
+            .await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Accept an invitation to a budget.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/accept",
+        tag = "member",
+        params(("id" = Uuid, Path, description = "Id of the budget")),
+        responses(
+            (status = 200, description = "The invitation was accepted"),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn accept_invitation(
+        State(repository): State<Arc<MemberRepository>>,
+        Path(budget_id): Path<Uuid>,
+        claims: Authorized<BudgetWrite>,
+    ) -> Result<(), ApiError> {
+        tracing::info!(
+            "User '{}' accept invitation to budget {budget_id}",
+            claims.user_id()
+        );
+
+        Ok(repository
+            .accept_invitation(claims.user_id(), &budget_id)
+            .await?)
+    }
+
+    /// Get the user's invitations to budgets that have not been accepted yet.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/invitation",
+        tag = "member",
+        responses(
+            (status = 200, body = Vec<dto::Invitation>),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_invitations(
+        State(repository): State<Arc<MemberRepository>>,
+        claims: Authorized<BudgetRead>,
+    ) -> Result<Json<Vec<dto::Invitation>>, ApiError> {
+        tracing::info!("Get invitations for user: {}", claims.user_id());
+
+        let invitations = repository.get_invitations(claims.user_id()).await?;
+
+        Ok(Json(invitations.iter().map(|x| x.into()).collect()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::auth::test_utils::TestIdentityProvider;
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const BUDGET_ID: &str = "b8d6ff4e-c12f-416b-a611-8ad0c90669fe";
+    const ITEM_ID: &str = "d831821b-1b50-41fc-a01e-19a1243c334a";
+    const TRANSACTION_ID: &str = "e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e701";
+
+    async fn router(identity_provider: &TestIdentityProvider) -> Router {
+        // Requests rejected because of missing scopes never reach the database.
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let state = AppState::new(
+            Arc::new(pool),
+            Arc::new(identity_provider.jwks_repository().await),
+        );
+
+        create_router(state)
+    }
+
+    /// Send a request to `uri` with a token that has `scope`, returning the
+    /// status code and the problem details in the body.
+    async fn send(method: Method, uri: &str, scope: &str) -> (StatusCode, serde_json::Value) {
+        let identity_provider = TestIdentityProvider::spawn().await;
+        let token = identity_provider.token("Alice").scope(scope).sign();
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"title":"Title","name":"Name","amount":1}"#))
+            .unwrap();
+
+        let response = router(&identity_provider)
             .await
-        {
-            Ok(_) => StatusCode::ACCEPTED,
-            Err(_) => StatusCode::BAD_REQUEST,
-        }
+            .oneshot(request)
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    async fn assert_requires_scope(method: Method, uri: &str, required: &str) {
+        // All other scopes than the one required.
+        let scope = [
+            "budget:read",
+            "budget:write",
+            "item:write",
+            "transaction:write",
+        ]
+        .into_iter()
+        .filter(|s| *s != required)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+        let (status, body) = send(method.clone(), uri, &scope).await;
+
+        assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri}");
+        assert_eq!(
+            body["detail"],
+            format!("Missing required scope '{required}'"),
+            "{method} {uri}"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_all_budgets_requires_budget_read() {
+        assert_requires_scope(Method::GET, "/", "budget:read").await;
+    }
+
+    #[tokio::test]
+    async fn create_budget_requires_budget_write() {
+        assert_requires_scope(Method::POST, "/", "budget:write").await;
+    }
+
+    #[tokio::test]
+    async fn get_budget_requires_budget_read() {
+        assert_requires_scope(Method::GET, &format!("/{BUDGET_ID}"), "budget:read").await;
+    }
+
+    #[tokio::test]
+    async fn get_history_requires_budget_read() {
+        assert_requires_scope(Method::GET, &format!("/{BUDGET_ID}/history"), "budget:read").await;
+    }
+
+    #[tokio::test]
+    async fn get_budget_summary_requires_budget_read() {
+        assert_requires_scope(Method::GET, &format!("/{BUDGET_ID}/summary"), "budget:read").await;
+    }
+
+    #[tokio::test]
+    async fn update_budget_requires_budget_write() {
+        assert_requires_scope(Method::PUT, &format!("/{BUDGET_ID}"), "budget:write").await;
+    }
+
+    #[tokio::test]
+    async fn patch_budget_requires_budget_write() {
+        assert_requires_scope(Method::PATCH, &format!("/{BUDGET_ID}"), "budget:write").await;
+    }
+
+    #[tokio::test]
+    async fn delete_budget_requires_budget_write() {
+        assert_requires_scope(Method::DELETE, &format!("/{BUDGET_ID}"), "budget:write").await;
+    }
+
+    #[tokio::test]
+    async fn restore_budget_requires_budget_write() {
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{BUDGET_ID}/restore"),
+            "budget:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn revert_budget_requires_budget_write() {
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{BUDGET_ID}/revert"),
+            "budget:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn add_item_requires_item_write() {
+        assert_requires_scope(Method::POST, &format!("/{BUDGET_ID}/item"), "item:write").await;
+    }
+
+    #[tokio::test]
+    async fn get_item_requires_budget_read() {
+        assert_requires_scope(
+            Method::GET,
+            &format!("/{BUDGET_ID}/item/{ITEM_ID}"),
+            "budget:read",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn update_item_requires_item_write() {
+        assert_requires_scope(
+            Method::PUT,
+            &format!("/{BUDGET_ID}/item/{ITEM_ID}"),
+            "item:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn patch_item_requires_item_write() {
+        assert_requires_scope(
+            Method::PATCH,
+            &format!("/{BUDGET_ID}/item/{ITEM_ID}"),
+            "item:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn delete_item_requires_item_write() {
+        assert_requires_scope(
+            Method::DELETE,
+            &format!("/{BUDGET_ID}/item/{ITEM_ID}"),
+            "item:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn restore_item_requires_item_write() {
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{BUDGET_ID}/item/{ITEM_ID}/restore"),
+            "item:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn export_budget_requires_budget_read() {
+        assert_requires_scope(Method::GET, &format!("/{BUDGET_ID}/export"), "budget:read").await;
+    }
+
+    #[tokio::test]
+    async fn export_all_budgets_requires_budget_read() {
+        assert_requires_scope(Method::GET, "/export", "budget:read").await;
+    }
+
+    #[tokio::test]
+    async fn import_items_requires_item_write() {
+        assert_requires_scope(Method::POST, &format!("/{BUDGET_ID}/import"), "item:write").await;
+    }
+
+    #[tokio::test]
+    async fn get_budget_progress_requires_budget_read() {
+        assert_requires_scope(
+            Method::GET,
+            &format!("/{BUDGET_ID}/progress"),
+            "budget:read",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn create_next_period_requires_budget_write() {
+        assert_requires_scope(Method::POST, &format!("/{BUDGET_ID}/next"), "budget:write").await;
+    }
+
+    #[tokio::test]
+    async fn duplicate_budget_requires_budget_write() {
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{BUDGET_ID}/duplicate"),
+            "budget:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn get_occurrences_requires_budget_read() {
+        assert_requires_scope(
+            Method::GET,
+            &format!("/{BUDGET_ID}/occurrences"),
+            "budget:read",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn reading_members_requires_budget_read() {
+        assert_requires_scope(Method::GET, &format!("/{BUDGET_ID}/member"), "budget:read").await;
+        assert_requires_scope(Method::GET, "/invitation", "budget:read").await;
+    }
+
+    #[tokio::test]
+    async fn managing_members_requires_budget_write() {
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{BUDGET_ID}/member"),
+            "budget:write",
+        )
+        .await;
+        assert_requires_scope(
+            Method::DELETE,
+            &format!("/{BUDGET_ID}/member/Bob"),
+            "budget:write",
+        )
+        .await;
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{BUDGET_ID}/accept"),
+            "budget:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn get_transactions_requires_budget_read() {
+        assert_requires_scope(
+            Method::GET,
+            &format!("/{BUDGET_ID}/transaction"),
+            "budget:read",
+        )
+        .await;
+        assert_requires_scope(
+            Method::GET,
+            &format!("/{BUDGET_ID}/transaction/{TRANSACTION_ID}"),
+            "budget:read",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn add_transaction_requires_transaction_write() {
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{BUDGET_ID}/transaction"),
+            "transaction:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn update_transaction_requires_transaction_write() {
+        assert_requires_scope(
+            Method::PUT,
+            &format!("/{BUDGET_ID}/transaction/{TRANSACTION_ID}"),
+            "transaction:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn delete_transaction_requires_transaction_write() {
+        assert_requires_scope(
+            Method::DELETE,
+            &format!("/{BUDGET_ID}/transaction/{TRANSACTION_ID}"),
+            "transaction:write",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn request_without_token_is_unauthorized() {
+        let identity_provider = TestIdentityProvider::spawn().await;
+        let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+
+        let response = router(&identity_provider)
+            .await
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 }
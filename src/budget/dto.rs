@@ -1,15 +1,34 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
+use validator::{Validate, ValidationError};
 
-use super::model;
+use super::{export::ExportFormat, member::Role, model, period::Period, schedule::Schedule};
+use crate::{
+    category::model::CategoryKind,
+    money::{Currency, Money},
+    pagination::SortOrder,
+    patch,
+    validation::{self, MAX_AMOUNT, MAX_NAME_LENGTH, MAX_TEXT_LENGTH, MIN_AMOUNT},
+};
 
-#[derive(Debug, Serialize)]
+/// Largest percentage the amounts of a duplicated budget can be scaled by.
+const MAX_SCALE: u32 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Budget {
     pub id: Uuid,
     pub user_id: String,
     pub title: String,
+    pub currency: Currency,
+    pub period: Option<Period>,
+    /// The budget of the previous period, if this budget was created from it.
+    pub previous_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
+    pub modified_at: DateTime<Utc>,
+    /// Incremented on every change to the budget or its items, and sent as its `ETag`.
+    pub version: i64,
 }
 impl From<&model::Budget> for Budget {
     fn from(from: &model::Budget) -> Self {
@@ -17,17 +36,76 @@ impl From<&model::Budget> for Budget {
             id: from.id,
             user_id: from.user_id.to_owned(),
             title: from.title.to_owned(),
-            created_at: DateTime::from_utc(from.created_at, Utc),
+            currency: from.currency,
+            period: from.period(),
+            previous_id: from.previous_id,
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+            modified_at: DateTime::from_naive_utc_and_offset(from.modified_at, Utc),
+            version: from.version,
+        }
+    }
+}
+impl From<&model::BudgetWithItems> for Budget {
+    fn from(from: &model::BudgetWithItems) -> Self {
+        Self {
+            id: from.id,
+            user_id: from.user_id.to_owned(),
+            title: from.title.to_owned(),
+            currency: from.currency,
+            period: from.period(),
+            previous_id: from.previous_id,
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+            modified_at: DateTime::from_naive_utc_and_offset(from.modified_at, Utc),
+            version: from.version,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetSort {
+    #[default]
+    CreatedAt,
+    Title,
+}
+
+/// Query parameters for listing budgets, e.g. `?search=food&sort=title&limit=10`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListBudgets {
+    /// Number of budgets in a page, defaults to 20.
+    pub limit: Option<u32>,
+    /// The `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub sort: BudgetSort,
+    /// Defaults to newest first when sorting by creation, and alphabetical when sorting by title.
+    pub order: Option<SortOrder>,
+    /// Only include budgets with a title containing this, ignoring case.
+    pub search: Option<String>,
+}
+
+impl ListBudgets {
+    pub fn order(&self) -> SortOrder {
+        self.order.unwrap_or(match self.sort {
+            BudgetSort::CreatedAt => SortOrder::Desc,
+            BudgetSort::Title => SortOrder::Asc,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BudgetWithItems {
     pub id: Uuid,
     pub user_id: String,
     pub title: String,
+    pub currency: Currency,
+    pub period: Option<Period>,
+    pub previous_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
+    pub modified_at: DateTime<Utc>,
+    /// Incremented on every change to the budget or its items, and sent as its `ETag`.
+    pub version: i64,
     pub items: Vec<Item>,
 }
 
@@ -37,53 +115,707 @@ impl From<&model::BudgetWithItems> for BudgetWithItems {
             id: from.id,
             user_id: from.user_id.to_owned(),
             title: from.title.to_owned(),
-            created_at: DateTime::from_utc(from.created_at, Utc),
+            currency: from.currency,
+            period: from.period(),
+            previous_id: from.previous_id,
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+            modified_at: DateTime::from_naive_utc_and_offset(from.modified_at, Utc),
+            version: from.version,
             items: from.items.iter().map(|x| x.into()).collect(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Totals of a budget, see [`BudgetRepository::get_summary`](super::repository::BudgetRepository::get_summary).
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BudgetSummary {
+    pub budget_id: Uuid,
+    pub currency: Currency,
+    /// Income, expenses, and balance for each currency used by the items.
+    pub totals: Vec<CurrencyTotal>,
+    /// Categories sorted by the size of their total, for each currency.
+    pub categories: Vec<CategoryTotal>,
+    pub largest_items: Vec<Item>,
+    /// Recurring items normalised to a month and a year, per category and currency.
+    pub recurring: Vec<RecurringTotal>,
+}
+
+impl From<&model::BudgetSummary> for BudgetSummary {
+    fn from(from: &model::BudgetSummary) -> Self {
+        Self {
+            budget_id: from.budget_id,
+            currency: from.currency,
+            totals: from.totals.iter().map(|x| x.into()).collect(),
+            categories: from.categories.iter().map(|x| x.into()).collect(),
+            largest_items: from.largest_items.iter().map(|x| x.into()).collect(),
+            recurring: from.recurring.iter().map(|x| x.into()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CurrencyTotal {
+    pub income: Money,
+    pub expenses: Money,
+    pub balance: Money,
+    pub item_count: i64,
+}
+
+impl From<&model::CurrencyTotal> for CurrencyTotal {
+    fn from(from: &model::CurrencyTotal) -> Self {
+        Self {
+            income: Money::new(from.income, from.currency),
+            expenses: Money::new(from.expenses, from.currency),
+            balance: Money::new(from.balance, from.currency),
+            item_count: from.item_count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CategoryTotal {
+    pub category_id: Uuid,
+    pub category: String,
+    pub kind: CategoryKind,
+    pub total: Money,
+    pub item_count: i64,
+    /// Share of the category in the total of its currency, in percent.
+    pub percentage: f64,
+}
+
+impl From<&model::CategoryTotal> for CategoryTotal {
+    fn from(from: &model::CategoryTotal) -> Self {
+        Self {
+            category_id: from.category_id,
+            category: from.category.to_owned(),
+            kind: from.kind,
+            total: Money::new(from.total, from.currency),
+            item_count: from.item_count,
+            percentage: from.percentage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecurringTotal {
+    pub category_id: Uuid,
+    pub category: String,
+    pub monthly: Money,
+    pub annual: Money,
+    pub item_count: i64,
+}
+
+impl From<&model::RecurringTotal> for RecurringTotal {
+    fn from(from: &model::RecurringTotal) -> Self {
+        Self {
+            category_id: from.category_id,
+            category: from.category.to_owned(),
+            monthly: Money::new(from.monthly, from.currency),
+            annual: Money::new(from.annual, from.currency),
+            item_count: from.item_count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateBudget {
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
     pub title: String,
+    /// Default currency of the items in the budget. Defaults to `DKK`.
+    #[serde(default)]
+    pub currency: Currency,
+    /// Dates covered by the budget, if it is for a specific period.
+    #[serde(default)]
+    #[validate(custom(function = "period_order"))]
+    pub period: Option<Period>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 pub struct UpdateBudget {
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub title: String,
+    /// New default currency of the budget. Existing items keep their currency.
+    #[serde(default)]
+    pub currency: Option<Currency>,
+    /// New period of the budget. The period is kept if none is given.
+    #[serde(default)]
+    #[validate(custom(function = "period_order"))]
+    pub period: Option<Period>,
+}
+
+/// Partial update of a budget. Fields that are left out keep their value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct PatchBudget {
+    #[serde(
+        default,
+        deserialize_with = "patch::required",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(nullable = false, max_length = 100)]
+    pub title: Option<String>,
+    /// New default currency of the budget. Existing items keep their currency.
+    #[serde(
+        default,
+        deserialize_with = "patch::required",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(nullable = false)]
+    pub currency: Option<Currency>,
+    /// New period of the budget, or `null` to remove it.
+    #[serde(
+        default,
+        deserialize_with = "patch::nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(custom(function = "period_order"))]
+    pub period: Option<Option<Period>>,
+}
+
+pub(crate) fn period_order(period: &Period) -> Result<(), ValidationError> {
+    period
+        .check()
+        .map_err(|message| ValidationError::new("period_order").with_message(message.into()))
+}
+
+/// Query parameters for getting a budget, e.g. `?as_of=2026-03-01T00:00:00Z`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetBudget {
+    /// Get the budget and its items as they were at this time, rebuilt from its history.
+    /// Categories have their current names.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Revert a budget and its items to a previous state.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct RevertBudget {
+    /// Revert the budget and its items to how they were at this time.
+    pub as_of: DateTime<Utc>,
+}
+
+/// Options for creating the budget of the next period.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct NextPeriod {
+    /// Title of the new budget. Defaults to the title of the current budget.
+    #[serde(default)]
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub title: Option<String>,
+    /// Add what is left of each planned expense category as an extra item in the next period.
+    #[serde(default)]
+    pub rollover: bool,
+}
+
+/// Options for duplicating a budget.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct DuplicateBudget {
+    /// Title of the copy.
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
     pub title: String,
+    /// Percentage to scale the amounts of the items by, e.g. `110` for 10% more.
+    /// Amounts are rounded to the nearest minor unit. Defaults to `100`.
+    #[serde(default)]
+    #[validate(range(min = 1, max = MAX_SCALE))]
+    #[schema(minimum = 1, maximum = 1000)]
+    pub scale: Option<u32>,
 }
 
 /// DTO for the basic item that can be returned to the user.
 /// This mirrors the database object closely.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Item {
     pub id: Uuid,
     pub budget_id: Uuid,
+    pub category_id: Uuid,
+    /// Name of the category.
     pub category: String,
     pub name: String,
-    pub amount: i32,
+    pub amount: Money,
+    pub schedule: Option<Schedule>,
+    /// What the item adds up to in an average month, if it recurs.
+    pub monthly_amount: Option<Money>,
+    /// What the item adds up to in a year, if it recurs.
+    pub annual_amount: Option<Money>,
     pub created_at: DateTime<Utc>,
     pub modified_at: DateTime<Utc>,
+    /// Incremented on every change to the item, and sent as its `ETag`.
+    pub version: i64,
 }
 
 impl From<&model::Item> for Item {
     fn from(from: &model::Item) -> Self {
+        let rule = from.schedule_rule.as_ref();
         Self {
             id: from.id,
             budget_id: from.budget_id,
+            category_id: from.category_id,
             category: from.category.to_owned(),
             name: from.name.to_owned(),
-            amount: from.amount,
-            created_at: DateTime::from_utc(from.created_at, Utc),
-            modified_at: DateTime::from_utc(from.modified_at, Utc),
+            amount: Money::new(from.amount, from.currency),
+            schedule: from.schedule(),
+            monthly_amount: rule.map(|r| Money::new(r.monthly_amount(from.amount), from.currency)),
+            annual_amount: rule.map(|r| Money::new(r.annual_amount(from.amount), from.currency)),
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+            modified_at: DateTime::from_naive_utc_and_offset(from.modified_at, Utc),
+            version: from.version,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
 #[cfg_attr(test, derive(derive_new::new))]
 pub struct AddItemToBudgetRequest {
+    /// Id of one of the user's categories.
+    pub category_id: Uuid,
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub name: String,
+    /// Amount in the minor unit of the currency, e.g. cents for `EUR`.
+    #[validate(range(min = MIN_AMOUNT, max = MAX_AMOUNT))]
+    #[schema(minimum = -1_000_000_000_000i64, maximum = 1_000_000_000_000i64)]
+    pub amount: i64,
+    /// Currency of the amount. Defaults to the currency of the budget.
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    pub currency: Option<Currency>,
+    /// When the item recurs, e.g. rent on the first of every month.
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    pub schedule: Option<Schedule>,
+}
+
+/// Partial update of an item. Fields that are left out keep their value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct PatchItem {
+    /// Id of one of the user's categories.
+    #[serde(
+        default,
+        deserialize_with = "patch::required",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(nullable = false)]
+    pub category_id: Option<Uuid>,
+    #[serde(
+        default,
+        deserialize_with = "patch::required",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(nullable = false, max_length = 100)]
+    pub name: Option<String>,
+    /// Amount in the minor unit of the currency.
+    #[serde(
+        default,
+        deserialize_with = "patch::required",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[validate(range(min = MIN_AMOUNT, max = MAX_AMOUNT))]
+    #[schema(nullable = false, minimum = -1_000_000_000_000i64, maximum = 1_000_000_000_000i64)]
+    pub amount: Option<i64>,
+    #[serde(
+        default,
+        deserialize_with = "patch::required",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(nullable = false)]
+    pub currency: Option<Currency>,
+    /// New schedule of the item, or `null` to stop it from recurring.
+    #[serde(
+        default,
+        deserialize_with = "patch::nullable",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub schedule: Option<Option<Schedule>>,
+}
+
+/// A user with access to a budget, or invited to it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Member {
+    pub user_id: String,
+    pub role: Role,
+    /// `None` for the user who created the budget.
+    pub invited_by: Option<String>,
+    /// `None` while the invitation has not been accepted.
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&model::Member> for Member {
+    fn from(from: &model::Member) -> Self {
+        Self {
+            user_id: from.user_id.to_owned(),
+            role: from.role,
+            invited_by: from.invited_by.to_owned(),
+            accepted_at: from
+                .accepted_at
+                .map(|at| DateTime::from_naive_utc_and_offset(at, Utc)),
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct InviteMember {
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub user_id: String,
+    pub role: Role,
+}
+
+/// An invitation to a budget, waiting to be accepted.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Invitation {
+    pub budget_id: Uuid,
+    /// Title of the budget.
+    pub title: String,
+    pub role: Role,
+    pub invited_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&model::Invitation> for Invitation {
+    fn from(from: &model::Invitation) -> Self {
+        Self {
+            budget_id: from.budget_id,
+            title: from.title.to_owned(),
+            role: from.role,
+            invited_by: from.invited_by.to_owned(),
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+        }
+    }
+}
+
+/// A transaction recorded against a budget, e.g. a purchase.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Transaction {
+    pub id: Uuid,
+    pub budget_id: Uuid,
+    pub item_id: Option<Uuid>,
+    /// The category of the transaction, or of its item if it has none of its own.
+    pub category_id: Option<Uuid>,
+    /// Name of the category.
+    pub category: Option<String>,
+    pub date: NaiveDate,
+    pub amount: Money,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub modified_at: DateTime<Utc>,
+}
+
+impl From<&model::Transaction> for Transaction {
+    fn from(from: &model::Transaction) -> Self {
+        Self {
+            id: from.id,
+            budget_id: from.budget_id,
+            item_id: from.item_id,
+            category_id: from.category_id,
+            category: from.category.to_owned(),
+            date: from.date,
+            amount: Money::new(from.amount, from.currency),
+            payee: from.payee.to_owned(),
+            memo: from.memo.to_owned(),
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+            modified_at: DateTime::from_naive_utc_and_offset(from.modified_at, Utc),
+        }
+    }
+}
+
+/// Used both to record a transaction, and to replace all fields of an existing one.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[cfg_attr(test, derive(derive_new::new))]
+pub struct TransactionRequest {
+    pub date: NaiveDate,
+    /// Amount in the minor unit of the currency. Negative for expenses.
+    #[validate(range(min = MIN_AMOUNT, max = MAX_AMOUNT))]
+    #[schema(minimum = -1_000_000_000_000i64, maximum = 1_000_000_000_000i64)]
+    pub amount: i64,
+    /// Defaults to the currency of the item, or of the budget if there is no item.
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    pub currency: Option<Currency>,
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub payee: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    #[validate(length(max = MAX_TEXT_LENGTH), custom(function = "validation::text"))]
+    #[schema(max_length = 1000)]
+    pub memo: Option<String>,
+    /// Item of the budget that the transaction counts against.
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    pub item_id: Option<Uuid>,
+    /// One of the user's categories. Only needed if it differs from the category of the item.
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    pub category_id: Option<Uuid>,
+}
+
+/// Query parameters for listing transactions, e.g. `?from=2024-01-01&to=2024-01-31`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListTransactions {
+    /// First date to include.
+    pub from: Option<NaiveDate>,
+    /// Last date to include.
+    pub to: Option<NaiveDate>,
+}
+
+/// Query parameters for expanding recurring items, e.g. `?from=2024-01-01&to=2024-03-31`.
+/// Both default to the period of the budget.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListOccurrences {
+    /// First date to include.
+    pub from: Option<NaiveDate>,
+    /// Last date to include.
+    pub to: Option<NaiveDate>,
+}
+
+/// Query parameters for exporting budgets, e.g. `?format=xlsx`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Export {
+    /// Defaults to JSON.
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Query parameters for importing items from CSV, e.g.
+/// `?delimiter=;&decimal_separator=,&category=Kategori&name=Navn&amount=Beløb`.
+#[derive(Debug, Clone, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportItems {
+    /// Only parse and validate the rows, without creating any items.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Separator between the fields of a row. Defaults to `,`.
+    #[serde(default = "ImportItems::default_delimiter")]
+    pub delimiter: char,
+    /// Either `.` or `,`, the other is then a thousands separator. Defaults to `.`.
+    #[serde(default = "ImportItems::default_decimal_separator")]
+    pub decimal_separator: char,
+    /// Header of the column with the name of the category. Defaults to `category`.
+    #[serde(default = "ImportItems::default_category")]
+    pub category: String,
+    /// Header of the column with the name of the item. Defaults to `name`.
+    #[serde(default = "ImportItems::default_name")]
+    pub name: String,
+    /// Header of the column with the amount. Defaults to `amount`.
+    #[serde(default = "ImportItems::default_amount")]
+    pub amount: String,
+}
+
+impl ImportItems {
+    fn default_delimiter() -> char {
+        ','
+    }
+
+    fn default_decimal_separator() -> char {
+        '.'
+    }
+
+    fn default_category() -> String {
+        "category".to_string()
+    }
+
+    fn default_name() -> String {
+        "name".to_string()
+    }
+
+    fn default_amount() -> String {
+        "amount".to_string()
+    }
+}
+
+impl Default for ImportItems {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            delimiter: Self::default_delimiter(),
+            decimal_separator: Self::default_decimal_separator(),
+            category: Self::default_category(),
+            name: Self::default_name(),
+            amount: Self::default_amount(),
+        }
+    }
+}
+
+/// The outcome of an import. Nothing is created if any row has errors.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImportResult {
+    pub dry_run: bool,
+    /// Number of items created, always 0 for a dry run.
+    pub created: usize,
+    pub rows: Vec<ImportedRow>,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// A row of an import that is valid.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImportedRow {
+    /// Line of the row in the CSV, counting the header as line 1.
+    pub line: u64,
+    pub category_id: Uuid,
+    /// Name of the category.
+    pub category: String,
+    pub name: String,
+    pub amount: Money,
+}
+
+/// Why a row of an import is not valid.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImportRowError {
+    /// Line of the row in the CSV, counting the header as line 1.
+    pub line: u64,
+    pub message: String,
+}
+
+impl From<&model::ImportResult> for ImportResult {
+    fn from(from: &model::ImportResult) -> Self {
+        Self {
+            dry_run: from.dry_run,
+            created: from.created,
+            rows: from
+                .rows
+                .iter()
+                .map(|row| ImportedRow {
+                    line: row.line,
+                    category_id: row.category_id,
+                    category: row.category.to_owned(),
+                    name: row.name.to_owned(),
+                    amount: Money::new(row.amount, row.currency),
+                })
+                .collect(),
+            errors: from
+                .errors
+                .iter()
+                .map(|error| ImportRowError {
+                    line: error.line,
+                    message: error.message.to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A date a recurring item occurs on.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Occurrence {
+    pub date: NaiveDate,
+    pub item_id: Uuid,
+    pub name: String,
+    pub category_id: Uuid,
+    /// Name of the category.
     pub category: String,
+    pub amount: Money,
+}
+
+impl From<&model::Occurrence> for Occurrence {
+    fn from(from: &model::Occurrence) -> Self {
+        Self {
+            date: from.date,
+            item_id: from.item_id,
+            name: from.name.to_owned(),
+            category_id: from.category_id,
+            category: from.category.to_owned(),
+            amount: Money::new(from.amount, from.currency),
+        }
+    }
+}
+
+/// Planned amounts of a budget compared with the recorded transactions.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BudgetProgress {
+    pub budget_id: Uuid,
+    pub items: Vec<ItemProgress>,
+    /// Per category and currency, including transactions not linked to an item.
+    pub categories: Vec<CategoryProgress>,
+    /// Sum of the transactions without an item or category, per currency.
+    pub uncategorized: Vec<Money>,
+}
+
+impl From<&model::BudgetProgress> for BudgetProgress {
+    fn from(from: &model::BudgetProgress) -> Self {
+        Self {
+            budget_id: from.budget_id,
+            items: from.items.iter().map(|x| x.into()).collect(),
+            categories: from.categories.iter().map(|x| x.into()).collect(),
+            uncategorized: from
+                .uncategorized
+                .iter()
+                .map(|(currency, amount)| Money::new(*amount, *currency))
+                .collect(),
+        }
+    }
+}
+
+/// Planned and actual amount, with what is left of the plan.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Progress {
+    pub planned: Money,
+    pub actual: Money,
+    /// How much more can be spent, or is still expected for income.
+    /// Negative if more has been spent than planned.
+    pub remaining: Money,
+    /// How much more has been spent than planned, zero if within the plan.
+    pub overspent: Money,
+}
+
+impl Progress {
+    fn new(planned: i64, actual: i64, currency: Currency) -> Self {
+        Self {
+            planned: Money::new(planned, currency),
+            actual: Money::new(actual, currency),
+            remaining: Money::new(model::remaining(planned, actual), currency),
+            overspent: Money::new(model::overspent(planned, actual), currency),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ItemProgress {
+    pub item_id: Uuid,
     pub name: String,
-    pub amount: i32,
+    pub category_id: Uuid,
+    #[serde(flatten)]
+    pub progress: Progress,
+}
+
+impl From<&model::ItemProgress> for ItemProgress {
+    fn from(from: &model::ItemProgress) -> Self {
+        Self {
+            item_id: from.item_id,
+            name: from.name.to_owned(),
+            category_id: from.category_id,
+            progress: Progress::new(from.planned, from.actual, from.currency),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CategoryProgress {
+    pub category_id: Uuid,
+    pub category: String,
+    pub kind: CategoryKind,
+    #[serde(flatten)]
+    pub progress: Progress,
+}
+
+impl From<&model::CategoryProgress> for CategoryProgress {
+    fn from(from: &model::CategoryProgress) -> Self {
+        Self {
+            category_id: from.category_id,
+            category: from.category.to_owned(),
+            kind: from.kind,
+            progress: Progress::new(from.planned, from.actual, from.currency),
+        }
+    }
 }
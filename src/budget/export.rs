@@ -0,0 +1,388 @@
+use super::dto::{BudgetSummary, BudgetWithItems, Item};
+use crate::{error::ApiError, money::Money};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Write};
+use utoipa::ToSchema;
+use zip::{result::ZipError, write::SimpleFileOptions, ZipWriter};
+
+/// Columns of the items in CSV and XLSX exports. Columns are only ever added
+/// at the end, so spreadsheets that refer to them by position keep working.
+pub const ITEM_COLUMNS: [&str; 12] = [
+    "budget_id",
+    "budget",
+    "item_id",
+    "category_id",
+    "category",
+    "name",
+    "amount",
+    "currency",
+    "schedule_start",
+    "schedule_rule",
+    "created_at",
+    "modified_at",
+];
+
+/// Columns of the summary sheet in XLSX exports, with the totals per category.
+pub const SUMMARY_COLUMNS: [&str; 7] = [
+    "category_id",
+    "category",
+    "kind",
+    "total",
+    "currency",
+    "item_count",
+    "percentage",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    #[default]
+    Json,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// Writing an export failed, which is never caused by the budget itself.
+#[derive(Debug)]
+pub struct ExportError(String);
+
+impl From<csv::Error> for ExportError {
+    fn from(error: csv::Error) -> Self {
+        Self(format!("CSV: {error}"))
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(error: serde_json::Error) -> Self {
+        Self(format!("JSON: {error}"))
+    }
+}
+
+impl From<XlsxError> for ExportError {
+    fn from(error: XlsxError) -> Self {
+        Self(format!("XLSX: {error}"))
+    }
+}
+
+impl From<ZipError> for ExportError {
+    fn from(error: ZipError) -> Self {
+        Self(format!("ZIP: {error}"))
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(error: std::io::Error) -> Self {
+        Self(format!("IO: {error}"))
+    }
+}
+
+impl From<ExportError> for ApiError {
+    fn from(error: ExportError) -> Self {
+        tracing::error!("Failed to export: {}", error.0);
+        ApiError::Internal
+    }
+}
+
+/// A budget with its items and summary, as it is exported.
+pub struct BudgetExport {
+    pub budget: BudgetWithItems,
+    pub summary: BudgetSummary,
+}
+
+impl BudgetExport {
+    /// Name of the exported file, e.g. `budget-<id>.csv`.
+    pub fn file_name(&self, format: ExportFormat) -> String {
+        format!("budget-{}.{}", self.budget.id, format.extension())
+    }
+
+    /// Write the budget in `format`. CSV only has the items, while XLSX also
+    /// has a sheet with the totals per category.
+    pub fn write(&self, format: ExportFormat) -> Result<Vec<u8>, ExportError> {
+        match format {
+            ExportFormat::Csv => self.csv(),
+            ExportFormat::Json => Ok(serde_json::to_vec_pretty(&self.budget)?),
+            ExportFormat::Xlsx => self.xlsx(),
+        }
+    }
+
+    /// Items sorted by when they were created, so exports are stable.
+    fn items(&self) -> Vec<&Item> {
+        let mut items: Vec<_> = self.budget.items.iter().collect();
+        items.sort_by_key(|item| (item.created_at, item.id));
+        items
+    }
+
+    fn item_row(&self, item: &Item) -> [Cell; 12] {
+        [
+            Cell::Text(self.budget.id.to_string()),
+            Cell::Text(self.budget.title.clone()),
+            Cell::Text(item.id.to_string()),
+            Cell::Text(item.category_id.to_string()),
+            Cell::Text(item.category.clone()),
+            Cell::Text(item.name.clone()),
+            Cell::Amount(item.amount),
+            Cell::Text(item.amount.currency().to_string()),
+            Cell::Text(
+                item.schedule
+                    .as_ref()
+                    .map_or(String::new(), |s| s.start.to_string()),
+            ),
+            Cell::Text(
+                item.schedule
+                    .as_ref()
+                    .map_or(String::new(), |s| s.rule.to_string()),
+            ),
+            Cell::Text(item.created_at.to_rfc3339()),
+            Cell::Text(item.modified_at.to_rfc3339()),
+        ]
+    }
+
+    fn csv(&self) -> Result<Vec<u8>, ExportError> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.write_record(ITEM_COLUMNS)?;
+        for item in self.items() {
+            writer.write_record(self.item_row(item).iter().map(Cell::text))?;
+        }
+
+        writer
+            .into_inner()
+            .map_err(|error| ExportError(format!("CSV: {error}")))
+    }
+
+    fn xlsx(&self) -> Result<Vec<u8>, ExportError> {
+        let mut workbook = Workbook::new();
+
+        let sheet = workbook.add_worksheet().set_name("Items")?;
+        write_header(sheet, &ITEM_COLUMNS)?;
+        for (row, item) in (1..).zip(self.items()) {
+            write_row(sheet, row, &self.item_row(item))?;
+        }
+
+        let sheet = workbook.add_worksheet().set_name("Summary")?;
+        write_header(sheet, &SUMMARY_COLUMNS)?;
+        for (row, category) in (1..).zip(&self.summary.categories) {
+            write_row(
+                sheet,
+                row,
+                &[
+                    Cell::Text(category.category_id.to_string()),
+                    Cell::Text(category.category.clone()),
+                    Cell::Text(category.kind.as_str().to_string()),
+                    Cell::Amount(category.total),
+                    Cell::Text(category.total.currency().to_string()),
+                    Cell::Number(category.item_count as f64),
+                    Cell::Number(category.percentage),
+                ],
+            )?;
+        }
+
+        Ok(workbook.save_to_buffer()?)
+    }
+}
+
+/// Write all the budgets in `format` to a ZIP archive, with a file per budget.
+pub fn archive(budgets: &[BudgetExport], format: ExportFormat) -> Result<Vec<u8>, ExportError> {
+    let mut archive = ZipWriter::new(Cursor::new(vec![]));
+    for budget in budgets {
+        archive.start_file(budget.file_name(format), SimpleFileOptions::default())?;
+        archive.write_all(&budget.write(format)?)?;
+    }
+
+    Ok(archive.finish()?.into_inner())
+}
+
+/// A value in a row of an export. Amounts are numbers in XLSX, so they can be
+/// summed in a spreadsheet, and decimal strings in CSV.
+enum Cell {
+    Text(String),
+    Amount(Money),
+    Number(f64),
+}
+
+impl Cell {
+    fn text(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Amount(money) => money.formatted(),
+            Cell::Number(number) => number.to_string(),
+        }
+    }
+}
+
+fn write_header(sheet: &mut Worksheet, columns: &[&str]) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    for (column, name) in (0..).zip(columns) {
+        sheet.write_string_with_format(0, column, *name, &bold)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+
+    Ok(())
+}
+
+fn write_row(sheet: &mut Worksheet, row: u32, cells: &[Cell]) -> Result<(), XlsxError> {
+    for (column, cell) in (0..).zip(cells) {
+        match cell {
+            Cell::Text(text) => sheet.write_string(row, column, text)?,
+            Cell::Amount(money) => {
+                let digits = money.currency().minor_units();
+                let format = match digits {
+                    0 => Format::new().set_num_format("0"),
+                    _ => Format::new().set_num_format(format!("0.{}", "0".repeat(digits.into()))),
+                };
+                let amount = money.minor_units() as f64 / 10f64.powi(digits.into());
+                sheet.write_number_with_format(row, column, amount, &format)?
+            }
+            Cell::Number(number) => sheet.write_number(row, column, *number)?,
+        };
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        budget::{dto::CategoryTotal, schedule::Schedule},
+        category::model::CategoryKind,
+        money::Currency,
+    };
+    use chrono::{DateTime, Utc};
+    use std::io::Read;
+    use uuid::Uuid;
+
+    fn time(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn item(id: u128, name: &str, amount: i64, created_at: &str) -> Item {
+        Item {
+            id: Uuid::from_u128(id),
+            budget_id: Uuid::from_u128(1),
+            category_id: Uuid::from_u128(10),
+            category: "Home".to_string(),
+            name: name.to_string(),
+            amount: Money::new(amount, Currency::DKK),
+            schedule: None,
+            monthly_amount: None,
+            annual_amount: None,
+            created_at: time(created_at),
+            modified_at: time(created_at),
+            version: 1,
+        }
+    }
+
+    fn export() -> BudgetExport {
+        let mut rent = item(2, "Rent", -850000, "2024-01-02T00:00:00Z");
+        rent.schedule = Some(Schedule {
+            start: "2024-01-01".parse().unwrap(),
+            rule: "FREQ=MONTHLY".parse().unwrap(),
+        });
+        BudgetExport {
+            budget: BudgetWithItems {
+                id: Uuid::from_u128(1),
+                user_id: "Alice".to_string(),
+                title: "Home, sweet home".to_string(),
+                currency: Currency::DKK,
+                period: None,
+                previous_id: None,
+                created_at: time("2024-01-01T00:00:00Z"),
+                modified_at: time("2024-01-01T00:00:00Z"),
+                version: 1,
+                items: vec![item(3, "Water", -25050, "2024-01-03T00:00:00Z"), rent],
+            },
+            summary: BudgetSummary {
+                budget_id: Uuid::from_u128(1),
+                currency: Currency::DKK,
+                totals: vec![],
+                categories: vec![CategoryTotal {
+                    category_id: Uuid::from_u128(10),
+                    category: "Home".to_string(),
+                    kind: CategoryKind::Expense,
+                    total: Money::new(-875050, Currency::DKK),
+                    item_count: 2,
+                    percentage: 100.0,
+                }],
+                largest_items: vec![],
+                recurring: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn csv_has_stable_columns_and_items_in_order_of_creation() {
+        let csv = String::from_utf8(export().write(ExportFormat::Csv).unwrap()).unwrap();
+
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "budget_id,budget,item_id,category_id,category,name,amount,currency,schedule_start,schedule_rule,created_at,modified_at"
+        );
+        assert_eq!(
+            lines[1],
+            "00000000-0000-0000-0000-000000000001,\"Home, sweet home\",00000000-0000-0000-0000-000000000002,00000000-0000-0000-0000-00000000000a,Home,Rent,-8500.00,DKK,2024-01-01,FREQ=MONTHLY,2024-01-02T00:00:00+00:00,2024-01-02T00:00:00+00:00"
+        );
+        assert!(lines[2].contains(",Water,-250.50,DKK,,,"));
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn json_is_the_budget_with_its_items() {
+        let json = export().write(ExportFormat::Json).unwrap();
+
+        let budget: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(budget["title"], "Home, sweet home");
+        assert_eq!(budget["items"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn xlsx_is_a_workbook_with_items_and_summary() {
+        let xlsx = export().write(ExportFormat::Xlsx).unwrap();
+
+        let mut workbook = zip::ZipArchive::new(Cursor::new(xlsx)).unwrap();
+        let mut sheets = String::new();
+        workbook
+            .by_name("xl/workbook.xml")
+            .unwrap()
+            .read_to_string(&mut sheets)
+            .unwrap();
+        assert!(sheets.contains(r#"name="Items""#));
+        assert!(sheets.contains(r#"name="Summary""#));
+    }
+
+    #[test]
+    fn archive_has_a_file_per_budget() {
+        let archive = archive(&[export()], ExportFormat::Csv).unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        let mut csv = String::new();
+        archive
+            .by_name("budget-00000000-0000-0000-0000-000000000001.csv")
+            .unwrap()
+            .read_to_string(&mut csv)
+            .unwrap();
+        assert!(csv.starts_with("budget_id,budget,"));
+        assert_eq!(archive.len(), 1);
+    }
+}
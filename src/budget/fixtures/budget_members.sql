@@ -0,0 +1,8 @@
+-- Members of the budget 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe' of Alice.
+INSERT INTO budget_member (budget_id, user_id, role, invited_by, accepted_at)
+VALUES
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Bob', 'viewer', 'Alice', '2024-01-01 12:00:00'),
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Carol', 'editor', 'Alice', '2024-01-01 12:00:00'),
+    -- Not accepted yet.
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Dave', 'owner', 'Alice', NULL)
+;
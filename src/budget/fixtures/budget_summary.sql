@@ -0,0 +1,20 @@
+INSERT INTO budget (id, user_id, title, currency)
+VALUES ('6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f', 'Alice', 'Monthly budget', 'DKK');
+
+INSERT INTO category (id, user_id, name, kind)
+VALUES
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Alice', 'Income', 'income'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Alice', 'Home', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Alice', 'Food', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a05', 'Alice', 'Travel', 'expense')
+;
+
+INSERT INTO item (budget_id, category_id, name, amount, currency)
+VALUES
+    ('6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Paycheck', 3000000, 'DKK'),
+    ('6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Bonus', 500000, 'DKK'),
+    ('6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Rent', -1200000, 'DKK'),
+    ('6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Groceries', -300000, 'DKK'),
+    ('6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Restaurants', -100000, 'DKK'),
+    ('6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a05', 'Hotel', -50000, 'EUR')
+;
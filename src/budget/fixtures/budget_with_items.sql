@@ -1,9 +1,17 @@
 INSERT INTO budget (id, user_id, title)
 VALUES ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Alice', 'My budget with items');
 
-INSERT INTO item (id, budget_id, category, name, amount)
+INSERT INTO category (id, user_id, name, kind)
 VALUES
-    ('5e666f18-de95-4513-abd8-1f09ed5ff98f', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Income', 'Paycheck', 100),
-    ('c4af1e7a-4dfd-4338-ad31-caee4848a69b', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Home', 'Rent', 50),
-    ('d831821b-1b50-41fc-a01e-19a1243c334a', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Food', 'Restaurants', 10)
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Alice', 'Income', 'income'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Alice', 'Home', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Alice', 'Food', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04', 'Bob', 'Food', 'expense')
+;
+
+INSERT INTO item (id, budget_id, category_id, name, amount)
+VALUES
+    ('5e666f18-de95-4513-abd8-1f09ed5ff98f', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Paycheck', 100),
+    ('c4af1e7a-4dfd-4338-ad31-caee4848a69b', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Rent', 50),
+    ('d831821b-1b50-41fc-a01e-19a1243c334a', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Restaurants', 10)
 ;
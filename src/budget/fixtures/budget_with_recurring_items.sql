@@ -0,0 +1,17 @@
+INSERT INTO budget (id, user_id, title, period_start, period_end, recurrence)
+VALUES ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Alice', 'January', '2024-01-01', '2024-01-31', 'monthly');
+
+INSERT INTO category (id, user_id, name, kind)
+VALUES
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Alice', 'Income', 'income'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Alice', 'Home', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Alice', 'Food', 'expense')
+;
+
+INSERT INTO item (id, budget_id, category_id, name, amount, schedule_start, schedule_rule)
+VALUES
+    ('5e666f18-de95-4513-abd8-1f09ed5ff98f', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Salary', 1500000, '2024-01-05', 'FREQ=WEEKLY;INTERVAL=2;BYDAY=FR'),
+    ('c4af1e7a-4dfd-4338-ad31-caee4848a69b', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Rent', -800000, '2023-06-01', 'FREQ=MONTHLY'),
+    ('0b7e9d1f-3a5c-4e7a-9c1e-3d5f7b9d1f3a', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Insurance', -240000, '2024-03-15', 'FREQ=YEARLY'),
+    ('d831821b-1b50-41fc-a01e-19a1243c334a', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Groceries', -300000, NULL, NULL)
+;
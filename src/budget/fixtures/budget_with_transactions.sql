@@ -0,0 +1,34 @@
+INSERT INTO budget (id, user_id, title)
+VALUES
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Alice', 'Monthly'),
+    ('7a1d3c5e-2b4f-4a6c-8e0d-1f3b5d7f9a0c', 'Alice', 'Holiday');
+
+INSERT INTO category (id, user_id, name, kind)
+VALUES
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Alice', 'Income', 'income'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Alice', 'Home', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Alice', 'Food', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04', 'Bob', 'Food', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a05', 'Alice', 'Travel', 'expense')
+;
+
+INSERT INTO item (id, budget_id, category_id, name, amount)
+VALUES
+    ('5e666f18-de95-4513-abd8-1f09ed5ff98f', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Paycheck', 2000000),
+    ('c4af1e7a-4dfd-4338-ad31-caee4848a69b', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Rent', -800000),
+    ('d831821b-1b50-41fc-a01e-19a1243c334a', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Groceries', -300000),
+    ('0b7e9d1f-3a5c-4e7a-9c1e-3d5f7b9d1f3a', '7a1d3c5e-2b4f-4a6c-8e0d-1f3b5d7f9a0c', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a05', 'Hotel', -500000)
+;
+
+INSERT INTO transaction (id, budget_id, item_id, category_id, date, amount, currency, payee)
+VALUES
+    ('e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e701', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '5e666f18-de95-4513-abd8-1f09ed5ff98f', NULL, '2024-01-25', 1500000, 'DKK', 'Employer'),
+    ('e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e702', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'c4af1e7a-4dfd-4338-ad31-caee4848a69b', NULL, '2024-01-01', -800000, 'DKK', 'Landlord'),
+    ('e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e703', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'd831821b-1b50-41fc-a01e-19a1243c334a', NULL, '2024-01-05', -200000, 'DKK', 'Supermarket'),
+    ('e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e704', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'd831821b-1b50-41fc-a01e-19a1243c334a', NULL, '2024-01-19', -150000, 'DKK', 'Supermarket'),
+    -- Not planned, but in a category.
+    ('e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e705', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', NULL, '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', '2024-01-12', -40000, 'DKK', 'Restaurant'),
+    ('e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e706', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', NULL, '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a05', '2024-01-20', -10000, 'EUR', 'Airline'),
+    -- Neither planned nor categorized.
+    ('e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e707', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', NULL, NULL, '2024-01-31', -5000, 'DKK', NULL)
+;
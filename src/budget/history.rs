@@ -0,0 +1,496 @@
+//! Past states of a budget and its items, rebuilt from the audit log.
+//!
+//! The audit log only has the fields that were changed, so a past state is
+//! found by starting from the current rows and undoing the changes made
+//! after it, newest first. Budgets and items purged from the trash cannot be
+//! rebuilt, as they are no longer there to start from. Categories are not in
+//! the audit log, so a past item is in the category it was in then, but with
+//! the name the category has now.
+
+use std::collections::HashSet;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use super::{period::Recurrence, schedule::Rule};
+use crate::{
+    audit::{Action, Entity},
+    money::Currency,
+};
+
+/// A change from the audit log of a budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Change {
+    pub entity: Entity,
+    pub entity_id: Uuid,
+    pub action: Action,
+    pub before: Option<Value>,
+    /// The version of the changed row before the change, unless it was created.
+    pub version: Option<i64>,
+    /// The version of the budget before the change, unless it was created.
+    pub budget_version: Option<i64>,
+    pub created_at: NaiveDateTime,
+}
+
+/// The columns of a budget as they were at some point.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub(crate) struct BudgetRow {
+    pub id: Uuid,
+    pub user_id: String,
+    pub title: String,
+    pub currency: Currency,
+    pub period_start: Option<NaiveDate>,
+    pub period_end: Option<NaiveDate>,
+    pub recurrence: Option<Recurrence>,
+    pub previous_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub modified_at: NaiveDateTime,
+    pub version: i64,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+impl BudgetRow {
+    /// Whether the fields a user can change are the same as in `other`.
+    pub fn same_content(&self, other: &BudgetRow) -> bool {
+        (
+            &self.title,
+            self.currency,
+            self.period_start,
+            self.period_end,
+            self.recurrence,
+        ) == (
+            &other.title,
+            other.currency,
+            other.period_start,
+            other.period_end,
+            other.recurrence,
+        )
+    }
+}
+
+/// The columns of an item as they were at some point.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub(crate) struct ItemRow {
+    pub id: Uuid,
+    pub budget_id: Uuid,
+    pub category_id: Uuid,
+    pub name: String,
+    pub amount: i64,
+    pub currency: Currency,
+    pub schedule_start: Option<NaiveDate>,
+    pub schedule_rule: Option<Rule>,
+    pub created_at: NaiveDateTime,
+    pub modified_at: NaiveDateTime,
+    pub version: i64,
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+impl ItemRow {
+    /// Whether the fields a user can change, and whether the item is in the
+    /// trash, are the same as in `other`.
+    pub fn same_content(&self, other: &ItemRow) -> bool {
+        (
+            self.category_id,
+            &self.name,
+            self.amount,
+            self.currency,
+            self.schedule_start,
+            &self.schedule_rule,
+            self.deleted_at.is_some(),
+        ) == (
+            other.category_id,
+            &other.name,
+            other.amount,
+            other.currency,
+            other.schedule_start,
+            &other.schedule_rule,
+            other.deleted_at.is_some(),
+        )
+    }
+}
+
+/// The rows of a budget and all of its items, including those in the trash,
+/// as JSON objects with a field per column.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Snapshot {
+    budget: Option<Map<String, Value>>,
+    items: Vec<Map<String, Value>>,
+}
+
+impl Snapshot {
+    pub fn new(budget: Option<Value>, items: Vec<Value>) -> Self {
+        Self {
+            budget: budget.and_then(into_object),
+            items: items.into_iter().filter_map(into_object).collect(),
+        }
+    }
+
+    /// The state at `as_of`, given all the `changes` of the budget, newest first.
+    ///
+    /// Undoing a change puts back the versions the budget and the changed item
+    /// had before it, as not every new version is in the audit log.
+    ///
+    /// A change is timed by the start of its transaction, so changes are not
+    /// in the order of their times when transactions overlap, and all those
+    /// after `as_of` are undone, not only the newest ones. Rows created before
+    /// there was an audit log have no change that created them, so they are
+    /// also left out if they were created after `as_of`.
+    pub fn at(mut self, changes: &[Change], as_of: NaiveDateTime) -> Self {
+        let (later, earlier): (Vec<_>, Vec<_>) =
+            changes.iter().partition(|change| change.created_at > as_of);
+
+        let mut undone = HashSet::new();
+        for change in later {
+            self.undo(change);
+            undone.insert(change.entity_id);
+        }
+        if self
+            .budget
+            .as_ref()
+            .is_some_and(|budget| created_after(budget, as_of))
+        {
+            self.budget = None;
+        }
+        self.items.retain(|item| !created_after(item, as_of));
+        if undone.is_empty() {
+            return self;
+        }
+
+        // The budget is modified with every change to it or its items.
+        let last = |entity_id: Option<Uuid>| {
+            earlier
+                .iter()
+                .filter(|change| entity_id.is_none() || entity_id == Some(change.entity_id))
+                .max_by_key(|change| change.created_at)
+                .copied()
+        };
+        if let Some(budget) = &mut self.budget {
+            set_modified_at(budget, last(None));
+        }
+        for item in &mut self.items {
+            let item_id = id(item);
+            if undone.contains(&item_id) {
+                set_modified_at(item, last(Some(item_id)));
+            }
+        }
+
+        self
+    }
+
+    fn undo(&mut self, change: &Change) {
+        let Some(budget) = &mut self.budget else {
+            return;
+        };
+        set_version(budget, change.budget_version);
+
+        match (change.entity, change.action) {
+            (Entity::Budget, Action::Create) => self.budget = None,
+            (Entity::Budget, _) => merge(budget, &change.before),
+            (Entity::Item, Action::Create) => {
+                self.items.retain(|item| id(item) != change.entity_id)
+            }
+            (Entity::Item, _) => {
+                if let Some(item) = self
+                    .items
+                    .iter_mut()
+                    .find(|item| id(item) == change.entity_id)
+                {
+                    merge(item, &change.before);
+                    set_version(item, change.version);
+                }
+            }
+        }
+    }
+
+    /// The budget, unless it did not exist yet or was in the trash.
+    pub fn budget(&self) -> Result<Option<BudgetRow>, serde_json::Error> {
+        let budget = self
+            .budget
+            .clone()
+            .map(|budget| serde_json::from_value::<BudgetRow>(Value::Object(budget)))
+            .transpose()?;
+
+        Ok(budget.filter(|budget| budget.deleted_at.is_none()))
+    }
+
+    /// All the items of the budget, including those in the trash, oldest first.
+    pub fn items(&self) -> Result<Vec<ItemRow>, serde_json::Error> {
+        let mut items = self
+            .items
+            .iter()
+            .map(|item| serde_json::from_value::<ItemRow>(Value::Object(item.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+        items.sort_by_key(|item| (item.created_at, item.id));
+
+        Ok(items)
+    }
+}
+
+fn into_object(value: Value) -> Option<Map<String, Value>> {
+    match value {
+        Value::Object(object) => Some(object),
+        _ => None,
+    }
+}
+
+fn id(row: &Map<String, Value>) -> Uuid {
+    row.get("id")
+        .and_then(Value::as_str)
+        .and_then(|id| id.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Put back the fields of a row as they were before a change.
+fn merge(row: &mut Map<String, Value>, before: &Option<Value>) {
+    if let Some(Value::Object(before)) = before {
+        for (field, value) in before {
+            row.insert(field.clone(), value.clone());
+        }
+    }
+}
+
+fn created_after(row: &Map<String, Value>, as_of: NaiveDateTime) -> bool {
+    row.get("created_at")
+        .and_then(|created_at| NaiveDateTime::deserialize(created_at).ok())
+        .is_some_and(|created_at| created_at > as_of)
+}
+
+fn set_version(row: &mut Map<String, Value>, version: Option<i64>) {
+    if let Some(version) = version {
+        row.insert("version".to_string(), Value::from(version));
+    }
+}
+
+/// The row was last modified by `last`, or never if there was no earlier change.
+fn set_modified_at(row: &mut Map<String, Value>, last: Option<&Change>) {
+    let modified_at = match last {
+        Some(change) => serde_json::to_value(change.created_at).unwrap_or_default(),
+        None => row.get("created_at").cloned().unwrap_or_default(),
+    };
+    row.insert("modified_at".to_string(), modified_at);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    const BUDGET: &str = "b8d6ff4e-c12f-416b-a611-8ad0c90669fe";
+    const BREAD: &str = "5e666f18-de95-4513-abd8-1f09ed5ff98f";
+    const MILK: &str = "c4af1e7a-4dfd-4338-ad31-caee4848a69b";
+
+    fn time(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 3, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    /// A change on `day`, made when the changed row and the budget had the
+    /// given `versions`.
+    fn change(
+        entity: Entity,
+        id: &str,
+        action: Action,
+        before: Value,
+        versions: (Option<i64>, i64),
+        day: u32,
+    ) -> Change {
+        Change {
+            entity,
+            entity_id: id.parse().unwrap(),
+            action,
+            before: Some(before).filter(|before| !before.is_null()),
+            version: versions.0,
+            budget_version: Some(versions.1),
+            created_at: time(day),
+        }
+    }
+
+    /// Bread was added on the 2nd and changed on the 5th, when the budget was
+    /// also renamed. Milk was added on the 6th.
+    fn snapshot() -> (Snapshot, Vec<Change>) {
+        let budget = json!({
+            "id": BUDGET, "user_id": "Alice", "title": "Home", "currency": "DKK",
+            "period_start": null, "period_end": null, "recurrence": null, "previous_id": null,
+            "created_at": time(1), "modified_at": time(6), "version": 5, "deleted_at": null,
+        });
+        let item = |id: &str, name: &str, amount: i64, day: u32, version: i64| {
+            json!({
+                "id": id, "budget_id": BUDGET, "category_id": "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03",
+                "name": name, "amount": amount, "currency": "DKK",
+                "schedule_start": null, "schedule_rule": null,
+                "created_at": time(day), "modified_at": time(5.max(day)), "version": version,
+                "deleted_at": null,
+            })
+        };
+        let changes = vec![
+            change(
+                Entity::Item,
+                MILK,
+                Action::Create,
+                Value::Null,
+                (None, 4),
+                6,
+            ),
+            change(
+                Entity::Budget,
+                BUDGET,
+                Action::Update,
+                json!({ "title": "Household" }),
+                (Some(3), 3),
+                5,
+            ),
+            change(
+                Entity::Item,
+                BREAD,
+                Action::Update,
+                json!({ "amount": -20 }),
+                (Some(1), 2),
+                5,
+            ),
+            change(
+                Entity::Item,
+                BREAD,
+                Action::Create,
+                Value::Null,
+                (None, 1),
+                2,
+            ),
+        ];
+        let snapshot = Snapshot::new(
+            Some(budget),
+            vec![
+                item(BREAD, "Bread", -25, 2, 2),
+                item(MILK, "Milk", -10, 6, 1),
+            ],
+        );
+
+        (snapshot, changes)
+    }
+
+    #[test]
+    fn current_state_is_kept_without_later_changes() {
+        let (snapshot, changes) = snapshot();
+
+        let now = snapshot.clone().at(&changes, time(7));
+
+        assert_eq!(now, snapshot);
+    }
+
+    #[test]
+    fn later_changes_are_undone() {
+        let (snapshot, changes) = snapshot();
+
+        let past = snapshot.at(&changes, time(3));
+
+        let budget = past.budget().unwrap().unwrap();
+        assert_eq!(budget.title, "Household");
+        assert_eq!(budget.version, 2);
+        assert_eq!(budget.modified_at, time(2));
+        let items = past.items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "Bread");
+        assert_eq!(items[0].amount, -20);
+        assert_eq!(items[0].version, 1);
+        assert_eq!(items[0].modified_at, time(2));
+    }
+
+    #[test]
+    fn budget_did_not_exist_before_it_was_created() {
+        let (snapshot, mut changes) = snapshot();
+        changes.push(change(
+            Entity::Budget,
+            BUDGET,
+            Action::Create,
+            Value::Null,
+            (None, 1),
+            1,
+        ));
+
+        let past = snapshot.at(&changes, time(1) - chrono::Duration::days(1));
+
+        assert_eq!(past.budget().unwrap(), None);
+    }
+
+    #[test]
+    fn items_in_the_trash_are_restored() {
+        let (snapshot, mut changes) = snapshot();
+        let mut now = snapshot.at(&changes, time(7));
+        now.items[1].insert("deleted_at".to_string(), json!(time(8)));
+        changes.insert(
+            0,
+            change(
+                Entity::Item,
+                MILK,
+                Action::Delete,
+                json!({ "deleted_at": null }),
+                (Some(1), 5),
+                8,
+            ),
+        );
+
+        let past = now.at(&changes, time(7));
+
+        let items = past.items().unwrap();
+        assert_eq!(items[1].name, "Milk");
+        assert_eq!(items[1].deleted_at, None);
+        assert_eq!(items[1].modified_at, time(6));
+        assert_eq!(items[1].version, 1);
+        assert_eq!(past.budget().unwrap().unwrap().version, 5);
+    }
+
+    #[test]
+    fn changes_after_as_of_are_undone_in_any_order() {
+        let (snapshot, mut changes) = snapshot();
+        // Made in a transaction that began on the 2nd, but was logged after Milk was added.
+        changes.insert(
+            0,
+            change(
+                Entity::Item,
+                BREAD,
+                Action::Update,
+                json!({ "name": "Rolls" }),
+                (Some(2), 5),
+                2,
+            ),
+        );
+        changes[0].created_at += chrono::Duration::hours(1);
+
+        let past = snapshot.at(&changes, time(3));
+
+        let items = past.items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "Bread");
+        assert_eq!(items[0].amount, -20);
+        assert_eq!(items[0].modified_at, time(2) + chrono::Duration::hours(1));
+    }
+
+    #[test]
+    fn rows_created_before_the_audit_log_are_left_out_before_they_were_created() {
+        let (snapshot, _) = snapshot();
+
+        let before_milk = snapshot.clone().at(&[], time(4));
+        let before_budget = snapshot.at(&[], time(1) - chrono::Duration::days(1));
+
+        let items = before_milk.items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "Bread");
+        assert_eq!(before_budget.budget().unwrap(), None);
+        assert!(before_budget.items().unwrap().is_empty());
+    }
+
+    #[test]
+    fn versions_are_restored_from_the_changes() {
+        let (mut snapshot, changes) = snapshot();
+        snapshot.budget.as_mut().unwrap()["version"] = json!(9);
+        snapshot.items[0]["version"] = json!(7);
+
+        let past = snapshot.at(&changes, time(3));
+
+        assert_eq!(past.budget().unwrap().unwrap().version, 2);
+        assert_eq!(past.items().unwrap()[0].version, 1);
+    }
+}
@@ -0,0 +1,236 @@
+use super::{dto::ImportItems, model::ImportRowError};
+use crate::money::{parse_amount, Currency};
+use csv::{ErrorKind, ReaderBuilder, StringRecord, Trim};
+
+/// The most rows that can be imported at once.
+pub const MAX_IMPORT_ROWS: usize = 1000;
+
+/// A row of an import, before its category has been looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// Line of the row, counting the header as line 1.
+    pub line: u64,
+    pub category: String,
+    pub name: String,
+    pub amount: i64,
+}
+
+/// Parse the rows of `csv`, with the amounts in `currency`.
+///
+/// Rows that are not valid are returned as errors, so all of them can be
+/// reported at once. Problems with the file itself, like a missing column,
+/// fail the whole import.
+pub fn parse_rows(
+    csv: &str,
+    options: &ImportItems,
+    currency: Currency,
+) -> Result<Vec<Result<Row, ImportRowError>>, String> {
+    if !options.delimiter.is_ascii() {
+        return Err(format!(
+            "The delimiter '{}' must be an ASCII character",
+            options.delimiter
+        ));
+    }
+    if !matches!(options.decimal_separator, '.' | ',') {
+        return Err(format!(
+            "The decimal separator '{}' must be either '.' or ','",
+            options.decimal_separator
+        ));
+    }
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(options.delimiter as u8)
+        .trim(Trim::All)
+        .from_reader(csv.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|error| format!("Invalid header: {error}"))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Missing column '{name}'"))
+    };
+    let columns = Columns {
+        category: column(&options.category)?,
+        name: column(&options.name)?,
+        amount: column(&options.amount)?,
+    };
+
+    let mut rows = vec![];
+    for record in reader.records() {
+        if rows.len() == MAX_IMPORT_ROWS {
+            return Err(format!(
+                "At most {MAX_IMPORT_ROWS} rows can be imported at once"
+            ));
+        }
+        let row = record
+            .map_err(|error| ImportRowError {
+                line: error.position().map_or(0, |position| position.line()),
+                message: match error.kind() {
+                    ErrorKind::UnequalLengths {
+                        expected_len, len, ..
+                    } => format!("Expected {expected_len} fields, but found {len}"),
+                    _ => error.to_string(),
+                },
+            })
+            .and_then(|record| columns.parse(&record, options, currency));
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Index of the columns in a row.
+struct Columns {
+    category: usize,
+    name: usize,
+    amount: usize,
+}
+
+impl Columns {
+    fn parse(
+        &self,
+        record: &StringRecord,
+        options: &ImportItems,
+        currency: Currency,
+    ) -> Result<Row, ImportRowError> {
+        let line = record.position().map_or(0, |position| position.line());
+        let error = |message: String| ImportRowError { line, message };
+        let field = |index: usize| record.get(index).unwrap_or_default();
+
+        let category = field(self.category);
+        if category.is_empty() {
+            return Err(error("Missing category".to_string()));
+        }
+        let name = field(self.name);
+        if name.is_empty() {
+            return Err(error("Missing name".to_string()));
+        }
+        let amount =
+            parse_amount(field(self.amount), currency, options.decimal_separator).map_err(error)?;
+
+        Ok(Row {
+            line,
+            category: category.to_string(),
+            name: name.to_string(),
+            amount,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn row(line: u64, category: &str, name: &str, amount: i64) -> Result<Row, ImportRowError> {
+        Ok(Row {
+            line,
+            category: category.to_string(),
+            name: name.to_string(),
+            amount,
+        })
+    }
+
+    fn row_error(line: u64, message: &str) -> Result<Row, ImportRowError> {
+        Err(ImportRowError {
+            line,
+            message: message.to_string(),
+        })
+    }
+
+    #[test]
+    fn parse_rows_with_default_options() {
+        let csv = "category,name,amount\nHome,Rent,-8500.00\nIncome, Paycheck ,\"25,000\"\n";
+
+        let rows = parse_rows(csv, &ImportItems::default(), Currency::DKK).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                row(2, "Home", "Rent", -850000),
+                row(3, "Income", "Paycheck", 2500000),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_rows_with_danish_format_and_custom_headers() {
+        let csv = "Dato;Kategori;Navn;Beløb\n2024-01-01;Bolig;Husleje;-8.500,00\n2024-01-02;Mad;Indkøb;-1.234,5\n";
+        let options = ImportItems {
+            delimiter: ';',
+            decimal_separator: ',',
+            category: "Kategori".to_string(),
+            name: "navn".to_string(),
+            amount: "Beløb".to_string(),
+            ..ImportItems::default()
+        };
+
+        let rows = parse_rows(csv, &options, Currency::DKK).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                row(2, "Bolig", "Husleje", -850000),
+                row(3, "Mad", "Indkøb", -123450),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_rows_are_returned_as_errors() {
+        let csv = "category,name,amount\nHome,Rent,abc\n,Water,10\nHome,,10\nHome,Heat\nFood,Groceries,12.5\n";
+
+        let rows = parse_rows(csv, &ImportItems::default(), Currency::DKK).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                row_error(2, "Invalid amount 'abc'"),
+                row_error(3, "Missing category"),
+                row_error(4, "Missing name"),
+                row_error(5, "Expected 3 fields, but found 2"),
+                row(6, "Food", "Groceries", 1250),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_column_fails_the_import() {
+        let csv = "category,title,amount\nHome,Rent,10\n";
+
+        let result = parse_rows(csv, &ImportItems::default(), Currency::DKK);
+
+        assert_eq!(result, Err("Missing column 'name'".to_string()));
+    }
+
+    #[test]
+    fn too_many_rows_fails_the_import() {
+        let csv = format!(
+            "category,name,amount\n{}",
+            "Home,Rent,10\n".repeat(MAX_IMPORT_ROWS + 1)
+        );
+
+        let result = parse_rows(&csv, &ImportItems::default(), Currency::DKK);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn invalid_options_fail_the_import() {
+        let csv = "category,name,amount\n";
+        for options in [
+            ImportItems {
+                delimiter: 'ø',
+                ..ImportItems::default()
+            },
+            ImportItems {
+                decimal_separator: ';',
+                ..ImportItems::default()
+            },
+        ] {
+            assert!(parse_rows(csv, &options, Currency::DKK).is_err());
+        }
+    }
+}
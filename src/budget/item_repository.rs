@@ -1,5 +1,15 @@
-use super::{dto, model};
-use sqlx::PgPool;
+use super::{
+    dto, import,
+    member::{role_in_budget, Role},
+    model,
+    schedule::Rule,
+};
+use crate::{
+    audit,
+    error::{ApiError, DatabaseError},
+    money::Currency,
+};
+use sqlx::{PgPool, Postgres, Transaction};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -8,6 +18,41 @@ pub enum ItemRepositoryError {
     Database,
     NotFound,
     Unauthorized(String),
+    /// The user has access to the budget, but their role does not allow the operation.
+    Forbidden(String),
+    Conflict(String),
+    Validation(String),
+    /// The item does not have any of the versions that the change was made for.
+    PreconditionFailed,
+}
+
+impl From<sqlx::Error> for ItemRepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        match DatabaseError::from(error) {
+            DatabaseError::NotFound => ItemRepositoryError::NotFound,
+            DatabaseError::Conflict(message) => ItemRepositoryError::Conflict(message),
+            DatabaseError::Validation(message) => ItemRepositoryError::Validation(message),
+            DatabaseError::Other => ItemRepositoryError::Database,
+        }
+    }
+}
+
+impl From<ItemRepositoryError> for ApiError {
+    fn from(error: ItemRepositoryError) -> Self {
+        match error {
+            ItemRepositoryError::Database => ApiError::Internal,
+            ItemRepositoryError::NotFound => ApiError::NotFound("Item not found".to_string()),
+            ItemRepositoryError::Unauthorized(user_id) => ApiError::Forbidden(format!(
+                "User '{user_id}' does not have access to the budget"
+            )),
+            ItemRepositoryError::Forbidden(message) => ApiError::Forbidden(message),
+            ItemRepositoryError::Conflict(message) => ApiError::Conflict(message),
+            ItemRepositoryError::Validation(message) => ApiError::BadRequest(message),
+            ItemRepositoryError::PreconditionFailed => ApiError::PreconditionFailed(
+                "The item has been changed since it was read".to_string(),
+            ),
+        }
+    }
 }
 
 /// Repository to access items.
@@ -22,145 +67,466 @@ impl ItemRepository {
         Self { db_pool }
     }
 
-    /// Get the item by its id.
-    #[allow(dead_code)]
-    pub async fn get_item(&self, budget_id: Uuid, item_id: Uuid) -> Option<model::Item> {
+    /// Get the item by its id, unless it is in the trash.
+    pub async fn get_item(
+        &self,
+        budget_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<Option<model::Item>, ItemRepositoryError> {
         let query = sqlx::query_as!(
             model::Item,
-            r#"SELECT * FROM item WHERE id = $1 AND budget_id = $2 "#,
+            r#"SELECT i.id, i.budget_id, i.category_id, c.name as category, i.name, i.amount, i.currency as "currency: Currency",
+    i.schedule_start, i.schedule_rule as "schedule_rule: Rule", i.created_at, i.modified_at, i.version
+FROM item AS i
+JOIN category AS c ON c.id = i.category_id
+WHERE i.id = $1 AND i.budget_id = $2 AND i.deleted_at IS NULL"#,
             item_id,
             budget_id
         );
 
-        match query.fetch_one(self.db_pool.as_ref()).await {
-            Ok(item) => Some(item),
-            Err(_) => None,
+        Ok(query.fetch_optional(self.db_pool.as_ref()).await?)
+    }
+
+    /// Get an item of a budget that the user has any role in.
+    /// Items of budgets the user has no access to are not revealed.
+    pub async fn get_item_for_user(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<model::Item, ItemRepositoryError> {
+        match role_in_budget(self.db_pool.as_ref(), &budget_id, user_id).await? {
+            Some(_) => self
+                .get_item(budget_id, item_id)
+                .await?
+                .ok_or(ItemRepositoryError::NotFound),
+            None => Err(ItemRepositoryError::NotFound),
         }
     }
 
-    /// Add a new item to a budget.
+    /// Add a new item to a budget, and return it. The item uses the currency of
+    /// the budget, unless another currency is given, and recurs if it has a schedule.
     pub async fn add_item_to_budget(
         &self,
         user_id: &str,
         budget_id: Uuid,
         payload: dto::AddItemToBudgetRequest,
-    ) -> Result<Uuid, ItemRepositoryError> {
-        if !self.check_access(budget_id, user_id).await {
-            return Err(ItemRepositoryError::Unauthorized(user_id.to_string()));
-        }
+    ) -> Result<model::Item, ItemRepositoryError> {
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        self.check_access(&mut transaction, budget_id, user_id)
+            .await?;
+        self.check_category(&mut transaction, budget_id, payload.category_id)
+            .await?;
 
-        let query = sqlx::query_scalar!(
-            "INSERT INTO item (budget_id, category, name, amount) VALUES ($1, $2, $3, $4) RETURNING id",
+        let query = sqlx::query_as!(
+            model::Item,
+            r#"WITH i AS (
+    INSERT INTO item (budget_id, category_id, name, amount, currency, schedule_start, schedule_rule)
+    VALUES ($1, $2, $3, $4, $5, $6, $7)
+    RETURNING *
+)
+SELECT i.id, i.budget_id, i.category_id, c.name as category, i.name, i.amount, i.currency as "currency: Currency",
+    i.schedule_start, i.schedule_rule as "schedule_rule: Rule", i.created_at, i.modified_at, i.version
+FROM i
+JOIN category AS c ON c.id = i.category_id"#,
             budget_id,
-            payload.category,
+            payload.category_id,
             payload.name,
-            payload.amount
+            payload.amount,
+            payload.currency.as_ref().map(Currency::code),
+            payload.schedule.as_ref().map(|s| s.start),
+            payload.schedule.as_ref().map(|s| s.rule.to_string())
         );
 
-        match query.fetch_one(self.db_pool.as_ref()).await {
-            Ok(id) => Ok(id),
-            Err(err) => {
-                tracing::error!("Error adding item to budget: {err:?}");
-                Err(ItemRepositoryError::Database)
-            }
-        }
+        let item = query.fetch_one(&mut transaction).await?;
+        transaction.commit().await?;
+
+        Ok(item)
     }
 
-    /// Delete an item.
+    /// Move an item to the trash, from where it can be restored with
+    /// [`restore_item`](Self::restore_item) until it is purged.
+    /// If `versions` are given, it is only deleted if it has one of them.
     pub async fn delete_item(
         &self,
         user_id: &str,
         budget_id: Uuid,
         item_id: Uuid,
+        versions: Option<&[i64]>,
     ) -> Result<(), ItemRepositoryError> {
         tracing::trace!("[item_repository] User '{user_id}' deleting item '{item_id}' from budget '{budget_id}'");
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        // Items of budgets the user has no access to are not revealed.
+        self.check_access(&mut transaction, budget_id, user_id)
+            .await
+            .map_err(|error| match error {
+                ItemRepositoryError::Unauthorized(_) => ItemRepositoryError::NotFound,
+                error => error,
+            })?;
         let query = sqlx::query!(
-            r#"with deleted as
-            (delete from item
-               where id = $1
-                 and exists(select * from budget where id = $2 and user_id = $3)
-               returning *)
-            select count(*) from deleted"#,
+            "UPDATE item SET deleted_at = current_timestamp
+WHERE id = $1 AND budget_id = $2 AND deleted_at IS NULL AND ($3::bigint[] IS NULL OR version = ANY($3))",
             item_id,
             budget_id,
-            user_id
+            versions
         );
 
-        match query.fetch_one(self.db_pool.as_ref()).await {
-            Ok(x) => match x.count {
-                Some(1) => Ok(()),
-                _ => {
-                    tracing::error!("Item '{item_id}' does not exists");
-                    Err(ItemRepositoryError::NotFound)
-                }
-            },
-            Err(err) => {
-                tracing::error!("Error: {err:?}");
-                Err(ItemRepositoryError::Database)
+        match query.execute(&mut transaction).await?.rows_affected() {
+            1 => Ok(transaction.commit().await?),
+            _ => Err(self.not_changed(budget_id, item_id).await),
+        }
+    }
+
+    /// Restore an item from the trash, and return it.
+    pub async fn restore_item(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<model::Item, ItemRepositoryError> {
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        // Items of budgets the user has no access to are not revealed.
+        self.check_access(&mut transaction, budget_id, user_id)
+            .await
+            .map_err(|error| match error {
+                ItemRepositoryError::Unauthorized(_) => ItemRepositoryError::NotFound,
+                error => error,
+            })?;
+        let query = sqlx::query_as!(
+            model::Item,
+            r#"WITH i AS (
+    UPDATE item SET deleted_at = NULL WHERE id = $1 AND budget_id = $2 AND deleted_at IS NOT NULL
+    RETURNING *
+)
+SELECT i.id, i.budget_id, i.category_id, c.name as category, i.name, i.amount, i.currency as "currency: Currency",
+    i.schedule_start, i.schedule_rule as "schedule_rule: Rule", i.created_at, i.modified_at, i.version
+FROM i
+JOIN category AS c ON c.id = i.category_id"#,
+            item_id,
+            budget_id
+        );
+
+        match query.fetch_optional(&mut transaction).await? {
+            None => Err(ItemRepositoryError::NotFound),
+            Some(item) => {
+                transaction.commit().await?;
+                Ok(item)
             }
         }
     }
 
-    /// Update an item. Can be provided with a new name, category, or amount.
+    /// Replace the name, category, amount, and schedule of an item, and its currency if one is given,
+    /// and return the updated item.
+    /// The schedule is replaced, so an item updated without one no longer recurs.
+    /// If `versions` are given, the item is only updated if it has one of them.
     pub async fn update_item(
         &self,
         user_id: &str,
         budget_id: Uuid,
         item_id: Uuid,
         request: dto::AddItemToBudgetRequest,
-    ) -> Result<(), ItemRepositoryError> {
-        if !self.check_access(budget_id, user_id).await {
-            return Err(ItemRepositoryError::Unauthorized(user_id.to_string()));
-        }
+        versions: Option<&[i64]>,
+    ) -> Result<model::Item, ItemRepositoryError> {
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        self.check_access(&mut transaction, budget_id, user_id)
+            .await?;
+        self.check_category(&mut transaction, budget_id, request.category_id)
+            .await?;
 
-        let query = sqlx::query!(
-            "UPDATE item SET category = $1, amount = $2, name = $3 WHERE id = $4",
-            request.category,
+        let query = sqlx::query_as!(
+            model::Item,
+            r#"WITH i AS (
+    UPDATE item
+    SET category_id = $1, amount = $2, name = $3, currency = COALESCE($6, currency),
+        schedule_start = $7, schedule_rule = $8
+    WHERE id = $4 AND budget_id = $5 AND deleted_at IS NULL AND ($9::bigint[] IS NULL OR version = ANY($9))
+    RETURNING *
+)
+SELECT i.id, i.budget_id, i.category_id, c.name as category, i.name, i.amount, i.currency as "currency: Currency",
+    i.schedule_start, i.schedule_rule as "schedule_rule: Rule", i.created_at, i.modified_at, i.version
+FROM i
+JOIN category AS c ON c.id = i.category_id"#,
+            request.category_id,
             request.amount,
             request.name,
-            item_id
+            item_id,
+            budget_id,
+            request.currency.as_ref().map(Currency::code),
+            request.schedule.as_ref().map(|s| s.start),
+            request.schedule.as_ref().map(|s| s.rule.to_string()),
+            versions
         );
 
-        match query.execute(self.db_pool.as_ref()).await {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                tracing::error!("Error: {err:?}");
-                Err(ItemRepositoryError::Database)
+        match query.fetch_optional(&mut transaction).await? {
+            None => Err(self.not_changed(budget_id, item_id).await),
+            Some(item) => {
+                transaction.commit().await?;
+                Ok(item)
             }
         }
     }
 
-    async fn check_access(&self, budget_id: Uuid, user_id: &str) -> bool {
-        let budget_query = sqlx::query!(
-            "SELECT * FROM budget WHERE id = $1 AND user_id = $2",
+    /// Change the fields of an item that are given, and return the updated item.
+    /// If `versions` are given, the item is only changed if it has one of them.
+    pub async fn patch_item(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        item_id: Uuid,
+        request: &dto::PatchItem,
+        versions: Option<&[i64]>,
+    ) -> Result<model::Item, ItemRepositoryError> {
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        self.check_access(&mut transaction, budget_id, user_id)
+            .await?;
+        if let Some(category_id) = request.category_id {
+            self.check_category(&mut transaction, budget_id, category_id)
+                .await?;
+        }
+
+        let schedule = request.schedule.as_ref().and_then(|s| s.as_ref());
+        let query = sqlx::query_as!(
+            model::Item,
+            r#"WITH i AS (
+    UPDATE item
+    SET category_id = COALESCE($3, category_id),
+        name = COALESCE($4, name),
+        amount = COALESCE($5, amount),
+        currency = COALESCE($6, currency),
+        schedule_start = CASE WHEN $7 THEN $8 ELSE schedule_start END,
+        schedule_rule = CASE WHEN $7 THEN $9 ELSE schedule_rule END
+    WHERE id = $1 AND budget_id = $2 AND deleted_at IS NULL AND ($10::bigint[] IS NULL OR version = ANY($10))
+    RETURNING *
+)
+SELECT i.id, i.budget_id, i.category_id, c.name as category, i.name, i.amount, i.currency as "currency: Currency",
+    i.schedule_start, i.schedule_rule as "schedule_rule: Rule", i.created_at, i.modified_at, i.version
+FROM i
+JOIN category AS c ON c.id = i.category_id"#,
+            item_id,
             budget_id,
-            user_id,
+            request.category_id,
+            request.name,
+            request.amount,
+            request.currency.as_ref().map(Currency::code),
+            request.schedule.is_some(),
+            schedule.map(|s| s.start),
+            schedule.map(|s| s.rule.to_string()),
+            versions
         );
 
-        match budget_query.fetch_optional(self.db_pool.as_ref()).await {
-            Ok(Some(_)) => {
+        match query.fetch_optional(&mut transaction).await? {
+            None => Err(self.not_changed(budget_id, item_id).await),
+            Some(item) => {
+                transaction.commit().await?;
+                Ok(item)
+            }
+        }
+    }
+
+    /// Import items into a budget from CSV, with the categories given by name.
+    ///
+    /// The rows are all created in one transaction, and only if all of them
+    /// are valid. A dry run only parses and validates the rows.
+    pub async fn import_items(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        csv: &str,
+        options: &dto::ImportItems,
+    ) -> Result<model::ImportResult, ItemRepositoryError> {
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        self.check_access(&mut transaction, budget_id, user_id)
+            .await?;
+
+        let currency = sqlx::query_scalar!(
+            r#"SELECT currency as "currency: Currency" FROM budget WHERE id = $1"#,
+            budget_id
+        )
+        .fetch_one(&mut transaction)
+        .await?;
+        let parsed =
+            import::parse_rows(csv, options, currency).map_err(ItemRepositoryError::Validation)?;
+
+        let categories = sqlx::query!(
+            "SELECT c.id, c.name FROM category AS c JOIN budget AS b ON b.user_id = c.user_id WHERE b.id = $1",
+            budget_id
+        )
+        .fetch_all(&mut transaction)
+        .await?;
+
+        let mut rows = vec![];
+        let mut errors = vec![];
+        for row in parsed {
+            let category = row.and_then(|row| {
+                categories
+                    .iter()
+                    .find(|category| category.name.to_lowercase() == row.category.to_lowercase())
+                    .map(|category| (category.id, category.name.to_owned(), row.clone()))
+                    .ok_or_else(|| model::ImportRowError {
+                        line: row.line,
+                        message: format!("Unknown category '{}'", row.category),
+                    })
+            });
+            match category {
+                Ok((category_id, category, row)) => rows.push(model::ImportedRow {
+                    line: row.line,
+                    category_id,
+                    category,
+                    name: row.name,
+                    amount: row.amount,
+                    currency,
+                }),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        if options.dry_run {
+            return Ok(model::ImportResult {
+                dry_run: true,
+                created: 0,
+                rows,
+                errors,
+            });
+        }
+        if let Some(first) = errors.first() {
+            return Err(ItemRepositoryError::Validation(format!(
+                "{} of the rows are not valid, the first on line {}: {}",
+                errors.len(),
+                first.line,
+                first.message
+            )));
+        }
+
+        tracing::trace!(
+            "[item_repository] User '{user_id}' importing {} items into budget '{budget_id}'",
+            rows.len()
+        );
+        for row in &rows {
+            sqlx::query!(
+                "INSERT INTO item (budget_id, category_id, name, amount, currency) VALUES ($1, $2, $3, $4, $5)",
+                budget_id,
+                row.category_id,
+                row.name,
+                row.amount,
+                row.currency.code()
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+
+        Ok(model::ImportResult {
+            dry_run: false,
+            created: rows.len(),
+            rows,
+            errors,
+        })
+    }
+
+    /// Why an item was not changed: either it no longer exists, or it does not
+    /// have the version the change was made for.
+    async fn not_changed(&self, budget_id: Uuid, item_id: Uuid) -> ItemRepositoryError {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM item WHERE id = $1 AND budget_id = $2 AND deleted_at IS NULL) as "exists!""#,
+            item_id,
+            budget_id
+        )
+        .fetch_one(self.db_pool.as_ref())
+        .await;
+
+        match exists {
+            Ok(true) => ItemRepositoryError::PreconditionFailed,
+            Ok(false) => ItemRepositoryError::NotFound,
+            Err(error) => error.into(),
+        }
+    }
+
+    /// Check that the user is an owner or editor of the budget, which is
+    /// required to do any operation on its items. The budget is locked until
+    /// the `transaction` of the operation ends, so it is not moved to the trash
+    /// in the meantime.
+    async fn check_access(
+        &self,
+        transaction: &mut Transaction<'static, Postgres>,
+        budget_id: Uuid,
+        user_id: &str,
+    ) -> Result<(), ItemRepositoryError> {
+        let role = match sqlx::query!(
+            "SELECT id FROM budget WHERE id = $1 AND deleted_at IS NULL FOR NO KEY UPDATE",
+            budget_id
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        {
+            Ok(_) => role_in_budget(&mut *transaction, &budget_id, user_id).await,
+            Err(err) => Err(err),
+        };
+        match role {
+            Ok(Some(role)) if role >= Role::Editor => {
                 tracing::trace!("User '{user_id}' has access to '{budget_id}'");
-                true
+                Ok(())
+            }
+            Ok(Some(_)) => {
+                tracing::warn!("User '{user_id}' can only view '{budget_id}'");
+                Err(ItemRepositoryError::Forbidden(format!(
+                    "User '{user_id}' can only view the budget"
+                )))
             }
             Ok(None) => {
                 tracing::warn!("User '{user_id}' does not have access to '{budget_id}'");
-                false
+                Err(ItemRepositoryError::Unauthorized(user_id.to_string()))
             }
             Err(err) => {
                 tracing::error!(
                     "Error check access for user '{user_id}' to budget '{budget_id}': {err:?}"
                 );
-                false
+                Err(ItemRepositoryError::Database)
             }
         }
     }
+
+    /// Check that the category belongs to the owner of the budget, so items of
+    /// a shared budget are put in the owner's categories, whoever adds them.
+    async fn check_category(
+        &self,
+        transaction: &mut Transaction<'static, Postgres>,
+        budget_id: Uuid,
+        category_id: Uuid,
+    ) -> Result<(), ItemRepositoryError> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+    SELECT 1 FROM category AS c JOIN budget AS b ON b.user_id = c.user_id WHERE c.id = $1 AND b.id = $2
+) as "exists!""#,
+            category_id,
+            budget_id,
+        )
+        .fetch_one(transaction)
+        .await?;
+
+        match exists {
+            true => Ok(()),
+            false => Err(ItemRepositoryError::Validation(format!(
+                "Unknown category '{category_id}'"
+            ))),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::budget::schedule::Schedule;
     use tracing_test::traced_test;
 
+    const HOME: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02";
+    const FOOD: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03";
+    /// Category owned by Bob.
+    const OTHER_USERS_FOOD: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04";
+
+    fn category(id: &str) -> Uuid {
+        Uuid::parse_str(id).unwrap()
+    }
+
     #[sqlx::test(fixtures("budget_with_items"))]
     #[cfg_attr(not(feature = "db_test"), ignore)]
     async fn get_item_with_an_id(pool: PgPool) -> sqlx::Result<()> {
@@ -170,9 +536,10 @@ mod test {
         let id = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
 
         // Act
-        let item = repo.get_item(budget_id, id).await.unwrap();
+        let item = repo.get_item(budget_id, id).await.unwrap().unwrap();
 
         // Assert
+        assert_eq!(item.category_id, category(FOOD));
         assert_eq!(item.category, "Food");
         assert_eq!(item.name, "Restaurants");
         assert_eq!(item.amount, 10);
@@ -188,43 +555,210 @@ mod test {
         let user_id = "Alice";
         let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
 
-        let request = dto::AddItemToBudgetRequest::new(
-            "Some category".to_string(),
-            "Some name".to_string(),
-            123,
-        );
+        let request =
+            dto::AddItemToBudgetRequest::new(category(HOME), "Some name".to_string(), 123);
 
         // Act
-        let item_id = repo
+        let added = repo
             .add_item_to_budget(user_id, budget_id, request.clone())
             .await
             .unwrap();
 
         // Assert
         // Get the item that was just created
-        let item = repo.get_item(budget_id, item_id).await.unwrap();
-        assert_eq!(item.category, request.category);
+        let item = repo.get_item(budget_id, added.id).await.unwrap().unwrap();
+        assert_eq!(item, added);
+        assert_eq!(item.category_id, request.category_id);
         assert_eq!(item.name, request.name);
         assert_eq!(item.amount, request.amount);
+        assert_eq!(item.currency, Currency::DKK);
 
         Ok(())
     }
 
     #[sqlx::test(fixtures("budget_with_items"))]
     #[cfg_attr(not(feature = "db_test"), ignore)]
-    #[traced_test]
-    async fn try_add_a_new_item_to_a_budget_as_other_user(pool: PgPool) -> sqlx::Result<()> {
+    async fn add_a_new_item_in_another_currency(pool: PgPool) -> sqlx::Result<()> {
         // Arrange
         let repo = ItemRepository::new(Arc::new(pool));
-        let user_id = "Bob";
         let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let currency = "EUR".parse().unwrap();
+        let request = dto::AddItemToBudgetRequest {
+            currency: Some(currency),
+            ..dto::AddItemToBudgetRequest::new(category(FOOD), "Hotel".to_string(), 12_000_000_000)
+        };
+
+        // Act
+        let item_id = repo
+            .add_item_to_budget("Alice", budget_id, request)
+            .await
+            .unwrap()
+            .id;
 
+        // Assert
+        let item = repo.get_item(budget_id, item_id).await.unwrap().unwrap();
+        assert_eq!(item.currency, currency);
+        assert_eq!(item.amount, 12_000_000_000);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_add_a_new_item_in_category_of_other_user(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
         let request = dto::AddItemToBudgetRequest::new(
-            "Some category".to_string(),
+            category(OTHER_USERS_FOOD),
             "Some name".to_string(),
             123,
         );
 
+        // Act
+        let error = repo
+            .add_item_to_budget("Alice", budget_id, request)
+            .await
+            .unwrap_err();
+
+        // Assert
+        assert_eq!(
+            error,
+            ItemRepositoryError::Validation(format!("Unknown category '{OTHER_USERS_FOOD}'"))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn editors_but_not_viewers_can_change_items(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let item_id = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
+        let request =
+            dto::AddItemToBudgetRequest::new(category(OTHER_USERS_FOOD), "Bread".to_string(), -50);
+
+        // Act
+        let by_viewer = repo.add_item_to_budget("Bob", budget_id, request).await;
+        let delete_by_viewer = repo.delete_item("Bob", budget_id, item_id, None).await;
+        let delete_by_editor = repo.delete_item("Carol", budget_id, item_id, None).await;
+
+        // Assert
+        assert!(matches!(by_viewer, Err(ItemRepositoryError::Forbidden(_))));
+        assert!(matches!(
+            delete_by_viewer,
+            Err(ItemRepositoryError::Forbidden(_))
+        ));
+        assert_eq!(delete_by_editor, Ok(()));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn editors_add_items_in_categories_of_the_budget_owner(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let owners = dto::AddItemToBudgetRequest::new(category(FOOD), "Bread".to_string(), -50);
+        let others =
+            dto::AddItemToBudgetRequest::new(category(OTHER_USERS_FOOD), "Milk".to_string(), -20);
+
+        // Act
+        let item = repo
+            .add_item_to_budget("Carol", budget_id, owners)
+            .await
+            .unwrap();
+        let error = repo
+            .add_item_to_budget("Carol", budget_id, others)
+            .await
+            .unwrap_err();
+
+        // Assert
+        assert_eq!(item.category_id, category(FOOD));
+        assert_eq!(
+            error,
+            ItemRepositoryError::Validation(format!("Unknown category '{OTHER_USERS_FOOD}'"))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn viewers_can_get_an_item(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let item_id = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
+
+        // Act
+        let by_viewer = repo.get_item_for_user("Bob", budget_id, item_id).await;
+        let by_stranger = repo.get_item_for_user("Mallory", budget_id, item_id).await;
+
+        // Assert
+        assert_eq!(by_viewer.unwrap().id, item_id);
+        assert_eq!(by_stranger, Err(ItemRepositoryError::NotFound));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn only_change_an_item_that_has_one_of_the_versions(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let item_id = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
+        let version = repo
+            .get_item(budget_id, item_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .version;
+        let request = dto::PatchItem {
+            amount: Some(-100),
+            ..Default::default()
+        };
+
+        // Act
+        let stale = repo
+            .patch_item("Alice", budget_id, item_id, &request, Some(&[version - 1]))
+            .await;
+        let current = repo
+            .patch_item("Alice", budget_id, item_id, &request, Some(&[version]))
+            .await
+            .unwrap();
+        let delete_stale = repo
+            .delete_item("Alice", budget_id, item_id, Some(&[version]))
+            .await;
+        let delete_missing = repo
+            .delete_item("Alice", budget_id, Uuid::new_v4(), Some(&[version]))
+            .await;
+
+        // Assert
+        assert_eq!(stale, Err(ItemRepositoryError::PreconditionFailed));
+        assert_eq!(current.version, version + 1);
+        assert_eq!(delete_stale, Err(ItemRepositoryError::PreconditionFailed));
+        assert_eq!(delete_missing, Err(ItemRepositoryError::NotFound));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    #[traced_test]
+    async fn try_add_a_new_item_to_a_budget_as_other_user(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let user_id = "Bob";
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        let request =
+            dto::AddItemToBudgetRequest::new(category(HOME), "Some name".to_string(), 123);
+
         // Act
         let item = repo
             .add_item_to_budget(user_id, budget_id, request.clone())
@@ -246,10 +780,46 @@ mod test {
         let user_id = "Alice";
 
         // Act
-        assert!(repo.delete_item(user_id, budget_id, item_id).await.is_ok());
+        assert!(repo
+            .delete_item(user_id, budget_id, item_id, None)
+            .await
+            .is_ok());
+
+        // Assert
+        assert_eq!(repo.get_item(budget_id, item_id).await, Ok(None));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn deleted_item_can_be_restored(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let item_id = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
+        repo.delete_item("Alice", budget_id, item_id, None)
+            .await
+            .unwrap();
+
+        // Act
+        let update_deleted = repo
+            .patch_item("Alice", budget_id, item_id, &Default::default(), None)
+            .await;
+        let delete_again = repo.delete_item("Alice", budget_id, item_id, None).await;
+        let by_stranger = repo.restore_item("Mallory", budget_id, item_id).await;
+        let restored = repo
+            .restore_item("Alice", budget_id, item_id)
+            .await
+            .unwrap();
+        let restore_again = repo.restore_item("Alice", budget_id, item_id).await;
 
         // Assert
-        assert_eq!(repo.get_item(budget_id, item_id).await, None);
+        assert_eq!(update_deleted, Err(ItemRepositoryError::NotFound));
+        assert_eq!(delete_again, Err(ItemRepositoryError::NotFound));
+        assert_eq!(by_stranger, Err(ItemRepositoryError::NotFound));
+        assert_eq!(restored.name, "Restaurants");
+        assert_eq!(restore_again, Err(ItemRepositoryError::NotFound));
 
         Ok(())
     }
@@ -267,14 +837,57 @@ mod test {
 
         // Act
         assert_eq!(
-            repo.delete_item(user_id, budget_id, item_id)
+            repo.delete_item(user_id, budget_id, item_id, None)
                 .await
                 .unwrap_err(),
             ItemRepositoryError::NotFound
         );
 
         // Assert
-        assert_ne!(repo.get_item(budget_id, item_id).await, None);
+        assert_ne!(repo.get_item(budget_id, item_id).await, Ok(None));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn add_and_remove_schedule_of_item(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let schedule = Schedule {
+            start: "2024-01-01".parse().unwrap(),
+            rule: "FREQ=MONTHLY".parse().unwrap(),
+        };
+        let request = dto::AddItemToBudgetRequest {
+            schedule: Some(schedule.clone()),
+            ..dto::AddItemToBudgetRequest::new(category(HOME), "Rent".to_string(), -800_000)
+        };
+
+        // Act
+        let item_id = repo
+            .add_item_to_budget("Alice", budget_id, request.clone())
+            .await
+            .unwrap()
+            .id;
+        let recurring = repo.get_item(budget_id, item_id).await.unwrap().unwrap();
+        repo.update_item(
+            "Alice",
+            budget_id,
+            item_id,
+            dto::AddItemToBudgetRequest {
+                schedule: None,
+                ..request
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let one_off = repo.get_item(budget_id, item_id).await.unwrap().unwrap();
+
+        // Assert
+        assert_eq!(recurring.schedule(), Some(schedule));
+        assert_eq!(one_off.schedule(), None);
 
         Ok(())
     }
@@ -287,21 +900,18 @@ mod test {
         let user_id = "Alice";
         let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
         let item_id = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
-        let request = dto::AddItemToBudgetRequest::new(
-            "Updated category".to_string(),
-            "Updated name".to_string(),
-            999,
-        );
+        let request =
+            dto::AddItemToBudgetRequest::new(category(HOME), "Updated name".to_string(), 999);
 
         // Act
         assert!(repo
-            .update_item(user_id, budget_id, item_id, request.clone())
+            .update_item(user_id, budget_id, item_id, request.clone(), None)
             .await
             .is_ok());
 
         // Assert
-        let item = repo.get_item(budget_id, item_id).await.unwrap();
-        assert_eq!(item.category, request.category);
+        let item = repo.get_item(budget_id, item_id).await.unwrap().unwrap();
+        assert_eq!(item.category_id, request.category_id);
         assert_eq!(item.name, request.name);
         assert_eq!(item.amount, request.amount);
 
@@ -317,15 +927,12 @@ mod test {
         let user_id = "Bob";
         let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
         let item_id = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
-        let request = dto::AddItemToBudgetRequest::new(
-            "Updated category".to_string(),
-            "Updated name".to_string(),
-            999,
-        );
+        let request =
+            dto::AddItemToBudgetRequest::new(category(HOME), "Updated name".to_string(), 999);
 
         // Act
         let error = repo
-            .update_item(user_id, budget_id, item_id, request.clone())
+            .update_item(user_id, budget_id, item_id, request.clone(), None)
             .await
             .unwrap_err();
 
@@ -334,11 +941,253 @@ mod test {
             error,
             ItemRepositoryError::Unauthorized(user_id.to_string())
         );
-        let item = repo.get_item(budget_id, item_id).await.unwrap();
-        assert_ne!(item.category, request.category);
+        let item = repo.get_item(budget_id, item_id).await.unwrap().unwrap();
+        assert_ne!(item.category_id, request.category_id);
         assert_ne!(item.name, request.name);
         assert_ne!(item.amount, request.amount);
 
         Ok(())
     }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn update_item_that_is_not_there(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let user_id = "Alice";
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let request =
+            dto::AddItemToBudgetRequest::new(category(HOME), "Updated name".to_string(), 999);
+
+        // Act
+        let error = repo
+            .update_item(user_id, budget_id, Uuid::new_v4(), request, None)
+            .await
+            .unwrap_err();
+
+        // Assert
+        assert_eq!(error, ItemRepositoryError::NotFound);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn patch_only_changes_the_given_fields(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let schedule = Schedule {
+            start: "2024-01-01".parse().unwrap(),
+            rule: "FREQ=MONTHLY".parse().unwrap(),
+        };
+        let item_id = repo
+            .add_item_to_budget(
+                "Alice",
+                budget_id,
+                dto::AddItemToBudgetRequest {
+                    schedule: Some(schedule.clone()),
+                    ..dto::AddItemToBudgetRequest::new(category(HOME), "Rent".to_string(), -800_000)
+                },
+            )
+            .await
+            .unwrap()
+            .id;
+
+        // Act
+        let new_amount = repo
+            .patch_item(
+                "Alice",
+                budget_id,
+                item_id,
+                &dto::PatchItem {
+                    amount: Some(-850_000),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let one_off = repo
+            .patch_item(
+                "Alice",
+                budget_id,
+                item_id,
+                &dto::PatchItem {
+                    schedule: Some(None),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(new_amount.amount, -850_000);
+        assert_eq!(new_amount.name, "Rent");
+        assert_eq!(new_amount.category_id, category(HOME));
+        assert_eq!(new_amount.schedule(), Some(schedule));
+        assert_eq!(one_off.amount, -850_000);
+        assert_eq!(one_off.schedule(), None);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn patch_item_into_category_of_other_user(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let item_id = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
+        let request = dto::PatchItem {
+            category_id: Some(category(OTHER_USERS_FOOD)),
+            ..Default::default()
+        };
+
+        // Act
+        let error = repo
+            .patch_item("Alice", budget_id, item_id, &request, None)
+            .await
+            .unwrap_err();
+        let not_there = repo
+            .patch_item(
+                "Alice",
+                budget_id,
+                Uuid::new_v4(),
+                &Default::default(),
+                None,
+            )
+            .await
+            .unwrap_err();
+
+        // Assert
+        assert!(matches!(error, ItemRepositoryError::Validation(_)));
+        assert_eq!(not_there, ItemRepositoryError::NotFound);
+        assert_eq!(
+            repo.get_item(budget_id, item_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .category_id,
+            category(FOOD)
+        );
+
+        Ok(())
+    }
+
+    async fn count_items(repo: &ItemRepository, budget_id: Uuid) -> i64 {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM item WHERE budget_id = $1"#,
+            budget_id
+        )
+        .fetch_one(repo.db_pool.as_ref())
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn import_items_with_categories_by_name(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let csv = "category,name,amount\nhome,Water,-250.50\nFood,Groceries,-3000\n";
+
+        // Act
+        let result = repo
+            .import_items("Alice", budget_id, csv, &dto::ImportItems::default())
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(result.created, 2);
+        assert!(result.errors.is_empty());
+        assert_eq!(result.rows[0].category_id, category(HOME));
+        assert_eq!(result.rows[0].category, "Home");
+        assert_eq!(result.rows[0].amount, -25050);
+        assert_eq!(result.rows[1].category_id, category(FOOD));
+        assert_eq!(count_items(&repo, budget_id).await, 5);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn import_items_as_dry_run(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let csv = "category,name,amount\nHome,Water,-250\nTravel,Hotel,-1200\n";
+        let options = dto::ImportItems {
+            dry_run: true,
+            ..dto::ImportItems::default()
+        };
+
+        // Act
+        let result = repo
+            .import_items("Alice", budget_id, csv, &options)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(result.created, 0);
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(
+            result.errors,
+            vec![model::ImportRowError {
+                line: 3,
+                message: "Unknown category 'Travel'".to_string()
+            }]
+        );
+        assert_eq!(count_items(&repo, budget_id).await, 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn import_with_invalid_rows_creates_nothing(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let csv = "category,name,amount\nHome,Water,-250\nHome,Heat,abc\n";
+
+        // Act
+        let error = repo
+            .import_items("Alice", budget_id, csv, &dto::ImportItems::default())
+            .await
+            .unwrap_err();
+
+        // Assert
+        assert_eq!(
+            error,
+            ItemRepositoryError::Validation(
+                "1 of the rows are not valid, the first on line 3: Invalid amount 'abc'"
+                    .to_string()
+            )
+        );
+        assert_eq!(count_items(&repo, budget_id).await, 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn viewers_cannot_import_items(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let repo = ItemRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let csv = "category,name,amount\nFood,Bread,-50\n";
+
+        // Act
+        let result = repo
+            .import_items("Bob", budget_id, csv, &dto::ImportItems::default())
+            .await;
+
+        // Assert
+        assert!(matches!(result, Err(ItemRepositoryError::Forbidden(_))));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,102 @@
+use crate::text_type::text_type;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use std::{fmt, str::FromStr};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// What a member of a budget is allowed to do. Each role can do everything
+/// the roles before it can, so they can be compared with `>=`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Can read the budget, its items, and transactions.
+    Viewer,
+    /// Can also change the budget, its items, and transactions.
+    Editor,
+    /// Can also delete the budget and manage its members.
+    Owner,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Owner => "owner",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "viewer" => Ok(Role::Viewer),
+            "editor" => Ok(Role::Editor),
+            "owner" => Ok(Role::Owner),
+            _ => Err(format!("Unknown role '{value}'")),
+        }
+    }
+}
+
+text_type!(Role);
+
+/// The role of a user in a budget, or `None` if the user has no access to it.
+/// The user who created a budget is always an owner, and invited users get
+/// their role once they have accepted the invitation.
+pub(crate) async fn role_in_budget(
+    executor: impl PgExecutor<'_>,
+    budget_id: &Uuid,
+    user_id: &str,
+) -> Result<Option<Role>, sqlx::Error> {
+    let roles = sqlx::query_scalar!(
+        r#"SELECT role as "role!: Role" FROM budget_access WHERE budget_id = $1 AND user_id = $2"#,
+        budget_id,
+        user_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(roles.into_iter().max())
+}
+
+/// The role of a user in a budget that is in the trash, or `None` if the
+/// budget is not in the trash or the user has no access to it.
+pub(crate) async fn role_in_trashed_budget(
+    db_pool: &PgPool,
+    budget_id: &Uuid,
+    user_id: &str,
+) -> Result<Option<Role>, sqlx::Error> {
+    let roles = sqlx::query_scalar!(
+        r#"SELECT role as "role!: Role" FROM trashed_budget_access WHERE budget_id = $1 AND user_id = $2"#,
+        budget_id,
+        user_id
+    )
+    .fetch_all(db_pool)
+    .await?;
+
+    Ok(roles.into_iter().max())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roles_are_ordered_by_permissions() {
+        assert!(Role::Owner > Role::Editor);
+        assert!(Role::Editor > Role::Viewer);
+        assert_eq!("editor".parse(), Ok(Role::Editor));
+        assert!("admin".parse::<Role>().is_err());
+    }
+}
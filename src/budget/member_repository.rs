@@ -0,0 +1,364 @@
+use super::{
+    dto,
+    member::{role_in_budget, Role},
+    model,
+};
+use crate::error::{ApiError, DatabaseError};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MemberRepositoryError {
+    Database,
+    /// The budget does not exist, or the user does not have access to it.
+    BudgetNotFound,
+    NotFound,
+    /// The user has access to the budget, but their role does not allow the operation.
+    Forbidden(String),
+    Conflict(String),
+    Validation(String),
+}
+
+impl From<sqlx::Error> for MemberRepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        match DatabaseError::from(error) {
+            DatabaseError::NotFound => MemberRepositoryError::NotFound,
+            DatabaseError::Conflict(message) => MemberRepositoryError::Conflict(message),
+            DatabaseError::Validation(message) => MemberRepositoryError::Validation(message),
+            DatabaseError::Other => MemberRepositoryError::Database,
+        }
+    }
+}
+
+impl From<MemberRepositoryError> for ApiError {
+    fn from(error: MemberRepositoryError) -> Self {
+        match error {
+            MemberRepositoryError::Database => ApiError::Internal,
+            MemberRepositoryError::BudgetNotFound => {
+                ApiError::NotFound("Budget not found".to_string())
+            }
+            MemberRepositoryError::NotFound => ApiError::NotFound("Member not found".to_string()),
+            MemberRepositoryError::Forbidden(message) => ApiError::Forbidden(message),
+            MemberRepositoryError::Conflict(message) => ApiError::Conflict(message),
+            MemberRepositoryError::Validation(message) => ApiError::BadRequest(message),
+        }
+    }
+}
+
+/// Repository to share budgets with other users.
+///
+/// Owners invite users with a role, and the users get access to the budget
+/// once they accept the invitation.
+#[derive(Debug)]
+pub struct MemberRepository {
+    db_pool: Arc<PgPool>,
+}
+
+impl MemberRepository {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Get the members of a budget, including the invitations that have not
+    /// been accepted yet. The user who created the budget is listed first.
+    pub async fn get_members(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+    ) -> Result<Vec<model::Member>, MemberRepositoryError> {
+        self.check_role(user_id, budget_id, Role::Viewer).await?;
+
+        let members = sqlx::query_as!(
+            model::Member,
+            r#"SELECT
+    budget_id as "budget_id!",
+    user_id as "user_id!",
+    role as "role!: Role",
+    invited_by,
+    accepted_at,
+    created_at as "created_at!"
+FROM (
+    SELECT id as budget_id, user_id, 'owner' as role, NULL::text as invited_by, created_at as accepted_at, created_at, 0 as position
+    FROM budget WHERE id = $1
+    UNION ALL
+    SELECT budget_id, user_id, role, invited_by, accepted_at, created_at, 1 as position
+    FROM budget_member WHERE budget_id = $1
+) AS members
+ORDER BY position, created_at, user_id"#,
+            budget_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        Ok(members)
+    }
+
+    /// Invite a user to a budget with a role, which only owners can do.
+    pub async fn invite_member(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        request: &dto::InviteMember,
+    ) -> Result<(), MemberRepositoryError> {
+        self.check_role(user_id, budget_id, Role::Owner).await?;
+        let already_member = || {
+            MemberRepositoryError::Conflict(format!(
+                "User '{}' is already a member of the budget",
+                request.user_id
+            ))
+        };
+
+        let creator = sqlx::query_scalar!("SELECT user_id FROM budget WHERE id = $1", budget_id)
+            .fetch_one(self.db_pool.as_ref())
+            .await?;
+        if creator == request.user_id {
+            return Err(already_member());
+        }
+
+        sqlx::query!(
+            "INSERT INTO budget_member (budget_id, user_id, role, invited_by) VALUES ($1, $2, $3, $4)",
+            budget_id,
+            request.user_id,
+            request.role.as_str(),
+            user_id
+        )
+        .execute(self.db_pool.as_ref())
+        .await
+        .map_err(|error| match MemberRepositoryError::from(error) {
+            MemberRepositoryError::Conflict(_) => already_member(),
+            error => error,
+        })?;
+
+        Ok(())
+    }
+
+    /// Accept an invitation to a budget, giving the user access to it.
+    pub async fn accept_invitation(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+    ) -> Result<(), MemberRepositoryError> {
+        let query = sqlx::query!(
+            r#"UPDATE budget_member SET accepted_at = current_timestamp
+WHERE budget_id = $1 AND user_id = $2 AND accepted_at IS NULL
+    AND budget_id IN (SELECT id FROM budget WHERE deleted_at IS NULL)"#,
+            budget_id,
+            user_id
+        );
+
+        match query.execute(self.db_pool.as_ref()).await?.rows_affected() {
+            0 => Err(MemberRepositoryError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove a member from a budget, or revoke their invitation, which only
+    /// owners can do. Any member can remove themselves, or decline an invitation.
+    pub async fn remove_member(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        member_id: &str,
+    ) -> Result<(), MemberRepositoryError> {
+        if user_id != member_id {
+            self.check_role(user_id, budget_id, Role::Owner).await?;
+        }
+
+        let query = sqlx::query!(
+            "DELETE FROM budget_member WHERE budget_id = $1 AND user_id = $2",
+            budget_id,
+            member_id
+        );
+
+        match query.execute(self.db_pool.as_ref()).await?.rows_affected() {
+            0 => Err(MemberRepositoryError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Get the invitations to budgets that the user has not accepted yet.
+    pub async fn get_invitations(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<model::Invitation>, MemberRepositoryError> {
+        let invitations = sqlx::query_as!(
+            model::Invitation,
+            r#"SELECT m.budget_id, b.title, m.role as "role: Role", m.invited_by, m.created_at
+FROM budget_member AS m
+JOIN budget AS b ON b.id = m.budget_id
+WHERE m.user_id = $1 AND m.accepted_at IS NULL AND b.deleted_at IS NULL
+ORDER BY m.created_at, m.budget_id"#,
+            user_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        Ok(invitations)
+    }
+
+    /// Check that the user has at least the `required` role in the budget.
+    async fn check_role(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        required: Role,
+    ) -> Result<(), MemberRepositoryError> {
+        match role_in_budget(self.db_pool.as_ref(), budget_id, user_id).await? {
+            Some(role) if role >= required => Ok(()),
+            Some(_) => Err(MemberRepositoryError::Forbidden(format!(
+                "User '{user_id}' must be an {required} of the budget"
+            ))),
+            None => Err(MemberRepositoryError::BudgetNotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const BUDGET_ID: &str = "b8d6ff4e-c12f-416b-a611-8ad0c90669fe";
+
+    fn budget_id() -> Uuid {
+        Uuid::parse_str(BUDGET_ID).unwrap()
+    }
+
+    fn invite(user_id: &str, role: Role) -> dto::InviteMember {
+        dto::InviteMember {
+            user_id: user_id.to_string(),
+            role,
+        }
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn invite_and_accept(pool: PgPool) -> sqlx::Result<()> {
+        let repo = MemberRepository::new(Arc::new(pool.clone()));
+
+        repo.invite_member("Alice", &budget_id(), &invite("Bob", Role::Editor))
+            .await
+            .unwrap();
+        let before = role_in_budget(&pool, &budget_id(), "Bob").await?;
+        let invitations = repo.get_invitations("Bob").await.unwrap();
+        repo.accept_invitation("Bob", &budget_id()).await.unwrap();
+        let after = role_in_budget(&pool, &budget_id(), "Bob").await?;
+
+        assert_eq!(before, None);
+        assert_eq!(invitations.len(), 1);
+        assert_eq!(invitations[0].title, "My budget with items");
+        assert_eq!(invitations[0].role, Role::Editor);
+        assert_eq!(invitations[0].invited_by, "Alice");
+        assert_eq!(after, Some(Role::Editor));
+        assert!(repo.get_invitations("Bob").await.unwrap().is_empty());
+        assert_eq!(
+            repo.accept_invitation("Bob", &budget_id()).await,
+            Err(MemberRepositoryError::NotFound)
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn list_members_of_budget(pool: PgPool) -> sqlx::Result<()> {
+        let repo = MemberRepository::new(Arc::new(pool));
+        repo.invite_member("Alice", &budget_id(), &invite("Bob", Role::Viewer))
+            .await
+            .unwrap();
+        repo.accept_invitation("Bob", &budget_id()).await.unwrap();
+        repo.invite_member("Alice", &budget_id(), &invite("Carol", Role::Owner))
+            .await
+            .unwrap();
+
+        let members = repo.get_members("Bob", &budget_id()).await.unwrap();
+        let not_accepted = repo.get_members("Carol", &budget_id()).await;
+
+        let members: Vec<_> = members
+            .iter()
+            .map(|m| {
+                (
+                    m.user_id.as_str(),
+                    m.role,
+                    m.invited_by.as_deref(),
+                    m.accepted_at.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            members,
+            vec![
+                ("Alice", Role::Owner, None, true),
+                ("Bob", Role::Viewer, Some("Alice"), true),
+                ("Carol", Role::Owner, Some("Alice"), false),
+            ]
+        );
+        assert_eq!(not_accepted, Err(MemberRepositoryError::BudgetNotFound));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn only_owners_can_invite(pool: PgPool) -> sqlx::Result<()> {
+        let repo = MemberRepository::new(Arc::new(pool));
+        repo.invite_member("Alice", &budget_id(), &invite("Bob", Role::Editor))
+            .await
+            .unwrap();
+        repo.accept_invitation("Bob", &budget_id()).await.unwrap();
+
+        let by_editor = repo
+            .invite_member("Bob", &budget_id(), &invite("Carol", Role::Viewer))
+            .await;
+        let by_stranger = repo
+            .invite_member("Carol", &budget_id(), &invite("Dave", Role::Viewer))
+            .await;
+        let again = repo
+            .invite_member("Alice", &budget_id(), &invite("Bob", Role::Viewer))
+            .await;
+        let creator = repo
+            .invite_member("Alice", &budget_id(), &invite("Alice", Role::Viewer))
+            .await;
+
+        assert!(matches!(
+            by_editor,
+            Err(MemberRepositoryError::Forbidden(_))
+        ));
+        assert_eq!(by_stranger, Err(MemberRepositoryError::BudgetNotFound));
+        assert!(matches!(again, Err(MemberRepositoryError::Conflict(_))));
+        assert!(matches!(creator, Err(MemberRepositoryError::Conflict(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn revoke_and_leave(pool: PgPool) -> sqlx::Result<()> {
+        let repo = MemberRepository::new(Arc::new(pool.clone()));
+        for user_id in ["Bob", "Carol"] {
+            repo.invite_member("Alice", &budget_id(), &invite(user_id, Role::Editor))
+                .await
+                .unwrap();
+            repo.accept_invitation(user_id, &budget_id()).await.unwrap();
+        }
+
+        let by_editor = repo.remove_member("Bob", &budget_id(), "Carol").await;
+        repo.remove_member("Alice", &budget_id(), "Bob")
+            .await
+            .unwrap();
+        repo.remove_member("Carol", &budget_id(), "Carol")
+            .await
+            .unwrap();
+        let creator = repo.remove_member("Alice", &budget_id(), "Alice").await;
+
+        assert!(matches!(
+            by_editor,
+            Err(MemberRepositoryError::Forbidden(_))
+        ));
+        assert_eq!(role_in_budget(&pool, &budget_id(), "Bob").await?, None);
+        assert_eq!(role_in_budget(&pool, &budget_id(), "Carol").await?, None);
+        assert_eq!(creator, Err(MemberRepositoryError::NotFound));
+
+        Ok(())
+    }
+}
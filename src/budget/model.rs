@@ -1,4 +1,10 @@
-use chrono::NaiveDateTime;
+use super::{
+    member::Role,
+    period::{Period, Recurrence},
+    schedule::{Rule, Schedule},
+};
+use crate::{category::model::CategoryKind, money::Currency};
+use chrono::{NaiveDate, NaiveDateTime};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -6,27 +12,284 @@ pub struct BudgetWithItems {
     pub id: Uuid,
     pub user_id: String,
     pub title: String,
+    pub currency: Currency,
+    pub period_start: Option<NaiveDate>,
+    pub period_end: Option<NaiveDate>,
+    pub recurrence: Option<Recurrence>,
+    pub previous_id: Option<Uuid>,
     pub created_at: NaiveDateTime,
+    pub modified_at: NaiveDateTime,
+    /// Incremented on every change to the budget or its items.
+    pub version: i64,
     pub items: Vec<Item>,
 }
 
+impl BudgetWithItems {
+    pub fn period(&self) -> Option<Period> {
+        Period::from_columns(self.period_start, self.period_end, self.recurrence)
+    }
+}
+
 /// Datamodel for the `Budget` table
-#[derive(Debug, Clone, PartialEq, Eq, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
 pub struct Budget {
     pub id: Uuid,
     pub user_id: String,
     pub title: String,
+    pub currency: Currency,
+    pub period_start: Option<NaiveDate>,
+    pub period_end: Option<NaiveDate>,
+    pub recurrence: Option<Recurrence>,
+    pub previous_id: Option<Uuid>,
     pub created_at: NaiveDateTime,
+    pub modified_at: NaiveDateTime,
+    /// Incremented on every change to the budget or its items.
+    pub version: i64,
+}
+
+impl Budget {
+    pub fn period(&self) -> Option<Period> {
+        Period::from_columns(self.period_start, self.period_end, self.recurrence)
+    }
 }
 
 /// Datamodel for the `Item` table.
+///
+/// The order of the fields must match the records built in
+/// [`BudgetRepository::get_budget`](super::repository::BudgetRepository::get_budget).
 #[derive(Debug, Clone, PartialEq, Eq, sqlx::Type)]
 pub struct Item {
     pub id: Uuid,
     pub budget_id: Uuid,
+    pub category_id: Uuid,
+    /// Name of the category.
     pub category: String,
     pub name: String,
-    pub amount: i32,
+    /// Amount in the minor unit of `currency`.
+    pub amount: i64,
+    pub currency: Currency,
+    pub schedule_start: Option<NaiveDate>,
+    pub schedule_rule: Option<Rule>,
+    pub created_at: NaiveDateTime,
+    pub modified_at: NaiveDateTime,
+    /// Incremented on every change to the item.
+    pub version: i64,
+}
+
+impl Item {
+    pub fn schedule(&self) -> Option<Schedule> {
+        Schedule::from_columns(self.schedule_start, self.schedule_rule.clone())
+    }
+}
+
+/// Totals of a budget, computed by
+/// [`BudgetRepository::get_summary`](super::repository::BudgetRepository::get_summary).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetSummary {
+    pub budget_id: Uuid,
+    pub currency: Currency,
+    pub totals: Vec<CurrencyTotal>,
+    pub categories: Vec<CategoryTotal>,
+    pub largest_items: Vec<Item>,
+    pub recurring: Vec<RecurringTotal>,
+}
+
+/// Income and expenses of the items in a single currency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrencyTotal {
+    pub currency: Currency,
+    pub income: i64,
+    pub expenses: i64,
+    pub balance: i64,
+    pub item_count: i64,
+}
+
+/// Sum of the items in a category, for a single currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryTotal {
+    pub category_id: Uuid,
+    pub category: String,
+    pub kind: CategoryKind,
+    pub currency: Currency,
+    pub total: i64,
+    pub item_count: i64,
+    /// Share of the category in the sum of all categories with the same currency,
+    /// ignoring whether they are income or expenses. Rounded to two decimals.
+    pub percentage: f64,
+}
+
+/// The recurring items of a category normalised to a month and a year, for a single currency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurringTotal {
+    pub category_id: Uuid,
+    pub category: String,
+    pub currency: Currency,
+    pub monthly: i64,
+    pub annual: i64,
+    pub item_count: i64,
+}
+
+/// A single date of a recurring item, computed by
+/// [`BudgetRepository::get_occurrences`](super::repository::BudgetRepository::get_occurrences).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub date: NaiveDate,
+    pub item_id: Uuid,
+    pub name: String,
+    pub category_id: Uuid,
+    pub category: String,
+    pub amount: i64,
+    pub currency: Currency,
+}
+
+/// The rows of a CSV import, computed by
+/// [`ItemRepository::import_items`](super::item_repository::ItemRepository::import_items).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportResult {
+    pub dry_run: bool,
+    pub created: usize,
+    pub rows: Vec<ImportedRow>,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// A valid row of an import, with its category looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedRow {
+    pub line: u64,
+    pub category_id: Uuid,
+    pub category: String,
+    pub name: String,
+    pub amount: i64,
+    pub currency: Currency,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportRowError {
+    pub line: u64,
+    pub message: String,
+}
+
+/// Datamodel for the `budget_member` table. The user who created a budget
+/// is listed as an owner, who was not invited by anyone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub budget_id: Uuid,
+    pub user_id: String,
+    pub role: Role,
+    pub invited_by: Option<String>,
+    /// `None` while the invitation has not been accepted.
+    pub accepted_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+/// An invitation to a budget, which the user has not accepted yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invitation {
+    pub budget_id: Uuid,
+    pub title: String,
+    pub role: Role,
+    pub invited_by: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Datamodel for the `transaction` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub id: Uuid,
+    pub budget_id: Uuid,
+    pub item_id: Option<Uuid>,
+    /// The category of the transaction, or of its item if it has none of its own.
+    pub category_id: Option<Uuid>,
+    /// Name of the category.
+    pub category: Option<String>,
+    pub date: NaiveDate,
+    /// Amount in the minor unit of `currency`.
+    pub amount: i64,
+    pub currency: Currency,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
     pub created_at: NaiveDateTime,
     pub modified_at: NaiveDateTime,
 }
+
+/// Planned and actual amounts of a budget, computed by
+/// [`BudgetRepository::get_progress`](super::repository::BudgetRepository::get_progress).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetProgress {
+    pub budget_id: Uuid,
+    pub items: Vec<ItemProgress>,
+    pub categories: Vec<CategoryProgress>,
+    /// Sum of the transactions without an item or category, per currency.
+    pub uncategorized: Vec<(Currency, i64)>,
+}
+
+/// The planned amount of an item, and the sum of the transactions recorded against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemProgress {
+    pub item_id: Uuid,
+    pub name: String,
+    pub category_id: Uuid,
+    pub currency: Currency,
+    pub planned: i64,
+    pub actual: i64,
+}
+
+/// The planned and actual amounts in a category, for a single currency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryProgress {
+    pub category_id: Uuid,
+    pub category: String,
+    pub kind: CategoryKind,
+    pub currency: Currency,
+    pub planned: i64,
+    pub actual: i64,
+}
+
+/// Amount left of `planned`, once `actual` has been spent or received.
+///
+/// Expenses are negative, so for a planned expense the remaining amount is
+/// how much more can be spent, and it is negative if the plan is overspent.
+/// For planned income it is how much is still expected.
+pub fn remaining(planned: i64, actual: i64) -> i64 {
+    match planned > 0 {
+        true => planned - actual,
+        false => actual - planned,
+    }
+}
+
+/// How much more has been spent than planned, or zero if within the plan.
+/// Income is never overspent.
+pub fn overspent(planned: i64, actual: i64) -> i64 {
+    match planned > 0 {
+        true => 0,
+        false => (-remaining(planned, actual)).max(0),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn remaining_of_planned_expense() {
+        assert_eq!(remaining(-500, -200), 300);
+        assert_eq!(overspent(-500, -200), 0);
+        assert_eq!(remaining(-500, -600), -100);
+        assert_eq!(overspent(-500, -600), 100);
+        // A refund makes more room in the plan.
+        assert_eq!(remaining(-500, 100), 600);
+    }
+
+    #[test]
+    fn remaining_of_planned_income() {
+        assert_eq!(remaining(2000, 1500), 500);
+        assert_eq!(remaining(2000, 2500), -500);
+        assert_eq!(overspent(2000, 2500), 0);
+    }
+
+    #[test]
+    fn spending_without_a_plan_is_overspent() {
+        assert_eq!(remaining(0, -100), -100);
+        assert_eq!(overspent(0, -100), 100);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::text_type::text_type;
+use chrono::{Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// How often a budget repeats, e.g. a new budget every month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Recurrence {
+    Weekly,
+    Biweekly,
+    Monthly,
+    Yearly,
+}
+
+impl Recurrence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recurrence::Weekly => "weekly",
+            Recurrence::Biweekly => "biweekly",
+            Recurrence::Monthly => "monthly",
+            Recurrence::Yearly => "yearly",
+        }
+    }
+
+    /// The same day one recurrence later. Days that do not exist in the
+    /// target month are clamped to its last day, e.g. January 31st becomes February 28th.
+    fn advance(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Weekly => date.checked_add_days(Days::new(7)),
+            Recurrence::Biweekly => date.checked_add_days(Days::new(14)),
+            Recurrence::Monthly => date.checked_add_months(Months::new(1)),
+            Recurrence::Yearly => date.checked_add_months(Months::new(12)),
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "weekly" => Ok(Recurrence::Weekly),
+            "biweekly" => Ok(Recurrence::Biweekly),
+            "monthly" => Ok(Recurrence::Monthly),
+            "yearly" => Ok(Recurrence::Yearly),
+            _ => Err(format!("Unknown recurrence '{value}'")),
+        }
+    }
+}
+
+text_type!(Recurrence);
+
+/// The dates covered by a budget, both inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Period {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Allows creating the next period of the budget. `None` for one-off budgets.
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+}
+
+impl Period {
+    /// Build the period from the columns of a budget, which are either all set or all `NULL`.
+    pub(crate) fn from_columns(
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+        recurrence: Option<Recurrence>,
+    ) -> Option<Self> {
+        Some(Self {
+            start: start?,
+            end: end?,
+            recurrence,
+        })
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        match self.end >= self.start {
+            true => Ok(()),
+            false => Err("The period must not end before it starts".to_string()),
+        }
+    }
+
+    /// The period starting the day after this one ends, lasting one recurrence.
+    /// `None` if the period does not recur.
+    pub fn next(&self) -> Option<Period> {
+        let recurrence = self.recurrence?;
+        let start = self.end.succ_opt()?;
+        let end = recurrence.advance(start)?.pred_opt()?;
+
+        Some(Period {
+            start,
+            end,
+            recurrence: Some(recurrence),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn period(start: &str, end: &str, recurrence: Recurrence) -> Period {
+        Period {
+            start: start.parse().unwrap(),
+            end: end.parse().unwrap(),
+            recurrence: Some(recurrence),
+        }
+    }
+
+    #[test]
+    fn next_calendar_month() {
+        let january = period("2024-01-01", "2024-01-31", Recurrence::Monthly);
+
+        let february = january.next().unwrap();
+        let march = february.next().unwrap();
+
+        assert_eq!(
+            february,
+            period("2024-02-01", "2024-02-29", Recurrence::Monthly)
+        );
+        assert_eq!(
+            march,
+            period("2024-03-01", "2024-03-31", Recurrence::Monthly)
+        );
+    }
+
+    #[test]
+    fn next_month_starting_mid_month() {
+        let payday = period("2024-01-15", "2024-02-14", Recurrence::Monthly);
+
+        assert_eq!(
+            payday.next(),
+            Some(period("2024-02-15", "2024-03-14", Recurrence::Monthly))
+        );
+    }
+
+    #[test]
+    fn next_week_and_year() {
+        let week = period("2024-12-30", "2025-01-05", Recurrence::Weekly);
+        let two_weeks = period("2024-01-01", "2024-01-14", Recurrence::Biweekly);
+        let year = period("2024-01-01", "2024-12-31", Recurrence::Yearly);
+
+        assert_eq!(
+            week.next(),
+            Some(period("2025-01-06", "2025-01-12", Recurrence::Weekly))
+        );
+        assert_eq!(
+            two_weeks.next(),
+            Some(period("2024-01-15", "2024-01-28", Recurrence::Biweekly))
+        );
+        assert_eq!(
+            year.next(),
+            Some(period("2025-01-01", "2025-12-31", Recurrence::Yearly))
+        );
+    }
+
+    #[test]
+    fn one_off_period_has_no_next() {
+        let period = Period {
+            recurrence: None,
+            ..period("2024-01-01", "2024-01-31", Recurrence::Monthly)
+        };
+
+        assert_eq!(period.next(), None);
+    }
+
+    #[test]
+    fn period_must_not_end_before_it_starts() {
+        assert!(period("2024-01-01", "2024-01-01", Recurrence::Weekly)
+            .check()
+            .is_ok());
+        assert!(period("2024-01-02", "2024-01-01", Recurrence::Weekly)
+            .check()
+            .is_err());
+    }
+}
@@ -1,9 +1,132 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use sqlx::PgPool;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
-use super::model;
+use super::{
+    dto,
+    history::{self, Snapshot},
+    member::{role_in_budget, role_in_trashed_budget, Role},
+    model,
+    period::Recurrence,
+    schedule::Rule,
+};
+use crate::{
+    audit::{self, Action, Entity},
+    category::model::CategoryKind,
+    error::{ApiError, DatabaseError, FieldError},
+    money::Currency,
+    pagination::{check_limit, decode_cursor, Page, SortOrder},
+    validation::{MAX_AMOUNT, MIN_AMOUNT},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BudgetRepositoryError {
+    Database,
+    NotFound,
+    /// The user has access to the budget, but their role does not allow the operation.
+    Forbidden(String),
+    Conflict(String),
+    Validation(String),
+    /// A field of the request is not valid for the budget it was applied to.
+    InvalidField(FieldError),
+    /// The budget does not have any of the versions that the change was made for.
+    PreconditionFailed,
+}
+
+impl From<sqlx::Error> for BudgetRepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        match DatabaseError::from(error) {
+            DatabaseError::NotFound => BudgetRepositoryError::NotFound,
+            DatabaseError::Conflict(message) => BudgetRepositoryError::Conflict(message),
+            DatabaseError::Validation(message) => BudgetRepositoryError::Validation(message),
+            DatabaseError::Other => BudgetRepositoryError::Database,
+        }
+    }
+}
+
+impl From<serde_json::Error> for BudgetRepositoryError {
+    fn from(error: serde_json::Error) -> Self {
+        tracing::error!("Unable to read the history of a budget: {error:?}");
+        BudgetRepositoryError::Database
+    }
+}
+
+impl From<BudgetRepositoryError> for ApiError {
+    fn from(error: BudgetRepositoryError) -> Self {
+        match error {
+            BudgetRepositoryError::Database => ApiError::Internal,
+            BudgetRepositoryError::NotFound => ApiError::NotFound("Budget not found".to_string()),
+            BudgetRepositoryError::Forbidden(message) => ApiError::Forbidden(message),
+            BudgetRepositoryError::Conflict(message) => ApiError::Conflict(message),
+            BudgetRepositoryError::Validation(message) => ApiError::BadRequest(message),
+            BudgetRepositoryError::InvalidField(error) => ApiError::Validation(vec![error]),
+            BudgetRepositoryError::PreconditionFailed => ApiError::PreconditionFailed(
+                "The budget has been changed since it was read".to_string(),
+            ),
+        }
+    }
+}
+
+/// Number of items included in the summary of a budget.
+const LARGEST_ITEMS: i64 = 5;
+/// Name of the items holding what was left of a category in the previous period.
+const ROLLOVER_ITEM: &str = "Rollover";
+/// Longest date range that recurring items can be expanded over.
+const MAX_OCCURRENCE_DAYS: i64 = 366;
+
+/// Position in a list of budgets. The sort and order are included, so a
+/// cursor cannot be used with another sort order than it was created for.
+#[derive(Debug, Serialize, Deserialize)]
+struct BudgetCursor {
+    sort: dto::BudgetSort,
+    order: SortOrder,
+    title: String,
+    created_at: NaiveDateTime,
+    id: Uuid,
+}
+
+/// Escape the wildcards of a `LIKE` pattern, so `search` is matched literally.
+fn escape_like(search: &str) -> String {
+    search
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// The current rows of a budget and all of its items, including those in the
+/// trash, along with all the changes to them, newest first.
+async fn load_history(
+    connection: &mut PgConnection,
+    budget_id: &Uuid,
+) -> Result<(Snapshot, Vec<history::Change>), BudgetRepositoryError> {
+    let budget = sqlx::query_scalar!(
+        r#"SELECT to_jsonb(b) as "row!" FROM budget AS b WHERE b.id = $1"#,
+        budget_id
+    )
+    .fetch_optional(&mut *connection)
+    .await?;
+    let items = sqlx::query_scalar!(
+        r#"SELECT to_jsonb(i) as "row!" FROM item AS i WHERE i.budget_id = $1"#,
+        budget_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+    let changes = sqlx::query_as!(
+        history::Change,
+        r#"SELECT entity as "entity: Entity", entity_id, action as "action: Action", before, version, budget_version, created_at
+FROM audit_event
+WHERE budget_id = $1
+ORDER BY id DESC"#,
+        budget_id
+    )
+    .fetch_all(&mut *connection)
+    .await?;
+
+    Ok((Snapshot::new(budget, items), changes))
+}
 
 /// Repository to access budgets.
 /// Used to abstract away the DB interation for the rest of the application.
@@ -17,20 +140,37 @@ impl BudgetRepository {
         Self { db_pool }
     }
 
-    /// Create a new budget with a title for the given user, returning the unique id
-    /// of the newly created budget.
-    pub async fn create_budget(&self, user_id: &str, title: &str) -> Result<Uuid, ()> {
-        match sqlx::query_scalar!(
-            r#"INSERT INTO budget (user_id, title) VALUES ($1, $2) RETURNING id"#,
+    /// Create a new budget with a title, default currency, and optionally a period
+    /// for the given user, returning the unique id of the newly created budget.
+    pub async fn create_budget(
+        &self,
+        user_id: &str,
+        request: &dto::CreateBudget,
+    ) -> Result<Uuid, BudgetRepositoryError> {
+        if let Some(period) = &request.period {
+            period.check().map_err(BudgetRepositoryError::Validation)?;
+        }
+
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO budget (user_id, title, currency, period_start, period_end, recurrence)
+VALUES ($1, $2, $3, $4, $5, $6)
+RETURNING id"#,
             user_id,
-            title
+            request.title,
+            request.currency.code(),
+            request.period.map(|p| p.start),
+            request.period.map(|p| p.end),
+            request
+                .period
+                .and_then(|p| p.recurrence)
+                .map(|r| r.as_str())
         )
-        .fetch_one(self.db_pool.as_ref())
-        .await
-        {
-            Ok(id) => Ok(id),
-            Err(_) => Err(()),
-        }
+        .fetch_one(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(id)
     }
 
     /// Get a budget for a specify user, along with all the items that are in the budget,
@@ -39,106 +179,930 @@ impl BudgetRepository {
         &self,
         user_id: &str,
         budget_id: &Uuid,
-    ) -> Option<model::BudgetWithItems> {
+    ) -> Result<model::BudgetWithItems, BudgetRepositoryError> {
         let query = sqlx::query_as!(
             model::BudgetWithItems,
-            r#"SELECT b.*,
+            r#"SELECT b.id, b.user_id, b.title, b.currency as "currency: Currency",
+    b.period_start, b.period_end, b.recurrence as "recurrence: Recurrence", b.previous_id, b.created_at,
+    b.modified_at, b.version,
 CASE
     WHEN count(i) = 0 THEN '{}'
     ELSE
         array_agg(
-            (i.id, i.budget_id, i.category, i.name, i.amount, i.created_at, i.modified_at)
+            (i.id, i.budget_id, i.category_id, c.name, i.name, i.amount, i.currency, i.schedule_start, i.schedule_rule, i.created_at, i.modified_at, i.version)
         )
     END as "items!: Vec<model::Item>"
 FROM budget AS b
-LEFT JOIN item AS i ON b.id = i.budget_id
-WHERE b.id = $1 AND b.user_id = $2
+LEFT JOIN item AS i ON b.id = i.budget_id AND i.deleted_at IS NULL
+LEFT JOIN category AS c ON c.id = i.category_id
+WHERE b.id = $1 AND b.id IN (SELECT budget_id FROM budget_access WHERE user_id = $2)
 GROUP BY b.id
 "#,
             budget_id,
             user_id
         );
 
-        match query.fetch_one(self.db_pool.as_ref()).await {
-            Ok(budget) => Some(budget),
-            Err(err) => {
-                tracing::error!("Error: {err:?}");
-                None
+        query
+            .fetch_optional(self.db_pool.as_ref())
+            .await?
+            .ok_or(BudgetRepositoryError::NotFound)
+    }
+
+    /// Compute the totals of a budget. Items with a positive amount are income,
+    /// and items with a negative amount are expenses.
+    ///
+    /// Amounts in different currencies are never added together, so all totals
+    /// are grouped by currency.
+    pub async fn get_summary(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+    ) -> Result<model::BudgetSummary, BudgetRepositoryError> {
+        let currency = sqlx::query_scalar!(
+            r#"SELECT currency as "currency: Currency" FROM budget
+WHERE id = $1 AND id IN (SELECT budget_id FROM budget_access WHERE user_id = $2)"#,
+            budget_id,
+            user_id
+        )
+        .fetch_optional(self.db_pool.as_ref())
+        .await?
+        .ok_or(BudgetRepositoryError::NotFound)?;
+
+        let totals = sqlx::query_as!(
+            model::CurrencyTotal,
+            r#"SELECT
+    currency as "currency: Currency",
+    COALESCE(SUM(amount) FILTER (WHERE amount > 0), 0)::bigint as "income!",
+    COALESCE(SUM(amount) FILTER (WHERE amount < 0), 0)::bigint as "expenses!",
+    SUM(amount)::bigint as "balance!",
+    COUNT(*) as "item_count!"
+FROM item
+WHERE budget_id = $1 AND deleted_at IS NULL
+GROUP BY currency
+ORDER BY currency"#,
+            budget_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let categories = sqlx::query_as!(
+            model::CategoryTotal,
+            r#"SELECT
+    c.id as category_id,
+    c.name as category,
+    c.kind as "kind: CategoryKind",
+    i.currency as "currency: Currency",
+    SUM(i.amount)::bigint as "total!",
+    COUNT(*) as "item_count!",
+    COALESCE(
+        ROUND(ABS(SUM(i.amount)) * 100 / NULLIF(SUM(ABS(SUM(i.amount))) OVER (PARTITION BY i.currency), 0), 2),
+        0
+    )::float8 as "percentage!"
+FROM item AS i
+JOIN category AS c ON c.id = i.category_id
+WHERE i.budget_id = $1 AND i.deleted_at IS NULL
+GROUP BY c.id, i.currency
+ORDER BY i.currency, ABS(SUM(i.amount)) DESC, c.name"#,
+            budget_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let largest_items = sqlx::query_as!(
+            model::Item,
+            r#"SELECT i.id, i.budget_id, i.category_id, c.name as category, i.name, i.amount, i.currency as "currency: Currency",
+    i.schedule_start, i.schedule_rule as "schedule_rule: Rule", i.created_at, i.modified_at, i.version
+FROM item AS i
+JOIN category AS c ON c.id = i.category_id
+WHERE i.budget_id = $1 AND i.deleted_at IS NULL
+ORDER BY ABS(i.amount) DESC, i.id
+LIMIT $2"#,
+            budget_id,
+            LARGEST_ITEMS
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let recurring_items = sqlx::query!(
+            r#"SELECT i.category_id, c.name as category, i.currency as "currency: Currency",
+    i.amount, i.schedule_rule as "schedule_rule!: Rule"
+FROM item AS i
+JOIN category AS c ON c.id = i.category_id
+WHERE i.budget_id = $1 AND i.schedule_rule IS NOT NULL AND i.deleted_at IS NULL
+ORDER BY i.currency, c.name, c.id"#,
+            budget_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+        let mut recurring: Vec<model::RecurringTotal> = vec![];
+        for item in recurring_items {
+            let monthly = item.schedule_rule.monthly_amount(item.amount);
+            let annual = item.schedule_rule.annual_amount(item.amount);
+            match recurring.last_mut() {
+                Some(total)
+                    if total.category_id == item.category_id && total.currency == item.currency =>
+                {
+                    total.monthly += monthly;
+                    total.annual += annual;
+                    total.item_count += 1;
+                }
+                _ => recurring.push(model::RecurringTotal {
+                    category_id: item.category_id,
+                    category: item.category,
+                    currency: item.currency,
+                    monthly,
+                    annual,
+                    item_count: 1,
+                }),
             }
         }
+
+        Ok(model::BudgetSummary {
+            budget_id: *budget_id,
+            currency,
+            totals,
+            categories,
+            largest_items,
+            recurring,
+        })
     }
 
-    /// Get all budgets that a given user have created.
-    pub async fn get_all_budgets_for_user(&self, user_id: &str) -> Vec<model::Budget> {
-        let query = sqlx::query_as!(
+    /// Compare the planned items of a budget with the transactions recorded
+    /// against them, per item and per category.
+    ///
+    /// Transactions count towards their own category, or the category of their
+    /// item if they have none. Amounts in different currencies are kept apart.
+    pub async fn get_progress(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+    ) -> Result<model::BudgetProgress, BudgetRepositoryError> {
+        sqlx::query_scalar!(
+            "SELECT id FROM budget WHERE id = $1 AND id IN (SELECT budget_id FROM budget_access WHERE user_id = $2)",
+            budget_id,
+            user_id
+        )
+        .fetch_optional(self.db_pool.as_ref())
+        .await?
+        .ok_or(BudgetRepositoryError::NotFound)?;
+
+        let items = sqlx::query_as!(
+            model::ItemProgress,
+            r#"SELECT
+    i.id as item_id,
+    i.name,
+    i.category_id,
+    i.currency as "currency: Currency",
+    i.amount as planned,
+    COALESCE(SUM(t.amount), 0)::bigint as "actual!"
+FROM item AS i
+LEFT JOIN transaction AS t ON t.item_id = i.id AND t.currency = i.currency
+WHERE i.budget_id = $1 AND i.deleted_at IS NULL
+GROUP BY i.id
+ORDER BY i.name, i.id"#,
+            budget_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let categories = sqlx::query_as!(
+            model::CategoryProgress,
+            r#"WITH planned AS (
+    SELECT category_id, currency, SUM(amount) AS amount
+    FROM item
+    WHERE budget_id = $1 AND deleted_at IS NULL
+    GROUP BY category_id, currency
+), actual AS (
+    SELECT COALESCE(t.category_id, i.category_id) AS category_id, t.currency, SUM(t.amount) AS amount
+    FROM transaction AS t
+    LEFT JOIN item AS i ON i.id = t.item_id
+    WHERE t.budget_id = $1 AND COALESCE(t.category_id, i.category_id) IS NOT NULL
+    GROUP BY 1, t.currency
+)
+SELECT
+    c.id as category_id,
+    c.name as category,
+    c.kind as "kind: CategoryKind",
+    currency as "currency!: Currency",
+    COALESCE(planned.amount, 0)::bigint as "planned!",
+    COALESCE(actual.amount, 0)::bigint as "actual!"
+FROM planned
+FULL JOIN actual USING (category_id, currency)
+JOIN category AS c ON c.id = category_id
+ORDER BY currency, c.name, c.id"#,
+            budget_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let uncategorized = sqlx::query!(
+            r#"SELECT currency as "currency: Currency", SUM(amount)::bigint as "amount!"
+FROM transaction
+WHERE budget_id = $1 AND item_id IS NULL AND category_id IS NULL
+GROUP BY currency
+ORDER BY currency"#,
+            budget_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?
+        .into_iter()
+        .map(|row| (row.currency, row.amount))
+        .collect();
+
+        Ok(model::BudgetProgress {
+            budget_id: *budget_id,
+            items,
+            categories,
+            uncategorized,
+        })
+    }
+
+    /// Expand the recurring items of a budget into the dates they occur on,
+    /// sorted by date. The range defaults to the period of the budget.
+    pub async fn get_occurrences(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        request: &dto::ListOccurrences,
+    ) -> Result<Vec<model::Occurrence>, BudgetRepositoryError> {
+        let budget = self.get_budget(user_id, budget_id).await?;
+        let period = budget.period();
+        let (Some(from), Some(to)) = (
+            request.from.or(period.map(|p| p.start)),
+            request.to.or(period.map(|p| p.end)),
+        ) else {
+            return Err(BudgetRepositoryError::Validation(
+                "A date range is required for budgets without a period".to_string(),
+            ));
+        };
+        if to < from || (to - from).num_days() >= MAX_OCCURRENCE_DAYS {
+            return Err(BudgetRepositoryError::Validation(format!(
+                "The date range must be from 1 to {MAX_OCCURRENCE_DAYS} days"
+            )));
+        }
+
+        let mut occurrences = vec![];
+        for item in &budget.items {
+            let Some(schedule) = item.schedule() else {
+                continue;
+            };
+            occurrences.extend(schedule.occurrences(from, to).into_iter().map(|date| {
+                model::Occurrence {
+                    date,
+                    item_id: item.id,
+                    name: item.name.clone(),
+                    category_id: item.category_id,
+                    category: item.category.clone(),
+                    amount: item.amount,
+                    currency: item.currency,
+                }
+            }));
+        }
+        occurrences.sort_by(|a, b| (a.date, &a.name, a.item_id).cmp(&(b.date, &b.name, b.item_id)));
+
+        Ok(occurrences)
+    }
+
+    /// Create the budget of the period after `budget_id`, with a copy of its
+    /// items and members. Each budget can only be followed by one next period.
+    ///
+    /// With `rollover`, what is left of each expense category in the current
+    /// period is added to the next period as an extra item in the category.
+    pub async fn create_next_period(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        request: &dto::NextPeriod,
+    ) -> Result<Uuid, BudgetRepositoryError> {
+        self.check_role(user_id, budget_id, Role::Editor).await?;
+        let rollover = match request.rollover {
+            true => self.get_progress(user_id, budget_id).await?.categories,
+            false => vec![],
+        };
+
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+
+        let current = sqlx::query_as!(
             model::Budget,
-            "SELECT * FROM budget WHERE user_id = $1",
+            r#"SELECT id, user_id, title, currency as "currency: Currency",
+    period_start, period_end, recurrence as "recurrence: Recurrence", previous_id, created_at,
+    modified_at, version
+FROM budget WHERE id = $1
+FOR UPDATE"#,
+            budget_id
+        )
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or(BudgetRepositoryError::NotFound)?;
+        let next = current
+            .period()
+            .and_then(|period| period.next())
+            .ok_or_else(|| {
+                BudgetRepositoryError::Validation(
+                    "Only budgets with a recurring period have a next period".to_string(),
+                )
+            })?;
+
+        let next_id = sqlx::query_scalar!(
+            r#"INSERT INTO budget (user_id, title, currency, period_start, period_end, recurrence, previous_id)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+RETURNING id"#,
+            current.user_id,
+            request.title.as_ref().unwrap_or(&current.title),
+            current.currency.code(),
+            next.start,
+            next.end,
+            next.recurrence.map(|r| r.as_str()),
+            budget_id
+        )
+        .fetch_one(&mut transaction)
+        .await
+        .map_err(|error| match BudgetRepositoryError::from(error) {
+            BudgetRepositoryError::Conflict(_) => BudgetRepositoryError::Conflict(
+                "The next period of the budget has already been created".to_string(),
+            ),
+            error => error,
+        })?;
+
+        sqlx::query!(
+            r#"INSERT INTO item (budget_id, category_id, name, amount, currency, schedule_start, schedule_rule)
+SELECT $2, category_id, name, amount, currency, schedule_start, schedule_rule FROM item
+WHERE budget_id = $1 AND deleted_at IS NULL"#,
+            budget_id,
+            next_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query!(
+            r#"INSERT INTO budget_member (budget_id, user_id, role, invited_by, accepted_at)
+SELECT $2, user_id, role, invited_by, accepted_at FROM budget_member WHERE budget_id = $1"#,
+            budget_id,
+            next_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        for category in rollover {
+            let left = model::remaining(category.planned, category.actual);
+            if category.planned >= 0 || left <= 0 {
+                continue;
+            }
+            sqlx::query!(
+                "INSERT INTO item (budget_id, category_id, name, amount, currency) VALUES ($1, $2, $3, $4, $5)",
+                next_id,
+                category.category_id,
+                ROLLOVER_ITEM,
+                -left,
+                category.currency.code()
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(next_id)
+    }
+
+    /// Create a copy of a budget and its items for the user, who owns the copy
+    /// but does not need to own the original. Members are not copied, and the
+    /// copy is not the next period of the original.
+    ///
+    /// The items are put in the user's own categories with the same names,
+    /// which are created if the user does not have them yet.
+    pub async fn duplicate_budget(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        request: &dto::DuplicateBudget,
+    ) -> Result<Uuid, BudgetRepositoryError> {
+        self.check_role(user_id, budget_id, Role::Viewer).await?;
+        let scale = i64::from(request.scale.unwrap_or(100));
+
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO budget (user_id, title, currency, period_start, period_end, recurrence)
+SELECT $2, $3, currency, period_start, period_end, recurrence FROM budget
+WHERE id = $1 AND deleted_at IS NULL
+RETURNING id"#,
+            budget_id,
+            user_id,
+            request.title
+        )
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or(BudgetRepositoryError::NotFound)?;
+
+        sqlx::query!(
+            r#"INSERT INTO category (user_id, name, color, icon, kind)
+SELECT DISTINCT ON (lower(trim(c.name))) $2, c.name, c.color, c.icon, c.kind
+FROM item AS i
+JOIN category AS c ON c.id = i.category_id
+WHERE i.budget_id = $1 AND i.deleted_at IS NULL AND c.user_id <> $2
+ORDER BY lower(trim(c.name)), c.name
+ON CONFLICT DO NOTHING"#,
+            budget_id,
             user_id
-        );
+        )
+        .execute(&mut transaction)
+        .await?;
 
-        match query.fetch_all(self.db_pool.as_ref()).await {
-            Ok(budgets) => budgets,
-            Err(err) => {
-                tracing::error!("Error: {err:?}");
-                vec![]
+        // Rounds half away from zero, so scaling does not favour income or expenses.
+        sqlx::query!(
+            r#"INSERT INTO item (budget_id, category_id, name, amount, currency, schedule_start, schedule_rule)
+SELECT $2, own.id, i.name, round(i.amount::numeric * $3::bigint / 100)::bigint, i.currency, i.schedule_start, i.schedule_rule
+FROM item AS i
+JOIN category AS c ON c.id = i.category_id
+JOIN category AS own ON own.user_id = $4 AND lower(trim(own.name)) = lower(trim(c.name))
+WHERE i.budget_id = $1 AND i.deleted_at IS NULL"#,
+            budget_id,
+            id,
+            scale,
+            user_id
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(|error| match &error {
+            sqlx::Error::Database(err) if err.constraint() == Some("item_amount_range") => {
+                BudgetRepositoryError::InvalidField(FieldError {
+                    field: "scale".to_string(),
+                    code: "range".to_string(),
+                    message: format!(
+                        "Scales an amount out of the range from {MIN_AMOUNT} to {MAX_AMOUNT}"
+                    ),
+                })
             }
+            _ => error.into(),
+        })?;
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    /// Get a page of the budgets that a given user have created or been given access to.
+    ///
+    /// Pages are fetched with keyset pagination on the sort column and the id,
+    /// so budgets created while paging do not shift the following pages.
+    pub async fn get_budgets_for_user(
+        &self,
+        user_id: &str,
+        request: &dto::ListBudgets,
+    ) -> Result<Page<model::Budget>, BudgetRepositoryError> {
+        let limit = check_limit(request.limit).map_err(BudgetRepositoryError::Validation)?;
+        let order = request.order();
+        let cursor = request
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                decode_cursor::<BudgetCursor>(cursor)
+                    .filter(|cursor| cursor.sort == request.sort && cursor.order == order)
+                    .ok_or_else(|| BudgetRepositoryError::Validation("Invalid cursor".to_string()))
+            })
+            .transpose()?;
+
+        let mut query = QueryBuilder::<Postgres>::new(
+            "SELECT id, user_id, title, currency, period_start, period_end, recurrence, previous_id, created_at,
+    modified_at, version FROM budget
+WHERE id IN (SELECT budget_id FROM budget_access WHERE user_id = ",
+        );
+        query.push_bind(user_id).push(")");
+        if let Some(search) = &request.search {
+            query
+                .push(" AND title ILIKE ")
+                .push_bind(format!("%{}%", escape_like(search)));
         }
+        let column = match request.sort {
+            dto::BudgetSort::CreatedAt => "created_at",
+            dto::BudgetSort::Title => "title",
+        };
+        if let Some(cursor) = cursor {
+            let comparison = match order {
+                SortOrder::Asc => ">",
+                SortOrder::Desc => "<",
+            };
+            query.push(format!(" AND ({column}, id) {comparison} ("));
+            match request.sort {
+                dto::BudgetSort::CreatedAt => query.push_bind(cursor.created_at),
+                dto::BudgetSort::Title => query.push_bind(cursor.title),
+            };
+            query.push(", ").push_bind(cursor.id).push(")");
+        }
+        let direction = order.as_sql();
+        query
+            .push(format!(
+                " ORDER BY {column} {direction}, id {direction} LIMIT "
+            ))
+            .push_bind(i64::from(limit) + 1);
+
+        let budgets = query
+            .build_query_as::<model::Budget>()
+            .fetch_all(self.db_pool.as_ref())
+            .await?;
+
+        Ok(Page::from_overfetched(budgets, limit, |last| {
+            BudgetCursor {
+                sort: request.sort,
+                order,
+                title: last.title.clone(),
+                created_at: last.created_at,
+                id: last.id,
+            }
+        }))
+    }
+
+    /// Get the ids of all budgets that a user has access to, oldest first.
+    pub async fn get_budget_ids_for_user(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<Uuid>, BudgetRepositoryError> {
+        let ids = sqlx::query_scalar!(
+            "SELECT id FROM budget
+WHERE id IN (SELECT budget_id FROM budget_access WHERE user_id = $1)
+ORDER BY created_at, id",
+            user_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        Ok(ids)
     }
 
-    /// Update the name of a budget.
+    /// Update the name of a budget, and its currency and period if they are given,
+    /// and return the updated budget.
+    /// If `versions` are given, the budget is only updated if it has one of them.
     pub async fn update_budget(
         &self,
         user_id: &str,
         budget_id: &Uuid,
-        title: &str,
-    ) -> Result<(), ()> {
+        request: &dto::UpdateBudget,
+        versions: Option<&[i64]>,
+    ) -> Result<model::BudgetWithItems, BudgetRepositoryError> {
+        if let Some(period) = &request.period {
+            period.check().map_err(BudgetRepositoryError::Validation)?;
+        }
+        self.check_role(user_id, budget_id, Role::Editor).await?;
+
         let query = sqlx::query!(
-            "UPDATE budget SET title = $3 WHERE user_id = $1 AND id = $2",
-            user_id,
+            r#"UPDATE budget
+SET title = $2,
+    currency = COALESCE($3, currency),
+    period_start = COALESCE($4, period_start),
+    period_end = COALESCE($5, period_end),
+    recurrence = CASE WHEN $4::date IS NULL THEN recurrence ELSE $6 END
+WHERE id = $1 AND deleted_at IS NULL AND ($7::bigint[] IS NULL OR version = ANY($7))"#,
+            budget_id,
+            request.title,
+            request.currency.as_ref().map(Currency::code),
+            request.period.map(|p| p.start),
+            request.period.map(|p| p.end),
+            request
+                .period
+                .and_then(|p| p.recurrence)
+                .map(|r| r.as_str()),
+            versions
+        );
+
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        match query.execute(&mut transaction).await?.rows_affected() {
+            0 => Err(self.not_changed(budget_id).await),
+            _ => {
+                transaction.commit().await?;
+                self.get_budget(user_id, budget_id).await
+            }
+        }
+    }
+
+    /// Change the fields of a budget that are given, and return the updated budget.
+    /// Removing the period also removes its recurrence.
+    /// If `versions` are given, the budget is only changed if it has one of them.
+    pub async fn patch_budget(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        request: &dto::PatchBudget,
+        versions: Option<&[i64]>,
+    ) -> Result<model::BudgetWithItems, BudgetRepositoryError> {
+        if let Some(Some(period)) = &request.period {
+            period.check().map_err(BudgetRepositoryError::Validation)?;
+        }
+        self.check_role(user_id, budget_id, Role::Editor).await?;
+
+        let period = request.period.flatten();
+        let query = sqlx::query!(
+            r#"UPDATE budget
+SET title = COALESCE($2, title),
+    currency = COALESCE($3, currency),
+    period_start = CASE WHEN $4 THEN $5 ELSE period_start END,
+    period_end = CASE WHEN $4 THEN $6 ELSE period_end END,
+    recurrence = CASE WHEN $4 THEN $7 ELSE recurrence END
+WHERE id = $1 AND deleted_at IS NULL AND ($8::bigint[] IS NULL OR version = ANY($8))"#,
             budget_id,
-            title
+            request.title,
+            request.currency.as_ref().map(Currency::code),
+            request.period.is_some(),
+            period.map(|p| p.start),
+            period.map(|p| p.end),
+            period.and_then(|p| p.recurrence).map(|r| r.as_str()),
+            versions
         );
 
-        match query.execute(self.db_pool.as_ref()).await {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                tracing::error!("Unable to update budget title. Error: {err:?}");
-                Err(())
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        match query.execute(&mut transaction).await?.rows_affected() {
+            0 => Err(self.not_changed(budget_id).await),
+            _ => {
+                transaction.commit().await?;
+                self.get_budget(user_id, budget_id).await
             }
         }
     }
 
-    #[allow(dead_code)]
-    /// Delete a user's budget.
-    pub async fn delete_budget(&self, user_id: &str, budget_id: &Uuid) -> Result<(), ()> {
+    /// Move a budget to the trash, which only its owners can do. It can be
+    /// restored with [`restore_budget`](Self::restore_budget) until it is purged.
+    /// If `versions` are given, the budget is only deleted if it has one of them.
+    pub async fn delete_budget(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        versions: Option<&[i64]>,
+    ) -> Result<(), BudgetRepositoryError> {
+        self.check_role(user_id, budget_id, Role::Owner).await?;
+
         let query = sqlx::query!(
-            "DELETE FROM budget WHERE user_id = $1 AND id = $2",
-            user_id,
+            "UPDATE budget SET deleted_at = current_timestamp
+WHERE id = $1 AND deleted_at IS NULL AND ($2::bigint[] IS NULL OR version = ANY($2))",
+            budget_id,
+            versions
+        );
+
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        match query.execute(&mut transaction).await?.rows_affected() {
+            0 => Err(self.not_changed(budget_id).await),
+            _ => Ok(transaction.commit().await?),
+        }
+    }
+
+    /// Restore a budget from the trash, which only its owners can do, and return it.
+    pub async fn restore_budget(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+    ) -> Result<model::BudgetWithItems, BudgetRepositoryError> {
+        match role_in_trashed_budget(&self.db_pool, budget_id, user_id).await? {
+            Some(Role::Owner) => {}
+            Some(_) => {
+                return Err(BudgetRepositoryError::Forbidden(format!(
+                    "User '{user_id}' must be an {} of the budget",
+                    Role::Owner
+                )))
+            }
+            None => return Err(BudgetRepositoryError::NotFound),
+        }
+
+        let query = sqlx::query!(
+            "UPDATE budget SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
             budget_id
         );
 
-        match query.execute(self.db_pool.as_ref()).await {
-            Ok(_) => Ok(()),
-            Err(err) => {
-                tracing::error!("Error: {err:?}");
-                Err(())
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        match query.execute(&mut transaction).await?.rows_affected() {
+            0 => Err(BudgetRepositoryError::NotFound),
+            _ => {
+                transaction.commit().await?;
+                self.get_budget(user_id, budget_id).await
+            }
+        }
+    }
+
+    /// Get a budget for a user, along with the items that were in it, as they
+    /// were at `as_of`. Budgets that did not exist yet, or were in the trash, are not found.
+    /// Only the budget and its items are rebuilt, so categories have their current names.
+    pub async fn get_budget_as_of(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        as_of: NaiveDateTime,
+    ) -> Result<model::BudgetWithItems, BudgetRepositoryError> {
+        self.check_role(user_id, budget_id, Role::Viewer).await?;
+
+        let mut connection = self.db_pool.acquire().await?;
+        let (current, changes) = load_history(&mut connection, budget_id).await?;
+        let past = current.at(&changes, as_of);
+        let budget = past.budget()?.ok_or(BudgetRepositoryError::NotFound)?;
+        let items = past
+            .items()?
+            .into_iter()
+            .filter(|item| item.deleted_at.is_none())
+            .collect::<Vec<_>>();
+
+        let category_ids: Vec<_> = items.iter().map(|item| item.category_id).collect();
+        let categories: HashMap<_, _> = sqlx::query!(
+            "SELECT id, name FROM category WHERE id = ANY($1)",
+            &category_ids
+        )
+        .fetch_all(&mut connection)
+        .await?
+        .into_iter()
+        .map(|category| (category.id, category.name))
+        .collect();
+
+        Ok(model::BudgetWithItems {
+            id: budget.id,
+            user_id: budget.user_id,
+            title: budget.title,
+            currency: budget.currency,
+            period_start: budget.period_start,
+            period_end: budget.period_end,
+            recurrence: budget.recurrence,
+            previous_id: budget.previous_id,
+            created_at: budget.created_at,
+            modified_at: budget.modified_at,
+            version: budget.version,
+            items: items
+                .into_iter()
+                .map(|item| model::Item {
+                    id: item.id,
+                    budget_id: item.budget_id,
+                    category_id: item.category_id,
+                    category: categories
+                        .get(&item.category_id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    name: item.name,
+                    amount: item.amount,
+                    currency: item.currency,
+                    schedule_start: item.schedule_start,
+                    schedule_rule: item.schedule_rule,
+                    created_at: item.created_at,
+                    modified_at: item.modified_at,
+                    version: item.version,
+                })
+                .collect(),
+        })
+    }
+
+    /// Revert a budget and its items to how they were at `as_of` in one
+    /// transaction, and return the reverted budget. Items added since are
+    /// moved to the trash, and items deleted since are restored.
+    /// If `versions` are given, the budget is only reverted if it has one of them.
+    pub async fn revert_budget(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        as_of: NaiveDateTime,
+        versions: Option<&[i64]>,
+    ) -> Result<model::BudgetWithItems, BudgetRepositoryError> {
+        self.check_role(user_id, budget_id, Role::Editor).await?;
+
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        let version = sqlx::query_scalar!(
+            "SELECT version FROM budget WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+            budget_id
+        )
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or(BudgetRepositoryError::NotFound)?;
+        if versions.is_some_and(|versions| !versions.contains(&version)) {
+            return Err(BudgetRepositoryError::PreconditionFailed);
+        }
+
+        let (current, changes) = load_history(&mut transaction, budget_id).await?;
+        let past = current.clone().at(&changes, as_of);
+        let budget = past.budget()?.ok_or_else(|| {
+            BudgetRepositoryError::Validation(format!("The budget did not exist at {as_of}"))
+        })?;
+
+        if !current
+            .budget()?
+            .is_some_and(|current| current.same_content(&budget))
+        {
+            sqlx::query!(
+                r#"UPDATE budget
+SET title = $2, currency = $3, period_start = $4, period_end = $5, recurrence = $6
+WHERE id = $1"#,
+                budget_id,
+                budget.title,
+                budget.currency.code(),
+                budget.period_start,
+                budget.period_end,
+                budget.recurrence.map(|r| r.as_str())
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        let past_items = past.items()?;
+        for item in current.items()? {
+            match past_items.iter().find(|past| past.id == item.id) {
+                Some(past) if past.same_content(&item) => {}
+                Some(past) => {
+                    // A category merged into another no longer exists, so the
+                    // item is kept in the category it was moved to.
+                    sqlx::query!(
+                        r#"UPDATE item
+SET category_id = coalesce((SELECT id FROM category WHERE id = $2), category_id), name = $3, amount = $4, currency = $5,
+    schedule_start = $6, schedule_rule = $7, deleted_at = $8
+WHERE id = $1"#,
+                        item.id,
+                        past.category_id,
+                        past.name,
+                        past.amount,
+                        past.currency.code(),
+                        past.schedule_start,
+                        past.schedule_rule.as_ref().map(|r| r.to_string()),
+                        past.deleted_at
+                    )
+                    .execute(&mut transaction)
+                    .await?;
+                }
+                None if item.deleted_at.is_none() => {
+                    sqlx::query!(
+                        "UPDATE item SET deleted_at = current_timestamp WHERE id = $1",
+                        item.id
+                    )
+                    .execute(&mut transaction)
+                    .await?;
+                }
+                None => {}
             }
         }
+        transaction.commit().await?;
+
+        self.get_budget(user_id, budget_id).await
+    }
+
+    /// Why a budget was not changed: either it no longer exists, or it does not
+    /// have the version the change was made for.
+    async fn not_changed(&self, budget_id: &Uuid) -> BudgetRepositoryError {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM budget WHERE id = $1 AND deleted_at IS NULL) as "exists!""#,
+            budget_id
+        )
+        .fetch_one(self.db_pool.as_ref())
+        .await;
+
+        match exists {
+            Ok(true) => BudgetRepositoryError::PreconditionFailed,
+            Ok(false) => BudgetRepositoryError::NotFound,
+            Err(error) => error.into(),
+        }
+    }
+
+    /// Check that the user has at least the `required` role in the budget.
+    /// Budgets the user has no access to are not found.
+    async fn check_role(
+        &self,
+        user_id: &str,
+        budget_id: &Uuid,
+        required: Role,
+    ) -> Result<(), BudgetRepositoryError> {
+        match role_in_budget(self.db_pool.as_ref(), budget_id, user_id).await? {
+            Some(role) if role >= required => Ok(()),
+            Some(_) => Err(BudgetRepositoryError::Forbidden(format!(
+                "User '{user_id}' must be an {required} of the budget"
+            ))),
+            None => Err(BudgetRepositoryError::NotFound),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{
+        budget::{item_repository::ItemRepository, period::Period},
+        category::repository::CategoryRepository,
+        pagination::encode_cursor,
+    };
 
     const USER_ID: &str = "Alice";
 
+    fn new_budget(title: &str) -> dto::CreateBudget {
+        dto::CreateBudget {
+            title: title.to_string(),
+            currency: Currency::DKK,
+            period: None,
+        }
+    }
+
+    fn budget_update(title: &str, currency: Option<Currency>) -> dto::UpdateBudget {
+        dto::UpdateBudget {
+            title: title.to_string(),
+            currency,
+            period: None,
+        }
+    }
+
     #[sqlx::test]
     #[cfg_attr(not(feature = "db_test"), ignore)]
     async fn create_a_new_budget(pool: PgPool) -> sqlx::Result<()> {
         let repo = BudgetRepository::new(Arc::new(pool));
 
-        assert!(repo.create_budget(USER_ID, "My first budget").await.is_ok());
+        assert!(repo
+            .create_budget(USER_ID, &new_budget("My first budget"))
+            .await
+            .is_ok());
 
         Ok(())
     }
@@ -149,7 +1113,10 @@ mod test {
         let repo = BudgetRepository::new(Arc::new(pool));
         let budget_id = Uuid::new_v4();
 
-        assert_eq!(repo.get_budget(USER_ID, &budget_id).await, None);
+        assert_eq!(
+            repo.get_budget(USER_ID, &budget_id).await.unwrap_err(),
+            BudgetRepositoryError::NotFound
+        );
 
         Ok(())
     }
@@ -160,7 +1127,10 @@ mod test {
         let repo = BudgetRepository::new(Arc::new(pool));
         // Setup data
         let budget_title = "some budget_name";
-        let budget_id = repo.create_budget(USER_ID, budget_title).await.unwrap();
+        let budget_id = repo
+            .create_budget(USER_ID, &new_budget(budget_title))
+            .await
+            .unwrap();
 
         // Act
         let budget = repo.get_budget(USER_ID, &budget_id).await.unwrap();
@@ -169,6 +1139,7 @@ mod test {
         assert_eq!(budget.id, budget_id);
         assert_eq!(budget.title, budget_title);
         assert_eq!(budget.user_id, USER_ID);
+        assert_eq!(budget.currency, Currency::DKK);
 
         Ok(())
     }
@@ -203,65 +1174,1262 @@ mod test {
     async fn get_all_budgets_for_alice(pool: PgPool) -> sqlx::Result<()> {
         let repo = BudgetRepository::new(Arc::new(pool));
 
-        let budgets = repo.get_all_budgets_for_user(USER_ID).await;
-        assert_eq!(budgets.len(), 3);
+        let page = repo
+            .get_budgets_for_user(USER_ID, &dto::ListBudgets::default())
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 3);
+        assert_eq!(page.next_cursor, None);
 
         Ok(())
     }
 
-    #[sqlx::test]
+    /// Page through all budgets of Alice, returning their titles.
+    async fn titles_of_all_pages(
+        repo: &BudgetRepository,
+        mut request: dto::ListBudgets,
+    ) -> Vec<String> {
+        let mut titles = vec![];
+        loop {
+            let page = repo.get_budgets_for_user(USER_ID, &request).await.unwrap();
+            titles.extend(page.items.into_iter().map(|budget| budget.title));
+            match page.next_cursor {
+                Some(cursor) => request.cursor = Some(cursor),
+                None => return titles,
+            }
+        }
+    }
+
+    #[sqlx::test(fixtures("budget_multiple"))]
     #[cfg_attr(not(feature = "db_test"), ignore)]
-    async fn delete_budget_for_a_user(pool: PgPool) -> sqlx::Result<()> {
+    async fn page_through_budgets_sorted_by_title(pool: PgPool) -> sqlx::Result<()> {
         let repo = BudgetRepository::new(Arc::new(pool));
-        // Arrange
-        let budget_id = repo
-            .create_budget(USER_ID, "budget to be deleted")
-            .await
-            .unwrap();
+        let request = dto::ListBudgets {
+            limit: Some(2),
+            sort: dto::BudgetSort::Title,
+            ..Default::default()
+        };
 
-        // Act
-        assert!(repo.delete_budget(USER_ID, &budget_id).await.is_ok());
+        let titles = titles_of_all_pages(&repo, request.clone()).await;
+        assert_eq!(titles, vec!["Budget A", "Budget B", "Budget C"]);
 
-        // Assert
-        assert_eq!(repo.get_budget(USER_ID, &budget_id).await, None);
+        let descending = dto::ListBudgets {
+            order: Some(SortOrder::Desc),
+            ..request
+        };
+        let titles = titles_of_all_pages(&repo, descending).await;
+        assert_eq!(titles, vec!["Budget C", "Budget B", "Budget A"]);
 
         Ok(())
     }
 
-    #[sqlx::test(fixtures("budget_with_items"))]
+    #[sqlx::test(fixtures("budget_multiple"))]
     #[cfg_attr(not(feature = "db_test"), ignore)]
-    async fn delete_budget_with_items_for_user(pool: PgPool) -> sqlx::Result<()> {
+    async fn page_through_budgets_created_at_the_same_time(pool: PgPool) -> sqlx::Result<()> {
         let repo = BudgetRepository::new(Arc::new(pool));
-        // Arrange
-        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let request = dto::ListBudgets {
+            limit: Some(1),
+            ..Default::default()
+        };
 
-        // Act
-        assert!(repo.delete_budget(USER_ID, &budget_id).await.is_ok());
+        let mut titles = titles_of_all_pages(&repo, request).await;
 
-        // Assert
-        assert_eq!(repo.get_budget(USER_ID, &budget_id).await, None);
+        // The fixture creates all budgets in the same transaction, so the id decides the order.
+        titles.sort();
+        assert_eq!(titles, vec!["Budget A", "Budget B", "Budget C"]);
 
         Ok(())
     }
 
-    #[sqlx::test(fixtures("budget_with_items"))]
+    #[sqlx::test(fixtures("budget_multiple"))]
     #[cfg_attr(not(feature = "db_test"), ignore)]
-    async fn update_budget_title(pool: PgPool) -> sqlx::Result<()> {
+    async fn search_budgets_by_title(pool: PgPool) -> sqlx::Result<()> {
         let repo = BudgetRepository::new(Arc::new(pool));
-        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
-        let new_title = "New title";
+        let search = |search: &str| dto::ListBudgets {
+            search: Some(search.to_string()),
+            ..Default::default()
+        };
 
-        // Act
-        assert!(repo
-            .update_budget(USER_ID, &budget_id, new_title)
+        let page = repo
+            .get_budgets_for_user(USER_ID, &search("get b"))
             .await
-            .is_ok());
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].title, "Budget B");
 
-        assert_eq!(
+        // Wildcards are matched literally.
+        let page = repo
+            .get_budgets_for_user(USER_ID, &search("%"))
+            .await
+            .unwrap();
+        assert!(page.items.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_get_budgets_with_invalid_cursor_or_limit(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let title_cursor = encode_cursor(&BudgetCursor {
+            sort: dto::BudgetSort::Title,
+            order: SortOrder::Asc,
+            title: "Budget A".to_string(),
+            created_at: NaiveDateTime::default(),
+            id: Uuid::new_v4(),
+        });
+
+        for request in [
+            dto::ListBudgets {
+                cursor: Some("garbage".to_string()),
+                ..Default::default()
+            },
+            // Created for another sort order.
+            dto::ListBudgets {
+                cursor: Some(title_cursor),
+                ..Default::default()
+            },
+            dto::ListBudgets {
+                limit: Some(0),
+                ..Default::default()
+            },
+        ] {
+            let result = repo.get_budgets_for_user(USER_ID, &request).await;
+            assert!(
+                matches!(result, Err(BudgetRepositoryError::Validation(_))),
+                "{request:?}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn delete_budget_for_a_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        // Arrange
+        let budget_id = repo
+            .create_budget(USER_ID, &new_budget("budget to be deleted"))
+            .await
+            .unwrap();
+
+        // Act
+        assert!(repo.delete_budget(USER_ID, &budget_id, None).await.is_ok());
+
+        // Assert
+        assert_eq!(
+            repo.get_budget(USER_ID, &budget_id).await.unwrap_err(),
+            BudgetRepositoryError::NotFound
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn delete_budget_with_items_for_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        // Arrange
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        // Act
+        assert!(repo.delete_budget(USER_ID, &budget_id, None).await.is_ok());
+
+        // Assert
+        assert_eq!(
+            repo.get_budget(USER_ID, &budget_id).await.unwrap_err(),
+            BudgetRepositoryError::NotFound
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn deleted_budget_can_be_restored_by_an_owner(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        repo.delete_budget(USER_ID, &budget_id, None).await.unwrap();
+
+        let by_member = repo.get_budget("Carol", &budget_id).await;
+        let restore_by_editor = repo.restore_budget("Carol", &budget_id).await;
+        let restored = repo.restore_budget(USER_ID, &budget_id).await.unwrap();
+        let restore_again = repo.restore_budget(USER_ID, &budget_id).await;
+
+        assert_eq!(by_member, Err(BudgetRepositoryError::NotFound));
+        assert!(matches!(
+            restore_by_editor,
+            Err(BudgetRepositoryError::Forbidden(_))
+        ));
+        assert_eq!(restored.items.len(), 3);
+        assert_eq!(restore_again, Err(BudgetRepositoryError::NotFound));
+        assert!(repo.get_budget("Carol", &budget_id).await.is_ok());
+
+        Ok(())
+    }
+
+    /// Time of the latest change in the audit log.
+    async fn latest_change(pool: &PgPool) -> sqlx::Result<NaiveDateTime> {
+        sqlx::query_scalar!(r#"SELECT max(created_at) as "created_at!" FROM audit_event"#)
+            .fetch_one(pool)
+            .await
+    }
+
+    fn sorted(mut budget: model::BudgetWithItems) -> model::BudgetWithItems {
+        budget.items.sort_by_key(|item| item.id);
+        budget
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_and_revert_to_an_earlier_state(pool: PgPool) -> sqlx::Result<()> {
+        // Arrange
+        let pool = Arc::new(pool);
+        let repo = BudgetRepository::new(pool.clone());
+        let items = ItemRepository::new(pool.clone());
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let rent = Uuid::parse_str("c4af1e7a-4dfd-4338-ad31-caee4848a69b").unwrap();
+        let restaurants = Uuid::parse_str("d831821b-1b50-41fc-a01e-19a1243c334a").unwrap();
+        let food = Uuid::parse_str("3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03").unwrap();
+        let rename = |title: &str| dto::PatchBudget {
+            title: Some(title.to_string()),
+            ..Default::default()
+        };
+        repo.patch_budget(USER_ID, &budget_id, &rename("Household"), None)
+            .await
+            .unwrap();
+        let as_of = latest_change(&pool).await?;
+        let earlier = repo.get_budget(USER_ID, &budget_id).await.unwrap();
+
+        let higher_rent = dto::PatchItem {
+            amount: Some(75),
+            ..Default::default()
+        };
+        items
+            .patch_item(USER_ID, budget_id, rent, &higher_rent, None)
+            .await
+            .unwrap();
+        items
+            .delete_item(USER_ID, budget_id, restaurants, None)
+            .await
+            .unwrap();
+        items
+            .add_item_to_budget(
+                USER_ID,
+                budget_id,
+                dto::AddItemToBudgetRequest::new(food, "Groceries".to_string(), 40),
+            )
+            .await
+            .unwrap();
+        repo.patch_budget(USER_ID, &budget_id, &rename("Renamed"), None)
+            .await
+            .unwrap();
+
+        // Act
+        let past = repo
+            .get_budget_as_of(USER_ID, &budget_id, as_of)
+            .await
+            .unwrap();
+        let stale = repo
+            .revert_budget(USER_ID, &budget_id, as_of, Some(&[earlier.version]))
+            .await;
+        let reverted = repo
+            .revert_budget(USER_ID, &budget_id, as_of, None)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(sorted(past), sorted(earlier.clone()));
+        assert_eq!(stale, Err(BudgetRepositoryError::PreconditionFailed));
+        assert_eq!(reverted.title, "Household");
+        let reverted_items: Vec<_> = sorted(reverted.clone())
+            .items
+            .into_iter()
+            .map(|item| (item.name, item.amount))
+            .collect();
+        let earlier_items: Vec<_> = sorted(earlier.clone())
+            .items
+            .into_iter()
+            .map(|item| (item.name, item.amount))
+            .collect();
+        assert_eq!(reverted_items, earlier_items);
+        assert!(reverted.version > earlier.version);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn earlier_state_has_the_versions_from_before_updates_without_changes(
+        pool: PgPool,
+    ) -> sqlx::Result<()> {
+        // Arrange
+        let pool = Arc::new(pool);
+        let repo = BudgetRepository::new(pool.clone());
+        let items = ItemRepository::new(pool.clone());
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let rent = Uuid::parse_str("c4af1e7a-4dfd-4338-ad31-caee4848a69b").unwrap();
+        let rename = dto::PatchBudget {
+            title: Some("Household".to_string()),
+            ..Default::default()
+        };
+        repo.patch_budget(USER_ID, &budget_id, &rename, None)
+            .await
+            .unwrap();
+        let as_of = latest_change(&pool).await?;
+        let earlier = repo.get_budget(USER_ID, &budget_id).await.unwrap();
+        let rent_amount = earlier
+            .items
+            .iter()
+            .find(|item| item.id == rent)
+            .unwrap()
+            .amount;
+        let patch_rent = |amount| dto::PatchItem {
+            amount: Some(amount),
+            ..Default::default()
+        };
+
+        // Act
+        repo.patch_budget(USER_ID, &budget_id, &rename, None)
+            .await
+            .unwrap();
+        items
+            .patch_item(USER_ID, budget_id, rent, &patch_rent(rent_amount), None)
+            .await
+            .unwrap();
+        let unchanged = repo.get_budget(USER_ID, &budget_id).await.unwrap();
+        items
+            .patch_item(USER_ID, budget_id, rent, &patch_rent(75), None)
+            .await
+            .unwrap();
+        let past = repo
+            .get_budget_as_of(USER_ID, &budget_id, as_of)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(unchanged.version, earlier.version);
+        assert_eq!(sorted(past), sorted(earlier));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn revert_keeps_items_in_the_category_theirs_was_merged_into(
+        pool: PgPool,
+    ) -> sqlx::Result<()> {
+        // Arrange
+        let pool = Arc::new(pool);
+        let repo = BudgetRepository::new(pool.clone());
+        let categories = CategoryRepository::new(pool.clone());
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let rent = Uuid::parse_str("c4af1e7a-4dfd-4338-ad31-caee4848a69b").unwrap();
+        let home = Uuid::parse_str("3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02").unwrap();
+        let food = Uuid::parse_str("3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03").unwrap();
+        let as_of = repo
+            .get_budget(USER_ID, &budget_id)
+            .await
+            .unwrap()
+            .created_at;
+        categories
+            .merge_categories(USER_ID, &home, &food)
+            .await
+            .unwrap();
+
+        // Act
+        let reverted = repo
+            .revert_budget(USER_ID, &budget_id, as_of, None)
+            .await
+            .unwrap();
+
+        // Assert
+        let rent = reverted.items.iter().find(|item| item.id == rent).unwrap();
+        assert_eq!(rent.category_id, food);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn budget_before_it_was_created_is_not_found(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool.clone()));
+        let budget_id = repo
+            .create_budget(USER_ID, &new_budget("Holiday"))
+            .await
+            .unwrap();
+        let created_at = latest_change(&pool).await?;
+        let before = created_at - chrono::Duration::seconds(1);
+
+        let past = repo.get_budget_as_of(USER_ID, &budget_id, before).await;
+        let revert = repo.revert_budget(USER_ID, &budget_id, before, None).await;
+        let stranger = repo
+            .get_budget_as_of("Mallory", &budget_id, created_at)
+            .await;
+
+        assert_eq!(past, Err(BudgetRepositoryError::NotFound));
+        assert!(matches!(revert, Err(BudgetRepositoryError::Validation(_))));
+        assert_eq!(stranger, Err(BudgetRepositoryError::NotFound));
+        assert!(repo
+            .get_budget_as_of(USER_ID, &budget_id, created_at)
+            .await
+            .is_ok());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn budget_created_without_audit_log_is_not_found_before_it_was_created(
+        pool: PgPool,
+    ) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let created_at = repo
+            .get_budget(USER_ID, &budget_id)
+            .await
+            .unwrap()
+            .created_at;
+
+        let before = repo
+            .get_budget_as_of(
+                USER_ID,
+                &budget_id,
+                created_at - chrono::Duration::seconds(1),
+            )
+            .await;
+        let after = repo.get_budget_as_of(USER_ID, &budget_id, created_at).await;
+
+        assert_eq!(before, Err(BudgetRepositoryError::NotFound));
+        assert_eq!(after.unwrap().items.len(), 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn update_budget_title(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let new_title = "New title";
+
+        // Act
+        assert!(repo
+            .update_budget(USER_ID, &budget_id, &budget_update(new_title, None), None)
+            .await
+            .is_ok());
+
+        assert_eq!(
             repo.get_budget(USER_ID, &budget_id).await.unwrap().title,
             new_title
         );
 
         Ok(())
     }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_ids_of_all_budgets_for_export(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let shared = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let own = repo
+            .create_budget("Bob", &new_budget("Bob's own"))
+            .await
+            .unwrap();
+
+        let for_viewer = repo.get_budget_ids_for_user("Bob").await.unwrap();
+        let not_accepted = repo.get_budget_ids_for_user("Dave").await.unwrap();
+
+        assert_eq!(for_viewer, vec![shared, own]);
+        assert!(not_accepted.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn shared_budget_is_listed_for_members(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        repo.create_budget("Bob", &new_budget("Bob's own"))
+            .await
+            .unwrap();
+
+        let for_viewer = repo
+            .get_budgets_for_user("Bob", &Default::default())
+            .await
+            .unwrap();
+        let not_accepted = repo
+            .get_budgets_for_user("Dave", &Default::default())
+            .await
+            .unwrap();
+
+        let titles: Vec<_> = for_viewer.items.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(titles, vec!["Bob's own", "My budget with items"]);
+        assert!(not_accepted.items.is_empty());
+        assert_eq!(
+            repo.get_budget("Bob", &budget_id)
+                .await
+                .unwrap()
+                .items
+                .len(),
+            3
+        );
+        assert_eq!(
+            repo.get_budget("Dave", &budget_id).await,
+            Err(BudgetRepositoryError::NotFound)
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn roles_of_members_limit_changes(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        let update_by_viewer = repo
+            .update_budget("Bob", &budget_id, &budget_update("By Bob", None), None)
+            .await;
+        let update_by_editor = repo
+            .update_budget("Carol", &budget_id, &budget_update("By Carol", None), None)
+            .await;
+        let delete_by_editor = repo.delete_budget("Carol", &budget_id, None).await;
+
+        assert!(matches!(
+            update_by_viewer,
+            Err(BudgetRepositoryError::Forbidden(_))
+        ));
+        assert_eq!(update_by_editor.unwrap().title, "By Carol");
+        assert!(matches!(
+            delete_by_editor,
+            Err(BudgetRepositoryError::Forbidden(_))
+        ));
+        assert_eq!(
+            repo.get_budget(USER_ID, &budget_id).await.unwrap().title,
+            "By Carol"
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn update_budget_for_another_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        assert_eq!(
+            repo.update_budget("Bob", &budget_id, &budget_update("New title", None), None)
+                .await
+                .unwrap_err(),
+            BudgetRepositoryError::NotFound
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn update_budget_currency(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let euro = "EUR".parse().unwrap();
+
+        repo.update_budget(
+            USER_ID,
+            &budget_id,
+            &budget_update("New title", Some(euro)),
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Items keep the currency they were created with.
+        let budget = repo.get_budget(USER_ID, &budget_id).await.unwrap();
+        assert_eq!(budget.currency, euro);
+        assert!(budget.items.iter().all(|i| i.currency == Currency::DKK));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn delete_budget_that_is_not_there(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+
+        assert_eq!(
+            repo.delete_budget(USER_ID, &Uuid::new_v4(), None)
+                .await
+                .unwrap_err(),
+            BudgetRepositoryError::NotFound
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_summary"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_summary_of_budget(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f").unwrap();
+        let dkk = Currency::DKK;
+        let eur = "EUR".parse().unwrap();
+
+        let summary = repo.get_summary(USER_ID, &budget_id).await.unwrap();
+
+        assert_eq!(summary.currency, dkk);
+        assert_eq!(
+            summary.totals,
+            vec![
+                model::CurrencyTotal {
+                    currency: dkk,
+                    income: 3_500_000,
+                    expenses: -1_600_000,
+                    balance: 1_900_000,
+                    item_count: 5,
+                },
+                model::CurrencyTotal {
+                    currency: eur,
+                    income: 0,
+                    expenses: -50_000,
+                    balance: -50_000,
+                    item_count: 1,
+                },
+            ]
+        );
+
+        let categories = summary
+            .categories
+            .iter()
+            .map(|c| {
+                (
+                    c.category.as_str(),
+                    c.kind,
+                    c.currency,
+                    c.total,
+                    c.item_count,
+                    c.percentage,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            categories,
+            vec![
+                ("Income", CategoryKind::Income, dkk, 3_500_000, 2, 68.63),
+                ("Home", CategoryKind::Expense, dkk, -1_200_000, 1, 23.53),
+                ("Food", CategoryKind::Expense, dkk, -400_000, 2, 7.84),
+                ("Travel", CategoryKind::Expense, eur, -50_000, 1, 100.0),
+            ]
+        );
+
+        let largest_items = summary
+            .largest_items
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            largest_items,
+            vec!["Paycheck", "Rent", "Bonus", "Groceries", "Restaurants"]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_summary_of_empty_budget(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = repo
+            .create_budget(USER_ID, &new_budget("Empty"))
+            .await
+            .unwrap();
+
+        let summary = repo.get_summary(USER_ID, &budget_id).await.unwrap();
+
+        assert!(summary.totals.is_empty());
+        assert!(summary.categories.is_empty());
+        assert!(summary.largest_items.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_recurring_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_summary_with_recurring_items(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        let summary = repo.get_summary(USER_ID, &budget_id).await.unwrap();
+
+        let recurring: Vec<_> = summary
+            .recurring
+            .iter()
+            .map(|r| (r.category.as_str(), r.monthly, r.annual, r.item_count))
+            .collect();
+        // Groceries do not recur, and the insurance counts with a twelfth each month.
+        assert_eq!(
+            recurring,
+            vec![
+                ("Home", -820_000, -9_840_000, 2),
+                ("Income", 3_261_161, 39_133_929, 1),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_recurring_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_occurrences_in_period_of_budget(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        let occurrences = repo
+            .get_occurrences(USER_ID, &budget_id, &dto::ListOccurrences::default())
+            .await
+            .unwrap();
+
+        let occurrences: Vec<_> = occurrences
+            .iter()
+            .map(|o| (o.date.to_string(), o.name.as_str(), o.amount))
+            .collect();
+        assert_eq!(
+            occurrences,
+            vec![
+                ("2024-01-01".to_string(), "Rent", -800_000),
+                ("2024-01-05".to_string(), "Salary", 1_500_000),
+                ("2024-01-19".to_string(), "Salary", 1_500_000),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_recurring_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_occurrences_in_date_range(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let range = |from: &str, to: &str| dto::ListOccurrences {
+            from: Some(from.parse().unwrap()),
+            to: Some(to.parse().unwrap()),
+        };
+
+        let march = repo
+            .get_occurrences(USER_ID, &budget_id, &range("2024-03-01", "2024-03-31"))
+            .await
+            .unwrap();
+        let backwards = repo
+            .get_occurrences(USER_ID, &budget_id, &range("2024-03-31", "2024-03-01"))
+            .await;
+        let too_long = repo
+            .get_occurrences(USER_ID, &budget_id, &range("2024-01-01", "2025-01-01"))
+            .await;
+        let other_user = repo
+            .get_occurrences("Bob", &budget_id, &range("2024-03-01", "2024-03-31"))
+            .await;
+
+        let names: Vec<_> = march.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Rent", "Salary", "Insurance", "Salary", "Salary"]
+        );
+        assert!(matches!(
+            backwards,
+            Err(BudgetRepositoryError::Validation(_))
+        ));
+        assert!(matches!(
+            too_long,
+            Err(BudgetRepositoryError::Validation(_))
+        ));
+        assert_eq!(other_user, Err(BudgetRepositoryError::NotFound));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_summary"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_summary_for_another_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("6f1c5a0e-3c1e-4c8e-9f57-1a2b3c4d5e6f").unwrap();
+
+        assert_eq!(
+            repo.get_summary("Bob", &budget_id).await.unwrap_err(),
+            BudgetRepositoryError::NotFound
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_progress_of_budget(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        let progress = repo.get_progress(USER_ID, &budget_id).await.unwrap();
+
+        let items: Vec<_> = progress
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.planned, i.actual))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                ("Groceries", -300_000, -350_000),
+                ("Paycheck", 2_000_000, 1_500_000),
+                ("Rent", -800_000, -800_000),
+            ]
+        );
+        let categories: Vec<_> = progress
+            .categories
+            .iter()
+            .map(|c| (c.category.as_str(), c.currency.code(), c.planned, c.actual))
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                // Includes the restaurant, which was not planned.
+                ("Food", "DKK", -300_000, -390_000),
+                ("Home", "DKK", -800_000, -800_000),
+                ("Income", "DKK", 2_000_000, 1_500_000),
+                ("Travel", "EUR", 0, -10_000),
+            ]
+        );
+        assert_eq!(progress.uncategorized, vec![(Currency::DKK, -5_000)]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_get_progress_of_budget_of_other_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        let result = repo.get_progress("Bob", &budget_id).await;
+
+        assert_eq!(result, Err(BudgetRepositoryError::NotFound));
+
+        Ok(())
+    }
+
+    fn january(recurrence: Option<Recurrence>) -> Period {
+        Period {
+            start: "2024-01-01".parse().unwrap(),
+            end: "2024-01-31".parse().unwrap(),
+            recurrence,
+        }
+    }
+
+    #[sqlx::test]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn patch_only_changes_the_given_fields(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let period = january(Some(Recurrence::Monthly));
+        let budget_id = repo
+            .create_budget(
+                USER_ID,
+                &dto::CreateBudget {
+                    period: Some(period),
+                    ..new_budget("January")
+                },
+            )
+            .await
+            .unwrap();
+
+        let renamed = repo
+            .patch_budget(
+                USER_ID,
+                &budget_id,
+                &dto::PatchBudget {
+                    title: Some("Renamed".to_string()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let without_period = repo
+            .patch_budget(
+                USER_ID,
+                &budget_id,
+                &dto::PatchBudget {
+                    period: Some(None),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(renamed.title, "Renamed");
+        assert_eq!(renamed.period(), Some(period));
+        assert_eq!(renamed.currency, Currency::DKK);
+        assert_eq!(without_period.title, "Renamed");
+        assert_eq!(without_period.period(), None);
+        assert_eq!(without_period.recurrence, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn patch_budget_requires_an_editor(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let request = dto::PatchBudget {
+            title: Some("Patched".to_string()),
+            ..Default::default()
+        };
+
+        let by_viewer = repo.patch_budget("Bob", &budget_id, &request, None).await;
+        let by_stranger = repo
+            .patch_budget("Mallory", &budget_id, &request, None)
+            .await;
+        let by_editor = repo.patch_budget("Carol", &budget_id, &request, None).await;
+
+        assert!(matches!(
+            by_viewer,
+            Err(BudgetRepositoryError::Forbidden(_))
+        ));
+        assert_eq!(by_stranger, Err(BudgetRepositoryError::NotFound));
+        assert_eq!(by_editor.unwrap().items.len(), 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn version_of_budget_changes_with_its_items(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let version = repo.get_budget(USER_ID, &budget_id).await.unwrap().version;
+        let rename = dto::PatchBudget {
+            title: Some("Renamed".to_string()),
+            ..Default::default()
+        };
+
+        let stale = repo
+            .patch_budget(USER_ID, &budget_id, &rename, Some(&[version - 1]))
+            .await;
+        let renamed = repo
+            .patch_budget(USER_ID, &budget_id, &rename, Some(&[version]))
+            .await
+            .unwrap();
+        sqlx::query!(
+            "UPDATE item SET amount = amount + 1 WHERE budget_id = $1",
+            budget_id
+        )
+        .execute(repo.db_pool.as_ref())
+        .await?;
+        let changed_item = repo.get_budget(USER_ID, &budget_id).await.unwrap();
+        let delete_stale = repo
+            .delete_budget(USER_ID, &budget_id, Some(&[renamed.version]))
+            .await;
+
+        assert_eq!(stale, Err(BudgetRepositoryError::PreconditionFailed));
+        assert!(renamed.version > version);
+        assert!(renamed.modified_at >= renamed.created_at);
+        assert!(changed_item.version > renamed.version);
+        assert_eq!(delete_stale, Err(BudgetRepositoryError::PreconditionFailed));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn create_budget_with_a_period(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let period = january(Some(Recurrence::Monthly));
+        let backwards = Period {
+            start: period.end,
+            end: period.start,
+            recurrence: None,
+        };
+
+        let budget_id = repo
+            .create_budget(
+                USER_ID,
+                &dto::CreateBudget {
+                    period: Some(period),
+                    ..new_budget("January")
+                },
+            )
+            .await
+            .unwrap();
+        let result = repo
+            .create_budget(
+                USER_ID,
+                &dto::CreateBudget {
+                    period: Some(backwards),
+                    ..new_budget("Backwards")
+                },
+            )
+            .await;
+
+        let budget = repo.get_budget(USER_ID, &budget_id).await.unwrap();
+        assert_eq!(budget.period(), Some(period));
+        assert_eq!(budget.previous_id, None);
+        assert!(matches!(result, Err(BudgetRepositoryError::Validation(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn create_next_period_of_budget(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        repo.update_budget(
+            USER_ID,
+            &budget_id,
+            &dto::UpdateBudget {
+                period: Some(january(Some(Recurrence::Monthly))),
+                ..budget_update("January", None)
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        let next_id = repo
+            .create_next_period(USER_ID, &budget_id, &dto::NextPeriod::default())
+            .await
+            .unwrap();
+        let again = repo
+            .create_next_period(USER_ID, &budget_id, &dto::NextPeriod::default())
+            .await;
+
+        let next = repo.get_budget(USER_ID, &next_id).await.unwrap();
+        assert_eq!(next.title, "January");
+        assert_eq!(next.previous_id, Some(budget_id));
+        assert_eq!(
+            next.period(),
+            Some(Period {
+                start: "2024-02-01".parse().unwrap(),
+                end: "2024-02-29".parse().unwrap(),
+                recurrence: Some(Recurrence::Monthly),
+            })
+        );
+        let mut items: Vec<_> = next
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.amount))
+            .collect();
+        items.sort();
+        assert_eq!(
+            items,
+            vec![
+                ("Groceries", -300_000),
+                ("Paycheck", 2_000_000),
+                ("Rent", -800_000)
+            ]
+        );
+        assert!(matches!(again, Err(BudgetRepositoryError::Conflict(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn create_next_period_with_rollover(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let monthly_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let holiday_id = Uuid::parse_str("7a1d3c5e-2b4f-4a6c-8e0d-1f3b5d7f9a0c").unwrap();
+        let rollover = dto::NextPeriod {
+            title: Some("Next".to_string()),
+            rollover: true,
+        };
+        for budget_id in [monthly_id, holiday_id] {
+            repo.update_budget(
+                USER_ID,
+                &budget_id,
+                &dto::UpdateBudget {
+                    period: Some(january(Some(Recurrence::Yearly))),
+                    ..budget_update("January", None)
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        let next_monthly = repo
+            .create_next_period(USER_ID, &monthly_id, &rollover)
+            .await
+            .unwrap();
+        let next_holiday = repo
+            .create_next_period(USER_ID, &holiday_id, &rollover)
+            .await
+            .unwrap();
+
+        // Food is overspent and everything planned for home has been spent.
+        let monthly = repo.get_budget(USER_ID, &next_monthly).await.unwrap();
+        assert_eq!(monthly.title, "Next");
+        assert_eq!(monthly.items.len(), 3);
+        assert!(monthly.items.iter().all(|i| i.name != ROLLOVER_ITEM));
+        // Nothing has been spent on the hotel yet.
+        let holiday = repo.get_budget(USER_ID, &next_holiday).await.unwrap();
+        let mut items: Vec<_> = holiday
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.category.as_str(), i.amount))
+            .collect();
+        items.sort();
+        assert_eq!(
+            items,
+            vec![
+                ("Hotel", "Travel", -500_000),
+                (ROLLOVER_ITEM, "Travel", -500_000)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn duplicate_budget_with_scaled_amounts(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let request = dto::DuplicateBudget {
+            title: "Next year".to_string(),
+            scale: Some(105),
+        };
+
+        let copy_id = repo
+            .duplicate_budget(USER_ID, &budget_id, &request)
+            .await
+            .unwrap();
+        let other_user = repo.duplicate_budget("Bob", &budget_id, &request).await;
+
+        let copy = repo.get_budget(USER_ID, &copy_id).await.unwrap();
+        assert_eq!(copy.title, "Next year");
+        assert_eq!(copy.user_id, USER_ID);
+        assert_eq!(copy.previous_id, None);
+        let mut items: Vec<_> = copy
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.amount))
+            .collect();
+        items.sort();
+        assert_eq!(
+            items,
+            vec![
+                ("Groceries", -315_000),
+                ("Paycheck", 2_100_000),
+                ("Rent", -840_000)
+            ]
+        );
+        let original = repo.get_budget(USER_ID, &budget_id).await.unwrap();
+        assert_eq!(original.title, "Monthly");
+        assert_eq!(original.items.len(), 3);
+        assert_eq!(other_user, Err(BudgetRepositoryError::NotFound));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn duplicate_budget_of_another_user_into_own_categories(
+        pool: PgPool,
+    ) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool.clone()));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+        let request = dto::DuplicateBudget {
+            title: "Copy".to_string(),
+            scale: None,
+        };
+
+        let copy_id = repo
+            .duplicate_budget("Bob", &budget_id, &request)
+            .await
+            .unwrap();
+
+        let copy = repo.get_budget("Bob", &copy_id).await.unwrap();
+        let mut items = Vec::new();
+        for item in &copy.items {
+            let category = sqlx::query!(
+                "SELECT user_id, name, kind FROM category WHERE id = $1",
+                item.category_id
+            )
+            .fetch_one(&pool)
+            .await?;
+            assert_eq!(category.user_id, "Bob");
+            items.push((item.name.clone(), category.name, category.kind));
+        }
+        items.sort();
+        assert_eq!(
+            items,
+            vec![
+                (
+                    "Paycheck".to_string(),
+                    "Income".to_string(),
+                    "income".to_string()
+                ),
+                (
+                    "Rent".to_string(),
+                    "Home".to_string(),
+                    "expense".to_string()
+                ),
+                (
+                    "Restaurants".to_string(),
+                    "Food".to_string(),
+                    "expense".to_string()
+                ),
+            ]
+        );
+        let restaurants = copy.items.iter().find(|i| i.name == "Restaurants").unwrap();
+        assert_eq!(
+            restaurants.category_id,
+            Uuid::parse_str("3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04").unwrap()
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_items"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_create_next_period_of_one_off_budget(pool: PgPool) -> sqlx::Result<()> {
+        let repo = BudgetRepository::new(Arc::new(pool));
+        let budget_id = Uuid::parse_str("b8d6ff4e-c12f-416b-a611-8ad0c90669fe").unwrap();
+
+        let without_period = repo
+            .create_next_period(USER_ID, &budget_id, &dto::NextPeriod::default())
+            .await;
+        repo.update_budget(
+            USER_ID,
+            &budget_id,
+            &dto::UpdateBudget {
+                period: Some(january(None)),
+                ..budget_update("January", None)
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let without_recurrence = repo
+            .create_next_period(USER_ID, &budget_id, &dto::NextPeriod::default())
+            .await;
+        let other_user = repo
+            .create_next_period("Bob", &budget_id, &dto::NextPeriod::default())
+            .await;
+
+        assert!(matches!(
+            without_period,
+            Err(BudgetRepositoryError::Validation(_))
+        ));
+        assert!(matches!(
+            without_recurrence,
+            Err(BudgetRepositoryError::Validation(_))
+        ));
+        assert_eq!(other_user, Err(BudgetRepositoryError::NotFound));
+
+        Ok(())
+    }
 }
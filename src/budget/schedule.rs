@@ -0,0 +1,488 @@
+//! Schedules of recurring items, using a subset of the iCalendar recurrence
+//! rules ([RFC 5545](https://www.rfc-editor.org/rfc/rfc5545#section-3.3.10)).
+//!
+//! A rule has a frequency and optionally an interval, the days of the week for
+//! weekly rules, and either an end date or a number of occurrences, e.g.
+//! `FREQ=WEEKLY;INTERVAL=2;BYDAY=FR` for every other Friday.
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    Decode, Encode, Postgres, Type,
+};
+use std::{fmt, str::FromStr};
+use utoipa::{
+    openapi::{schema::Type as SchemaType, ObjectBuilder, RefOr, Schema},
+    PartialSchema, ToSchema,
+};
+
+/// Average number of days in a year, in hundredths of a day.
+const CENTIDAYS_PER_YEAR: i128 = 36_525;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+impl FromStr for Frequency {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "DAILY" => Ok(Frequency::Daily),
+            "WEEKLY" => Ok(Frequency::Weekly),
+            "MONTHLY" => Ok(Frequency::Monthly),
+            "YEARLY" => Ok(Frequency::Yearly),
+            _ => Err(format!("Unsupported frequency '{value}'")),
+        }
+    }
+}
+
+/// A recurrence rule, written as an iCalendar `RRULE` value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rule {
+    pub frequency: Frequency,
+    /// Number of days, weeks, months, or years between occurrences.
+    pub interval: u32,
+    /// Days of the week of a weekly rule. Defaults to the weekday of the start.
+    pub by_day: Vec<Weekday>,
+    /// Last possible date of an occurrence, inclusive.
+    pub until: Option<NaiveDate>,
+    /// Total number of occurrences, counted from the start.
+    pub count: Option<u32>,
+}
+
+impl Rule {
+    /// Occurrences per year as a fraction, ignoring `until` and `count`.
+    fn per_year(&self) -> (i128, i128) {
+        let interval = i128::from(self.interval);
+        match self.frequency {
+            Frequency::Daily => (CENTIDAYS_PER_YEAR, 100 * interval),
+            Frequency::Weekly => {
+                let days = self.by_day.len().max(1) as i128;
+                (CENTIDAYS_PER_YEAR * days, 700 * interval)
+            }
+            Frequency::Monthly => (12, interval),
+            Frequency::Yearly => (1, interval),
+        }
+    }
+
+    /// What an `amount` paid on every occurrence adds up to in a year.
+    pub fn annual_amount(&self, amount: i64) -> i64 {
+        let (occurrences, years) = self.per_year();
+        scale(amount, occurrences, years)
+    }
+
+    /// What an `amount` paid on every occurrence adds up to in an average month.
+    pub fn monthly_amount(&self, amount: i64) -> i64 {
+        let (occurrences, years) = self.per_year();
+        scale(amount, occurrences, years * 12)
+    }
+}
+
+/// `amount * numerator / denominator`, rounded half away from zero.
+fn scale(amount: i64, numerator: i128, denominator: i128) -> i64 {
+    let scaled = i128::from(amount) * numerator;
+    let rounded = (scaled.abs() * 2 + denominator) / (denominator * 2) * scaled.signum();
+    i64::try_from(rounded).unwrap_or(if rounded < 0 { i64::MIN } else { i64::MAX })
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("Unsupported day '{value}'")),
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_positive(name: &str, value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("{name} must be a positive number")),
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// Parse an `RRULE` value, with or without the `RRULE:` prefix.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.strip_prefix("RRULE:").unwrap_or(value);
+        let mut frequency = None;
+        let mut interval = None;
+        let mut by_day = None;
+        let mut until = None;
+        let mut count = None;
+
+        for part in value.split(';') {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid rule part '{part}'"))?;
+            let duplicate = match name {
+                "FREQ" => frequency.replace(value.parse()?).is_some(),
+                "INTERVAL" => interval.replace(parse_positive(name, value)?).is_some(),
+                "BYDAY" => by_day
+                    .replace(
+                        value
+                            .split(',')
+                            .map(parse_weekday)
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )
+                    .is_some(),
+                "UNTIL" => {
+                    // Only the date of a date-time is used.
+                    let date = value.split('T').next().unwrap_or_default();
+                    let date = NaiveDate::parse_from_str(date, "%Y%m%d")
+                        .map_err(|_| format!("Invalid UNTIL date '{value}'"))?;
+                    until.replace(date).is_some()
+                }
+                "COUNT" => count.replace(parse_positive(name, value)?).is_some(),
+                _ => return Err(format!("Unsupported rule part '{name}'")),
+            };
+            if duplicate {
+                return Err(format!("{name} must only be given once"));
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| "FREQ is required".to_string())?;
+        if by_day.is_some() && frequency != Frequency::Weekly {
+            return Err("BYDAY is only supported for weekly rules".to_string());
+        }
+        if until.is_some() && count.is_some() {
+            return Err("UNTIL and COUNT must not both be given".to_string());
+        }
+        let mut by_day = by_day.unwrap_or_default();
+        by_day.sort_by_key(Weekday::num_days_from_monday);
+        by_day.dedup();
+
+        Ok(Self {
+            frequency,
+            interval: interval.unwrap_or(1),
+            by_day,
+            until,
+            count,
+        })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<_> = self.by_day.iter().map(|d| weekday_code(*d)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Rule> for String {
+    fn from(rule: Rule) -> Self {
+        rule.to_string()
+    }
+}
+
+/// Documented as the `RRULE` string it is serialized as.
+impl PartialSchema for Rule {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(SchemaType::String)
+            .description(Some(
+                "An iCalendar `RRULE` with `FREQ`, and optionally `INTERVAL`, `BYDAY`, and `UNTIL` or `COUNT`",
+            ))
+            .examples(["FREQ=WEEKLY;INTERVAL=2;BYDAY=FR"])
+            .into()
+    }
+}
+
+impl ToSchema for Rule {}
+
+/// Stored as `RRULE` text in the `schedule_rule` column.
+impl Type<Postgres> for Rule {
+    fn type_info() -> PgTypeInfo {
+        <&str as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <&str as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Rule {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(<&str as Decode<Postgres>>::decode(value)?.parse()?)
+    }
+}
+
+impl Encode<'_, Postgres> for Rule {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <String as Encode<Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+/// When a recurring item occurs: the first date, and the rule for the following dates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Schedule {
+    pub start: NaiveDate,
+    pub rule: Rule,
+}
+
+impl Schedule {
+    /// Build the schedule from the columns of an item, which are either both set or both `NULL`.
+    pub(crate) fn from_columns(start: Option<NaiveDate>, rule: Option<Rule>) -> Option<Self> {
+        Some(Self {
+            start: start?,
+            rule: rule?,
+        })
+    }
+
+    /// The dates of all occurrences from `from` to `to`, both inclusive.
+    pub fn occurrences(&self, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let mut dates = vec![];
+        let mut count = 0;
+
+        for period in 0.. {
+            let Some(candidates) = self.candidates(period) else {
+                break;
+            };
+            for date in candidates.into_iter().filter(|date| *date >= self.start) {
+                let ended = self.rule.until.is_some_and(|until| date > until)
+                    || self.rule.count.is_some_and(|total| count >= total);
+                if ended || date > to {
+                    return dates;
+                }
+                count += 1;
+                if date >= from {
+                    dates.push(date);
+                }
+            }
+        }
+
+        dates
+    }
+
+    /// The dates in the `period`th day, week, month, or year of the rule counted
+    /// from the start. Dates that do not exist, e.g. the 31st of a shorter month,
+    /// are skipped. `None` once the dates are out of range.
+    fn candidates(&self, period: u32) -> Option<Vec<NaiveDate>> {
+        let steps = period.checked_mul(self.rule.interval)?;
+        let start = self.start;
+
+        let dates = match self.rule.frequency {
+            Frequency::Daily => vec![start.checked_add_days(Days::new(steps.into()))?],
+            Frequency::Weekly => {
+                let monday = start
+                    .checked_sub_days(Days::new(start.weekday().num_days_from_monday().into()))?
+                    .checked_add_days(Days::new(u64::from(steps) * 7))?;
+                match self.rule.by_day.is_empty() {
+                    true => vec![monday.checked_add_days(Days::new(
+                        start.weekday().num_days_from_monday().into(),
+                    ))?],
+                    false => self
+                        .rule
+                        .by_day
+                        .iter()
+                        .filter_map(|day| {
+                            monday.checked_add_days(Days::new(day.num_days_from_monday().into()))
+                        })
+                        .collect(),
+                }
+            }
+            Frequency::Monthly => {
+                let month = start.with_day(1)?.checked_add_months(Months::new(steps))?;
+                month.with_day(start.day()).into_iter().collect()
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(i32::try_from(steps).ok()?)?;
+                NaiveDate::from_ymd_opt(year, start.month(), 1)?;
+                NaiveDate::from_ymd_opt(year, start.month(), start.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+
+        Some(dates)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    fn schedule(start: &str, rule: &str) -> Schedule {
+        Schedule {
+            start: date(start),
+            rule: rule.parse().unwrap(),
+        }
+    }
+
+    fn dates(values: &[&str]) -> Vec<NaiveDate> {
+        values.iter().map(|value| date(value)).collect()
+    }
+
+    #[test]
+    fn parse_and_format_rule() {
+        let rule: Rule = "RRULE:FREQ=WEEKLY;BYDAY=FR,MO;INTERVAL=2;UNTIL=20241231T235959Z"
+            .parse()
+            .unwrap();
+
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.by_day, vec![Weekday::Mon, Weekday::Fri]);
+        assert_eq!(rule.until, Some(date("2024-12-31")));
+        assert_eq!(rule.count, None);
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;UNTIL=20241231"
+        );
+        assert_eq!(
+            "FREQ=MONTHLY;COUNT=3".parse::<Rule>().unwrap().to_string(),
+            "FREQ=MONTHLY;COUNT=3"
+        );
+    }
+
+    #[test]
+    fn reject_unsupported_rules() {
+        for rule in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;FREQ=WEEKLY",
+            "FREQ=MONTHLY;BYDAY=1MO",
+            "FREQ=MONTHLY;BYDAY=MO",
+            "FREQ=WEEKLY;BYMONTH=1",
+            "FREQ=DAILY;COUNT=2;UNTIL=20240101",
+            "FREQ=DAILY;UNTIL=tomorrow",
+        ] {
+            assert!(rule.parse::<Rule>().is_err(), "{rule}");
+        }
+    }
+
+    #[test]
+    fn rent_on_the_first_of_every_month() {
+        let rent = schedule("2024-01-01", "FREQ=MONTHLY");
+
+        assert_eq!(
+            rent.occurrences(date("2024-02-15"), date("2024-05-01")),
+            dates(&["2024-03-01", "2024-04-01", "2024-05-01"])
+        );
+    }
+
+    #[test]
+    fn salary_every_other_friday() {
+        let salary = schedule("2024-01-05", "FREQ=WEEKLY;INTERVAL=2;BYDAY=FR");
+
+        assert_eq!(
+            salary.occurrences(date("2024-01-01"), date("2024-02-29")),
+            dates(&["2024-01-05", "2024-01-19", "2024-02-02", "2024-02-16"])
+        );
+    }
+
+    #[test]
+    fn weekly_on_several_days_starting_mid_week() {
+        let gym = schedule("2024-01-03", "FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=4");
+
+        assert_eq!(
+            gym.occurrences(date("2024-01-01"), date("2024-12-31")),
+            dates(&["2024-01-03", "2024-01-05", "2024-01-08", "2024-01-10"])
+        );
+    }
+
+    #[test]
+    fn count_includes_occurrences_before_the_range() {
+        let loan = schedule("2024-01-10", "FREQ=DAILY;INTERVAL=10;COUNT=3");
+
+        assert_eq!(
+            loan.occurrences(date("2024-01-15"), date("2024-12-31")),
+            dates(&["2024-01-20", "2024-01-30"])
+        );
+    }
+
+    #[test]
+    fn missing_days_are_skipped() {
+        let end_of_month = schedule("2024-01-31", "FREQ=MONTHLY;UNTIL=20240630");
+        let leap_day = schedule("2024-02-29", "FREQ=YEARLY");
+
+        assert_eq!(
+            end_of_month.occurrences(date("2024-01-01"), date("2024-12-31")),
+            dates(&["2024-01-31", "2024-03-31", "2024-05-31"])
+        );
+        assert_eq!(
+            leap_day.occurrences(date("2024-01-01"), date("2029-01-01")),
+            dates(&["2024-02-29", "2028-02-29"])
+        );
+    }
+
+    #[test]
+    fn normalised_amounts() {
+        let yearly: Rule = "FREQ=YEARLY".parse().unwrap();
+        let quarterly: Rule = "FREQ=MONTHLY;INTERVAL=3".parse().unwrap();
+        let weekly: Rule = "FREQ=WEEKLY;BYDAY=MO,TH".parse().unwrap();
+        let daily: Rule = "FREQ=DAILY".parse().unwrap();
+
+        assert_eq!(yearly.monthly_amount(-120_000), -10_000);
+        assert_eq!(yearly.annual_amount(-120_000), -120_000);
+        assert_eq!(quarterly.monthly_amount(300), 100);
+        assert_eq!(quarterly.annual_amount(300), 1200);
+        assert_eq!(weekly.annual_amount(700), 73_050);
+        assert_eq!(daily.monthly_amount(100), 3044);
+        assert_eq!(daily.monthly_amount(-100), -3044);
+    }
+}
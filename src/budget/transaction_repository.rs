@@ -0,0 +1,500 @@
+use super::{
+    dto,
+    member::{role_in_budget, Role},
+    model,
+};
+use crate::{
+    error::{ApiError, DatabaseError},
+    money::Currency,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TransactionRepositoryError {
+    Database,
+    NotFound,
+    Unauthorized(String),
+    /// The user has access to the budget, but their role does not allow the operation.
+    Forbidden(String),
+    Conflict(String),
+    Validation(String),
+}
+
+impl From<sqlx::Error> for TransactionRepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        match DatabaseError::from(error) {
+            DatabaseError::NotFound => TransactionRepositoryError::NotFound,
+            DatabaseError::Conflict(message) => TransactionRepositoryError::Conflict(message),
+            DatabaseError::Validation(message) => TransactionRepositoryError::Validation(message),
+            DatabaseError::Other => TransactionRepositoryError::Database,
+        }
+    }
+}
+
+impl From<TransactionRepositoryError> for ApiError {
+    fn from(error: TransactionRepositoryError) -> Self {
+        match error {
+            TransactionRepositoryError::Database => ApiError::Internal,
+            TransactionRepositoryError::NotFound => {
+                ApiError::NotFound("Transaction not found".to_string())
+            }
+            TransactionRepositoryError::Unauthorized(user_id) => ApiError::Forbidden(format!(
+                "User '{user_id}' does not have access to the budget"
+            )),
+            TransactionRepositoryError::Forbidden(message) => ApiError::Forbidden(message),
+            TransactionRepositoryError::Conflict(message) => ApiError::Conflict(message),
+            TransactionRepositoryError::Validation(message) => ApiError::BadRequest(message),
+        }
+    }
+}
+
+/// Repository to access the transactions recorded against a budget.
+#[derive(Debug)]
+pub struct TransactionRepository {
+    db_pool: Arc<PgPool>,
+}
+
+impl TransactionRepository {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Get a transaction of a budget the user has access to.
+    pub async fn get_transaction(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        transaction_id: Uuid,
+    ) -> Result<model::Transaction, TransactionRepositoryError> {
+        self.check_access(budget_id, user_id, Role::Viewer).await?;
+
+        let query = sqlx::query_as!(
+            model::Transaction,
+            r#"SELECT
+    t.id, t.budget_id, t.item_id, c.id as "category_id?", c.name as "category?", t.date,
+    t.amount, t.currency as "currency: Currency", t.payee, t.memo, t.created_at, t.modified_at
+FROM transaction AS t
+LEFT JOIN item AS i ON i.id = t.item_id
+LEFT JOIN category AS c ON c.id = COALESCE(t.category_id, i.category_id)
+WHERE t.id = $1 AND t.budget_id = $2"#,
+            transaction_id,
+            budget_id
+        );
+
+        query
+            .fetch_optional(self.db_pool.as_ref())
+            .await?
+            .ok_or(TransactionRepositoryError::NotFound)
+    }
+
+    /// Get the transactions of a budget, newest first, optionally only within a range of dates.
+    pub async fn get_transactions(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        request: &dto::ListTransactions,
+    ) -> Result<Vec<model::Transaction>, TransactionRepositoryError> {
+        self.check_access(budget_id, user_id, Role::Viewer).await?;
+
+        let query = sqlx::query_as!(
+            model::Transaction,
+            r#"SELECT
+    t.id, t.budget_id, t.item_id, c.id as "category_id?", c.name as "category?", t.date,
+    t.amount, t.currency as "currency: Currency", t.payee, t.memo, t.created_at, t.modified_at
+FROM transaction AS t
+LEFT JOIN item AS i ON i.id = t.item_id
+LEFT JOIN category AS c ON c.id = COALESCE(t.category_id, i.category_id)
+WHERE t.budget_id = $1
+    AND ($2::date IS NULL OR t.date >= $2)
+    AND ($3::date IS NULL OR t.date <= $3)
+ORDER BY t.date DESC, t.created_at DESC, t.id"#,
+            budget_id,
+            request.from,
+            request.to
+        );
+
+        Ok(query.fetch_all(self.db_pool.as_ref()).await?)
+    }
+
+    /// Record a transaction. It uses the currency of its item, or of the
+    /// budget if it is not linked to an item, unless another currency is given.
+    pub async fn add_transaction(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        request: &dto::TransactionRequest,
+    ) -> Result<Uuid, TransactionRepositoryError> {
+        self.check_access(budget_id, user_id, Role::Editor).await?;
+        let currency = self.check_links(budget_id, request).await?;
+
+        let query = sqlx::query_scalar!(
+            r#"INSERT INTO transaction (budget_id, item_id, category_id, date, amount, currency, payee, memo)
+VALUES ($1, $2, $3, $4, $5, COALESCE($6, (SELECT currency FROM budget WHERE id = $1)), $7, $8)
+RETURNING id"#,
+            budget_id,
+            request.item_id,
+            request.category_id,
+            request.date,
+            request.amount,
+            currency.as_ref().map(Currency::code),
+            request.payee,
+            request.memo
+        );
+
+        Ok(query.fetch_one(self.db_pool.as_ref()).await?)
+    }
+
+    /// Replace all fields of a transaction. The currency is kept, unless
+    /// another one is given or the transaction is linked to an item.
+    pub async fn update_transaction(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        transaction_id: Uuid,
+        request: &dto::TransactionRequest,
+    ) -> Result<(), TransactionRepositoryError> {
+        self.check_access(budget_id, user_id, Role::Editor).await?;
+        let currency = self.check_links(budget_id, request).await?;
+
+        let query = sqlx::query!(
+            r#"UPDATE transaction
+SET item_id = $3, category_id = $4, date = $5, amount = $6, currency = COALESCE($7, currency), payee = $8, memo = $9
+WHERE id = $1 AND budget_id = $2"#,
+            transaction_id,
+            budget_id,
+            request.item_id,
+            request.category_id,
+            request.date,
+            request.amount,
+            currency.as_ref().map(Currency::code),
+            request.payee,
+            request.memo
+        );
+
+        match query.execute(self.db_pool.as_ref()).await?.rows_affected() {
+            0 => Err(TransactionRepositoryError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Delete a transaction.
+    pub async fn delete_transaction(
+        &self,
+        user_id: &str,
+        budget_id: Uuid,
+        transaction_id: Uuid,
+    ) -> Result<(), TransactionRepositoryError> {
+        self.check_access(budget_id, user_id, Role::Editor).await?;
+
+        let query = sqlx::query!(
+            "DELETE FROM transaction WHERE id = $1 AND budget_id = $2",
+            transaction_id,
+            budget_id
+        );
+
+        match query.execute(self.db_pool.as_ref()).await?.rows_affected() {
+            0 => Err(TransactionRepositoryError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the user has at least the `required` role in the budget.
+    /// Viewers can read the transactions, and editors and owners can also change them.
+    async fn check_access(
+        &self,
+        budget_id: Uuid,
+        user_id: &str,
+        required: Role,
+    ) -> Result<(), TransactionRepositoryError> {
+        match role_in_budget(self.db_pool.as_ref(), &budget_id, user_id).await? {
+            Some(role) if role >= required => Ok(()),
+            Some(_) => {
+                tracing::warn!("User '{user_id}' can only view '{budget_id}'");
+                Err(TransactionRepositoryError::Forbidden(format!(
+                    "User '{user_id}' can only view the budget"
+                )))
+            }
+            None => {
+                tracing::warn!("User '{user_id}' does not have access to '{budget_id}'");
+                Err(TransactionRepositoryError::Unauthorized(
+                    user_id.to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Check that the item is in the budget and the category belongs to the
+    /// owner of the budget. Returns the currency of the transaction, which must be the
+    /// currency of its item, so the item's planned and actual amounts can be compared.
+    async fn check_links(
+        &self,
+        budget_id: Uuid,
+        request: &dto::TransactionRequest,
+    ) -> Result<Option<Currency>, TransactionRepositoryError> {
+        if let Some(category_id) = request.category_id {
+            let exists = sqlx::query_scalar!(
+                r#"SELECT EXISTS(
+    SELECT 1 FROM category AS c JOIN budget AS b ON b.user_id = c.user_id WHERE c.id = $1 AND b.id = $2
+) as "exists!""#,
+                category_id,
+                budget_id,
+            )
+            .fetch_one(self.db_pool.as_ref())
+            .await?;
+            if !exists {
+                return Err(TransactionRepositoryError::Validation(format!(
+                    "Unknown category '{category_id}'"
+                )));
+            }
+        }
+
+        let Some(item_id) = request.item_id else {
+            return Ok(request.currency);
+        };
+        let item_currency = sqlx::query_scalar!(
+            r#"SELECT currency as "currency: Currency" FROM item
+WHERE id = $1 AND budget_id = $2 AND deleted_at IS NULL"#,
+            item_id,
+            budget_id,
+        )
+        .fetch_optional(self.db_pool.as_ref())
+        .await?
+        .ok_or_else(|| {
+            TransactionRepositoryError::Validation(format!("Unknown item '{item_id}'"))
+        })?;
+
+        match request.currency {
+            Some(currency) if currency != item_currency => {
+                Err(TransactionRepositoryError::Validation(format!(
+                    "Transaction must be in the currency of its item, which is {item_currency}"
+                )))
+            }
+            _ => Ok(Some(item_currency)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const USER_ID: &str = "Alice";
+    const BUDGET_ID: &str = "b8d6ff4e-c12f-416b-a611-8ad0c90669fe";
+    const GROCERIES: &str = "d831821b-1b50-41fc-a01e-19a1243c334a";
+    /// Item in another budget of Alice.
+    const HOTEL: &str = "0b7e9d1f-3a5c-4e7a-9c1e-3d5f7b9d1f3a";
+    const FOOD: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03";
+    /// Category owned by Bob.
+    const OTHER_USERS_FOOD: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04";
+    const RESTAURANT: &str = "e1a2b3c4-d5e6-4f70-8192-a3b4c5d6e705";
+
+    fn id(id: &str) -> Uuid {
+        Uuid::parse_str(id).unwrap()
+    }
+
+    fn date(date: &str) -> NaiveDate {
+        date.parse().unwrap()
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_transactions_within_dates(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TransactionRepository::new(Arc::new(pool));
+        let request = dto::ListTransactions {
+            from: Some(date("2024-01-05")),
+            to: Some(date("2024-01-19")),
+        };
+
+        let transactions = repo
+            .get_transactions(USER_ID, id(BUDGET_ID), &request)
+            .await
+            .unwrap();
+
+        let dates: Vec<_> = transactions.iter().map(|t| t.date).collect();
+        assert_eq!(
+            dates,
+            vec![date("2024-01-19"), date("2024-01-12"), date("2024-01-05")]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions", "budget_members"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn viewers_can_read_but_not_record_transactions(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TransactionRepository::new(Arc::new(pool));
+        let request = dto::TransactionRequest {
+            item_id: Some(id(GROCERIES)),
+            ..dto::TransactionRequest::new(date("2024-01-08"), -3500)
+        };
+
+        let read = repo
+            .get_transactions("Bob", id(BUDGET_ID), &Default::default())
+            .await;
+        let by_viewer = repo.add_transaction("Bob", id(BUDGET_ID), &request).await;
+        let by_editor = repo.add_transaction("Carol", id(BUDGET_ID), &request).await;
+        let not_accepted = repo
+            .get_transactions("Dave", id(BUDGET_ID), &Default::default())
+            .await;
+
+        assert_eq!(read.unwrap().len(), 7);
+        assert!(matches!(
+            by_viewer,
+            Err(TransactionRepositoryError::Forbidden(_))
+        ));
+        assert!(by_editor.is_ok());
+        assert_eq!(
+            not_accepted.unwrap_err(),
+            TransactionRepositoryError::Unauthorized("Dave".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn transaction_uses_category_of_its_item(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TransactionRepository::new(Arc::new(pool));
+        let request = dto::TransactionRequest {
+            item_id: Some(id(GROCERIES)),
+            payee: Some("Bakery".to_string()),
+            ..dto::TransactionRequest::new(date("2024-01-08"), -3500)
+        };
+
+        let transaction_id = repo
+            .add_transaction(USER_ID, id(BUDGET_ID), &request)
+            .await
+            .unwrap();
+
+        let transaction = repo
+            .get_transaction(USER_ID, id(BUDGET_ID), transaction_id)
+            .await
+            .unwrap();
+        assert_eq!(transaction.item_id, Some(id(GROCERIES)));
+        assert_eq!(transaction.category_id, Some(id(FOOD)));
+        assert_eq!(transaction.category.as_deref(), Some("Food"));
+        assert_eq!(transaction.currency, Currency::DKK);
+        assert_eq!(transaction.payee.as_deref(), Some("Bakery"));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_add_transaction_in_other_currency_than_item(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TransactionRepository::new(Arc::new(pool));
+        let request = dto::TransactionRequest {
+            item_id: Some(id(GROCERIES)),
+            currency: Some("EUR".parse().unwrap()),
+            ..dto::TransactionRequest::new(date("2024-01-08"), -500)
+        };
+
+        let result = repo.add_transaction(USER_ID, id(BUDGET_ID), &request).await;
+
+        assert_eq!(
+            result,
+            Err(TransactionRepositoryError::Validation(
+                "Transaction must be in the currency of its item, which is DKK".to_string()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_add_transaction_with_item_of_other_budget(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TransactionRepository::new(Arc::new(pool));
+        let request = dto::TransactionRequest {
+            item_id: Some(id(HOTEL)),
+            ..dto::TransactionRequest::new(date("2024-01-08"), -500)
+        };
+
+        let result = repo.add_transaction(USER_ID, id(BUDGET_ID), &request).await;
+
+        assert_eq!(
+            result,
+            Err(TransactionRepositoryError::Validation(format!(
+                "Unknown item '{HOTEL}'"
+            )))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_add_transaction_in_category_of_other_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TransactionRepository::new(Arc::new(pool));
+        let request = dto::TransactionRequest {
+            category_id: Some(id(OTHER_USERS_FOOD)),
+            ..dto::TransactionRequest::new(date("2024-01-08"), -500)
+        };
+
+        let result = repo.add_transaction(USER_ID, id(BUDGET_ID), &request).await;
+
+        assert_eq!(
+            result,
+            Err(TransactionRepositoryError::Validation(format!(
+                "Unknown category '{OTHER_USERS_FOOD}'"
+            )))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_access_transactions_of_other_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TransactionRepository::new(Arc::new(pool));
+        let request = dto::TransactionRequest::new(date("2024-01-08"), -500);
+
+        assert_eq!(
+            repo.add_transaction("Bob", id(BUDGET_ID), &request).await,
+            Err(TransactionRepositoryError::Unauthorized("Bob".to_string()))
+        );
+        assert_eq!(
+            repo.delete_transaction("Bob", id(BUDGET_ID), id(RESTAURANT))
+                .await,
+            Err(TransactionRepositoryError::Unauthorized("Bob".to_string()))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("budget_with_transactions"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn update_and_delete_transaction(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TransactionRepository::new(Arc::new(pool));
+        let request = dto::TransactionRequest {
+            memo: Some("Birthday dinner".to_string()),
+            ..dto::TransactionRequest::new(date("2024-01-13"), -45000)
+        };
+
+        repo.update_transaction(USER_ID, id(BUDGET_ID), id(RESTAURANT), &request)
+            .await
+            .unwrap();
+        let transaction = repo
+            .get_transaction(USER_ID, id(BUDGET_ID), id(RESTAURANT))
+            .await
+            .unwrap();
+        assert_eq!(transaction.amount, -45000);
+        assert_eq!(transaction.date, date("2024-01-13"));
+        assert_eq!(transaction.memo.as_deref(), Some("Birthday dinner"));
+        assert_eq!(transaction.category_id, None);
+
+        repo.delete_transaction(USER_ID, id(BUDGET_ID), id(RESTAURANT))
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_transaction(USER_ID, id(BUDGET_ID), id(RESTAURANT))
+                .await,
+            Err(TransactionRepositoryError::NotFound)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+pub mod dto;
+pub mod model;
+pub(crate) mod repository;
+
+use crate::app_state::AppState;
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+use utoipa::OpenApi;
+
+/// Documentation of the routes in [`create_router`].
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        endpoints::get_all_categories,
+        endpoints::create_category,
+        endpoints::get_category,
+        endpoints::update_category,
+        endpoints::delete_category,
+        endpoints::merge_category,
+    ),
+    tags((name = "category", description = "Categories of the user's items")),
+)]
+pub struct CategoryApi;
+
+pub fn create_router(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(endpoints::get_all_categories))
+        .route("/", post(endpoints::create_category))
+        .route("/:id", delete(endpoints::delete_category))
+        .route("/:id", get(endpoints::get_category))
+        .route("/:id", put(endpoints::update_category))
+        .route("/:id/merge", post(endpoints::merge_category))
+        .with_state(state)
+}
+
+mod endpoints {
+    use super::{dto, repository::CategoryRepository};
+    use crate::{
+        app_state::AppState,
+        auth::scope::{Authorized, CategoryRead, CategoryWrite},
+        error::{ApiError, ProblemDetails},
+        validation::ValidatedJson,
+    };
+    use axum::{
+        debug_handler,
+        extract::{Path, State},
+        Json,
+    };
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    /// Create a new category.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "",
+        tag = "category",
+        request_body = dto::CategoryRequest,
+        responses(
+            (status = 200, description = "Id of the new category", body = String),
+            (status = 400, response = ProblemDetails),
+            (status = 409, response = ProblemDetails),
+        ),
+        security(("bearer" = ["category:write"])),
+    )]
+    pub async fn create_category(
+        State(repository): State<Arc<CategoryRepository>>,
+        claims: Authorized<CategoryWrite>,
+        ValidatedJson(payload): ValidatedJson<dto::CategoryRequest>,
+    ) -> Result<String, ApiError> {
+        tracing::info!("Creating category for user '{}'", claims.user_id());
+
+        let id = repository
+            .create_category(claims.user_id(), &payload)
+            .await?;
+
+        Ok(id.to_string())
+    }
+
+    /// Get all categories of the user.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "",
+        tag = "category",
+        responses((status = 200, body = Vec<dto::Category>)),
+        security(("bearer" = ["category:read"])),
+    )]
+    pub async fn get_all_categories(
+        State(repository): State<Arc<CategoryRepository>>,
+        claims: Authorized<CategoryRead>,
+    ) -> Result<Json<Vec<dto::Category>>, ApiError> {
+        tracing::info!("Get all categories for user {}", claims.user_id());
+
+        Ok(Json(
+            repository
+                .get_all_categories(claims.user_id())
+                .await?
+                .iter()
+                .map(|x| x.into())
+                .collect(),
+        ))
+    }
+
+    /// Get a category from its id.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}",
+        tag = "category",
+        params(("id" = Uuid, Path, description = "Id of the category")),
+        responses(
+            (status = 200, body = dto::Category),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["category:read"])),
+    )]
+    pub async fn get_category(
+        State(repository): State<Arc<CategoryRepository>>,
+        Path(category_id): Path<Uuid>,
+        claims: Authorized<CategoryRead>,
+    ) -> Result<Json<dto::Category>, ApiError> {
+        tracing::info!("Get category {category_id} for user: {}", claims.user_id());
+
+        let category = repository
+            .get_category(claims.user_id(), &category_id)
+            .await?;
+
+        Ok(Json((&category).into()))
+    }
+
+    /// Update or rename a category.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        put,
+        path = "/{id}",
+        tag = "category",
+        params(("id" = Uuid, Path, description = "Id of the category")),
+        request_body = dto::CategoryRequest,
+        responses(
+            (status = 200, description = "The category was updated"),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+            (status = 409, response = ProblemDetails),
+        ),
+        security(("bearer" = ["category:write"])),
+    )]
+    pub async fn update_category(
+        State(repository): State<Arc<CategoryRepository>>,
+        Path(category_id): Path<Uuid>,
+        claims: Authorized<CategoryWrite>,
+        ValidatedJson(payload): ValidatedJson<dto::CategoryRequest>,
+    ) -> Result<(), ApiError> {
+        tracing::info!(
+            "Updating category {category_id} for user '{}'",
+            claims.user_id()
+        );
+
+        Ok(repository
+            .update_category(claims.user_id(), &category_id, &payload)
+            .await?)
+    }
+
+    /// Delete a category that is not used by any items.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        delete,
+        path = "/{id}",
+        tag = "category",
+        params(("id" = Uuid, Path, description = "Id of the category")),
+        responses(
+            (status = 200, description = "The category was deleted"),
+            (status = 404, response = ProblemDetails),
+            (status = 409, response = ProblemDetails),
+        ),
+        security(("bearer" = ["category:write"])),
+    )]
+    pub async fn delete_category(
+        State(repository): State<Arc<CategoryRepository>>,
+        Path(category_id): Path<Uuid>,
+        claims: Authorized<CategoryWrite>,
+    ) -> Result<(), ApiError> {
+        tracing::info!(
+            "Deleting category {category_id} for user '{}'",
+            claims.user_id()
+        );
+
+        Ok(repository
+            .delete_category(claims.user_id(), &category_id)
+            .await?)
+    }
+
+    /// Merge a category into another, moving all its items.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/merge",
+        tag = "category",
+        params(("id" = Uuid, Path, description = "Id of the category")),
+        request_body = dto::MergeCategory,
+        responses(
+            (status = 200, body = dto::MergeResult),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["category:write"])),
+    )]
+    pub async fn merge_category(
+        State(repository): State<Arc<CategoryRepository>>,
+        Path(category_id): Path<Uuid>,
+        claims: Authorized<CategoryWrite>,
+        ValidatedJson(payload): ValidatedJson<dto::MergeCategory>,
+    ) -> Result<Json<dto::MergeResult>, ApiError> {
+        tracing::info!(
+            "Merging category {category_id} into {} for user '{}'",
+            payload.into,
+            claims.user_id()
+        );
+
+        let moved_items = repository
+            .merge_categories(claims.user_id(), &category_id, &payload.into)
+            .await?;
+
+        Ok(Json(dto::MergeResult { moved_items }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::auth::test_utils::TestIdentityProvider;
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const CATEGORY_ID: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01";
+
+    /// Send a request to `uri` with a token that has all scopes except
+    /// `required`, and check that it is rejected.
+    async fn assert_requires_scope(method: Method, uri: &str, required: &str) {
+        let identity_provider = TestIdentityProvider::spawn().await;
+        // Requests rejected because of missing scopes never reach the database.
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let router = create_router(AppState::new(
+            Arc::new(pool),
+            Arc::new(identity_provider.jwks_repository().await),
+        ));
+        let scope = ["category:read", "category:write"]
+            .into_iter()
+            .filter(|s| *s != required)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let token = identity_provider.token("Alice").scope(&scope).sign();
+        let request = Request::builder()
+            .method(method.clone())
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!(
+                r#"{{"name":"Name","into":"{CATEGORY_ID}"}}"#
+            )))
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{method} {uri}");
+    }
+
+    #[tokio::test]
+    async fn get_all_categories_requires_category_read() {
+        assert_requires_scope(Method::GET, "/", "category:read").await;
+    }
+
+    #[tokio::test]
+    async fn get_category_requires_category_read() {
+        assert_requires_scope(Method::GET, &format!("/{CATEGORY_ID}"), "category:read").await;
+    }
+
+    #[tokio::test]
+    async fn create_category_requires_category_write() {
+        assert_requires_scope(Method::POST, "/", "category:write").await;
+    }
+
+    #[tokio::test]
+    async fn update_category_requires_category_write() {
+        assert_requires_scope(Method::PUT, &format!("/{CATEGORY_ID}"), "category:write").await;
+    }
+
+    #[tokio::test]
+    async fn delete_category_requires_category_write() {
+        assert_requires_scope(Method::DELETE, &format!("/{CATEGORY_ID}"), "category:write").await;
+    }
+
+    #[tokio::test]
+    async fn merge_category_requires_category_write() {
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{CATEGORY_ID}/merge"),
+            "category:write",
+        )
+        .await;
+    }
+}
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use super::model::{self, CategoryKind};
+use crate::validation::{self, MAX_NAME_LENGTH};
+
+/// Most characters in the icon of a category, e.g. an emoji or the name of an icon.
+const MAX_ICON_LENGTH: u64 = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Category {
+    pub id: Uuid,
+    pub name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub kind: CategoryKind,
+    pub parent_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub modified_at: DateTime<Utc>,
+}
+
+impl From<&model::Category> for Category {
+    fn from(from: &model::Category) -> Self {
+        Self {
+            id: from.id,
+            name: from.name.to_owned(),
+            color: from.color.to_owned(),
+            icon: from.icon.to_owned(),
+            kind: from.kind,
+            parent_id: from.parent_id,
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+            modified_at: DateTime::from_naive_utc_and_offset(from.modified_at, Utc),
+        }
+    }
+}
+
+/// Used both to create a category, and to replace all fields of an existing one.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+#[cfg_attr(test, derive(derive_new::new))]
+pub struct CategoryRequest {
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub name: String,
+    /// Color as a hex code, e.g. `#1e90ff`.
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    #[validate(custom(function = "hex_color"))]
+    #[schema(pattern = "^#[0-9a-fA-F]{6}$")]
+    pub color: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    #[validate(length(max = MAX_ICON_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 50)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    pub kind: CategoryKind,
+    /// Makes this a subcategory of another category.
+    #[serde(default)]
+    #[cfg_attr(test, new(default))]
+    pub parent_id: Option<Uuid>,
+}
+
+fn hex_color(color: &str) -> Result<(), ValidationError> {
+    let digits = color.strip_prefix('#').unwrap_or_default();
+    match digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        true => Ok(()),
+        false => Err(ValidationError::new("color")
+            .with_message(format!("'{color}' is not a hex code like '#1e90ff'").into())),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct MergeCategory {
+    /// The category that receives the items and subcategories.
+    pub into: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MergeResult {
+    /// Number of items moved to the other category.
+    pub moved_items: u64,
+}
@@ -0,0 +1,18 @@
+INSERT INTO budget (id, user_id, title)
+VALUES ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Alice', 'Monthly');
+
+INSERT INTO category (id, user_id, name, kind, parent_id)
+VALUES
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Alice', 'Income', 'income', NULL),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Alice', 'Food', 'expense', NULL),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a06', 'Alice', 'Groceries', 'expense', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a07', 'Alice', 'Eating out', 'expense', NULL),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04', 'Bob', 'Food', 'expense', NULL)
+;
+
+INSERT INTO item (budget_id, category_id, name, amount)
+VALUES
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Paycheck', 100),
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a07', 'Restaurants', 10),
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a07', 'Take away', 20)
+;
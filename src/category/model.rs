@@ -0,0 +1,52 @@
+use crate::text_type::text_type;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Whether the items in a category are income or expenses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CategoryKind {
+    Income,
+    #[default]
+    Expense,
+}
+
+impl CategoryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CategoryKind::Income => "income",
+            CategoryKind::Expense => "expense",
+        }
+    }
+}
+
+impl FromStr for CategoryKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "income" => Ok(CategoryKind::Income),
+            "expense" => Ok(CategoryKind::Expense),
+            _ => Err(format!("Unknown category kind '{value}'")),
+        }
+    }
+}
+
+text_type!(CategoryKind);
+
+/// Datamodel for the `Category` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub id: Uuid,
+    pub user_id: String,
+    pub name: String,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub kind: CategoryKind,
+    pub parent_id: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub modified_at: NaiveDateTime,
+}
@@ -0,0 +1,561 @@
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{
+    dto,
+    model::{self, CategoryKind},
+};
+use crate::{
+    audit,
+    error::{ApiError, DatabaseError},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CategoryRepositoryError {
+    Database,
+    NotFound,
+    Conflict(String),
+    Validation(String),
+}
+
+impl From<sqlx::Error> for CategoryRepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        match DatabaseError::from(error) {
+            DatabaseError::NotFound => CategoryRepositoryError::NotFound,
+            DatabaseError::Conflict(message) => CategoryRepositoryError::Conflict(message),
+            DatabaseError::Validation(message) => CategoryRepositoryError::Validation(message),
+            DatabaseError::Other => CategoryRepositoryError::Database,
+        }
+    }
+}
+
+impl From<CategoryRepositoryError> for ApiError {
+    fn from(error: CategoryRepositoryError) -> Self {
+        match error {
+            CategoryRepositoryError::Database => ApiError::Internal,
+            CategoryRepositoryError::NotFound => {
+                ApiError::NotFound("Category not found".to_string())
+            }
+            CategoryRepositoryError::Conflict(message) => ApiError::Conflict(message),
+            CategoryRepositoryError::Validation(message) => ApiError::BadRequest(message),
+        }
+    }
+}
+
+/// Replace the message of a unique violation, which is raised if the user
+/// already has a category with the same name.
+fn duplicate_name(name: &str) -> impl Fn(sqlx::Error) -> CategoryRepositoryError + '_ {
+    move |error| match CategoryRepositoryError::from(error) {
+        CategoryRepositoryError::Conflict(_) => {
+            CategoryRepositoryError::Conflict(format!("A category named '{name}' already exists"))
+        }
+        error => error,
+    }
+}
+
+/// Repository to access the categories of a user.
+#[derive(Debug)]
+pub struct CategoryRepository {
+    db_pool: Arc<PgPool>,
+}
+
+impl CategoryRepository {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Create a new category for the user, returning its id.
+    ///
+    /// Names are unique per user, ignoring case and surrounding whitespace.
+    pub async fn create_category(
+        &self,
+        user_id: &str,
+        request: &dto::CategoryRequest,
+    ) -> Result<Uuid, CategoryRepositoryError> {
+        if let Some(parent_id) = request.parent_id {
+            self.check_parent(user_id, None, parent_id).await?;
+        }
+
+        let query = sqlx::query_scalar!(
+            r#"INSERT INTO category (user_id, name, color, icon, kind, parent_id)
+VALUES ($1, trim($2), $3, $4, $5, $6)
+RETURNING id"#,
+            user_id,
+            request.name,
+            request.color,
+            request.icon,
+            request.kind.as_str(),
+            request.parent_id
+        );
+
+        query
+            .fetch_one(self.db_pool.as_ref())
+            .await
+            .map_err(duplicate_name(&request.name))
+    }
+
+    /// Get a single category of the user.
+    pub async fn get_category(
+        &self,
+        user_id: &str,
+        category_id: &Uuid,
+    ) -> Result<model::Category, CategoryRepositoryError> {
+        let query = sqlx::query_as!(
+            model::Category,
+            r#"SELECT id, user_id, name, color, icon, kind as "kind: CategoryKind", parent_id, created_at, modified_at
+FROM category WHERE id = $1 AND user_id = $2"#,
+            category_id,
+            user_id
+        );
+
+        query
+            .fetch_optional(self.db_pool.as_ref())
+            .await?
+            .ok_or(CategoryRepositoryError::NotFound)
+    }
+
+    /// Get all categories of the user, sorted by name.
+    pub async fn get_all_categories(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<model::Category>, CategoryRepositoryError> {
+        let query = sqlx::query_as!(
+            model::Category,
+            r#"SELECT id, user_id, name, color, icon, kind as "kind: CategoryKind", parent_id, created_at, modified_at
+FROM category WHERE user_id = $1
+ORDER BY lower(name)"#,
+            user_id
+        );
+
+        Ok(query.fetch_all(self.db_pool.as_ref()).await?)
+    }
+
+    /// Replace all fields of a category, e.g. to rename it. The items in the
+    /// category are not changed, as they reference it by id.
+    pub async fn update_category(
+        &self,
+        user_id: &str,
+        category_id: &Uuid,
+        request: &dto::CategoryRequest,
+    ) -> Result<(), CategoryRepositoryError> {
+        if let Some(parent_id) = request.parent_id {
+            self.check_parent(user_id, Some(*category_id), parent_id)
+                .await?;
+        }
+
+        let query = sqlx::query!(
+            r#"UPDATE category
+SET name = trim($3), color = $4, icon = $5, kind = $6, parent_id = $7
+WHERE id = $1 AND user_id = $2"#,
+            category_id,
+            user_id,
+            request.name,
+            request.color,
+            request.icon,
+            request.kind.as_str(),
+            request.parent_id
+        );
+
+        let result = query
+            .execute(self.db_pool.as_ref())
+            .await
+            .map_err(duplicate_name(&request.name))?;
+        match result.rows_affected() {
+            0 => Err(CategoryRepositoryError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Delete a category, which is not used by any items, transactions, or templates.
+    /// Its subcategories become top level categories.
+    pub async fn delete_category(
+        &self,
+        user_id: &str,
+        category_id: &Uuid,
+    ) -> Result<(), CategoryRepositoryError> {
+        self.get_category(user_id, category_id).await?;
+        let usage = sqlx::query!(
+            r#"SELECT
+    (SELECT count(*) FROM item WHERE category_id = $1) as "items!",
+    (SELECT count(*) FROM transaction WHERE category_id = $1) as "transactions!",
+    (SELECT count(DISTINCT template_id) FROM template_item WHERE category_id = $1) as "templates!""#,
+            category_id
+        )
+        .fetch_one(self.db_pool.as_ref())
+        .await?;
+        if usage.items > 0 || usage.transactions > 0 || usage.templates > 0 {
+            return Err(CategoryRepositoryError::Conflict(format!(
+                "Category is used by {} items, {} transactions, and {} templates, merge it into another category instead",
+                usage.items, usage.transactions, usage.templates
+            )));
+        }
+
+        let query = sqlx::query!(
+            "DELETE FROM category WHERE id = $1 AND user_id = $2",
+            category_id,
+            user_id
+        );
+
+        match query.execute(self.db_pool.as_ref()).await?.rows_affected() {
+            0 => Err(CategoryRepositoryError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Move all items, transactions, template items, and subcategories of `source` to `target`,
+    /// and delete `source`.
+    /// Either everything is moved, or nothing is. Returns the number of items moved.
+    pub async fn merge_categories(
+        &self,
+        user_id: &str,
+        source: &Uuid,
+        target: &Uuid,
+    ) -> Result<u64, CategoryRepositoryError> {
+        if source == target {
+            return Err(CategoryRepositoryError::Validation(
+                "A category cannot be merged into itself".to_string(),
+            ));
+        }
+
+        // Moving the items is recorded in the history of their budgets.
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+
+        // Lock both categories, so they cannot be changed or deleted while merging.
+        let found = sqlx::query_scalar!(
+            r#"SELECT count(*) as "count!" FROM (
+    SELECT id FROM category WHERE id IN ($1, $2) AND user_id = $3 FOR UPDATE
+) AS found"#,
+            source,
+            target,
+            user_id
+        )
+        .fetch_one(&mut transaction)
+        .await?;
+        if found != 2 {
+            return Err(CategoryRepositoryError::NotFound);
+        }
+
+        let moved_items = sqlx::query!(
+            "UPDATE item SET category_id = $2 WHERE category_id = $1",
+            source,
+            target
+        )
+        .execute(&mut transaction)
+        .await?
+        .rows_affected();
+        sqlx::query!(
+            "UPDATE transaction SET category_id = $2 WHERE category_id = $1",
+            source,
+            target
+        )
+        .execute(&mut transaction)
+        .await?;
+        sqlx::query!(
+            "UPDATE template_item SET category_id = $2 WHERE category_id = $1",
+            source,
+            target
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        // If `target` is a subcategory of `source`, it takes the place of `source`.
+        sqlx::query!(
+            r#"UPDATE category
+SET parent_id = (SELECT parent_id FROM category WHERE id = $1)
+WHERE id = $2 AND parent_id = $1"#,
+            source,
+            target
+        )
+        .execute(&mut transaction)
+        .await?;
+        sqlx::query!(
+            "UPDATE category SET parent_id = $2 WHERE parent_id = $1",
+            source,
+            target
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query!("DELETE FROM category WHERE id = $1", source)
+            .execute(&mut transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(moved_items)
+    }
+
+    /// Check that `parent_id` is a category of the user, and that using it as
+    /// the parent of `category_id` does not create a cycle.
+    async fn check_parent(
+        &self,
+        user_id: &str,
+        category_id: Option<Uuid>,
+        parent_id: Uuid,
+    ) -> Result<(), CategoryRepositoryError> {
+        let ancestors = sqlx::query_scalar!(
+            r#"WITH RECURSIVE ancestors AS (
+    SELECT id, parent_id FROM category WHERE id = $1 AND user_id = $2
+    UNION
+    SELECT category.id, category.parent_id
+    FROM category
+    JOIN ancestors ON category.id = ancestors.parent_id
+)
+SELECT id as "id!" FROM ancestors"#,
+            parent_id,
+            user_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        if ancestors.is_empty() {
+            return Err(CategoryRepositoryError::Validation(format!(
+                "Unknown parent category '{parent_id}'"
+            )));
+        }
+        if category_id.is_some_and(|id| ancestors.contains(&id)) {
+            return Err(CategoryRepositoryError::Validation(
+                "A category cannot be a subcategory of itself".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INCOME: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01";
+    const FOOD: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03";
+    /// Subcategory of `FOOD`.
+    const GROCERIES: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a06";
+    const EATING_OUT: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a07";
+    /// Category owned by Bob.
+    const OTHER_USERS_FOOD: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04";
+
+    fn id(id: &str) -> Uuid {
+        Uuid::parse_str(id).unwrap()
+    }
+
+    fn named(name: &str) -> dto::CategoryRequest {
+        dto::CategoryRequest::new(name.to_string())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn get_all_categories_of_user_sorted_by_name(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        let categories = repo.get_all_categories("Alice").await.unwrap();
+
+        let names: Vec<_> = categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Eating out", "Food", "Groceries", "Income"]);
+        assert_eq!(categories[3].kind, CategoryKind::Income);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn create_category_trims_name(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        let category_id = repo
+            .create_category("Alice", &named("  Travel "))
+            .await
+            .unwrap();
+
+        let category = repo.get_category("Alice", &category_id).await.unwrap();
+        assert_eq!(category.name, "Travel");
+        assert_eq!(category.kind, CategoryKind::Expense);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_create_category_with_existing_name(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        let result = repo.create_category("Alice", &named(" food")).await;
+
+        assert_eq!(
+            result,
+            Err(CategoryRepositoryError::Conflict(
+                "A category named ' food' already exists".to_string()
+            ))
+        );
+        // Names are only unique per user.
+        assert!(repo.create_category("Bob", &named("Income")).await.is_ok());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_create_category_with_invalid_color(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+        let mut request = named("Travel");
+        request.color = Some("blue".to_string());
+
+        let result = repo.create_category("Alice", &request).await;
+
+        assert!(matches!(
+            result,
+            Err(CategoryRepositoryError::Validation(_))
+        ));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn rename_category(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        repo.update_category("Alice", &id(FOOD), &named("Food & drinks"))
+            .await
+            .unwrap();
+
+        let category = repo.get_category("Alice", &id(FOOD)).await.unwrap();
+        assert_eq!(category.name, "Food & drinks");
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_update_category_of_other_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        let result = repo
+            .update_category("Alice", &id(OTHER_USERS_FOOD), &named("Mine"))
+            .await;
+
+        assert_eq!(result, Err(CategoryRepositoryError::NotFound));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_make_category_a_subcategory_of_itself(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+        let mut request = named("Food");
+        request.parent_id = Some(id(GROCERIES));
+
+        let result = repo.update_category("Alice", &id(FOOD), &request).await;
+
+        assert!(matches!(
+            result,
+            Err(CategoryRepositoryError::Validation(_))
+        ));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_use_category_of_other_user_as_parent(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+        let mut request = named("Snacks");
+        request.parent_id = Some(id(OTHER_USERS_FOOD));
+
+        let result = repo.create_category("Alice", &request).await;
+
+        assert_eq!(
+            result,
+            Err(CategoryRepositoryError::Validation(format!(
+                "Unknown parent category '{OTHER_USERS_FOOD}'"
+            )))
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn delete_unused_category(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        repo.delete_category("Alice", &id(FOOD)).await.unwrap();
+
+        let groceries = repo.get_category("Alice", &id(GROCERIES)).await.unwrap();
+        assert_eq!(groceries.parent_id, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_delete_category_with_items(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        let result = repo.delete_category("Alice", &id(EATING_OUT)).await;
+
+        assert!(matches!(result, Err(CategoryRepositoryError::Conflict(_))));
+        assert!(repo.get_category("Alice", &id(EATING_OUT)).await.is_ok());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn merge_category_moves_items_and_subcategories(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool.clone()));
+
+        let moved_items = repo
+            .merge_categories("Alice", &id(EATING_OUT), &id(FOOD))
+            .await
+            .unwrap();
+
+        assert_eq!(moved_items, 2);
+        assert_eq!(
+            repo.get_category("Alice", &id(EATING_OUT)).await,
+            Err(CategoryRepositoryError::NotFound)
+        );
+        let items = sqlx::query_scalar!(
+            r#"SELECT count(*) as "count!" FROM item WHERE category_id = $1"#,
+            id(FOOD)
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(items, 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn merge_category_into_its_subcategory(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        repo.merge_categories("Alice", &id(FOOD), &id(GROCERIES))
+            .await
+            .unwrap();
+
+        let groceries = repo.get_category("Alice", &id(GROCERIES)).await.unwrap();
+        assert_eq!(groceries.parent_id, None);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("categories"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn try_merge_into_category_of_other_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = CategoryRepository::new(Arc::new(pool));
+
+        let result = repo
+            .merge_categories("Alice", &id(INCOME), &id(OTHER_USERS_FOOD))
+            .await;
+
+        assert_eq!(result, Err(CategoryRepositoryError::NotFound));
+        assert!(repo.get_category("Alice", &id(INCOME)).await.is_ok());
+
+        Ok(())
+    }
+}
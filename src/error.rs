@@ -0,0 +1,336 @@
+use axum::{
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{
+    openapi::{
+        response::{Response as ResponseDoc, ResponseBuilder},
+        ContentBuilder, Ref, RefOr,
+    },
+    ToResponse, ToSchema,
+};
+
+/// Header used to correlate a request with its logs and error responses.
+pub(crate) static REQUEST_ID_HEADER: &str = "x-request-id";
+
+static PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Error type shared by all handlers in the API.
+///
+/// Every variant maps to a single status code, and is returned to the client
+/// as a [`ProblemDetails`] body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    /// The resource has changed since the version in `If-Match`.
+    PreconditionFailed(String),
+    PayloadTooLarge(String),
+    UnsupportedMediaType(String),
+    /// The request body is well-formed, but some of its fields are not valid.
+    Validation(Vec<FieldError>),
+    Internal,
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn detail(&self) -> Option<String> {
+        match self {
+            ApiError::BadRequest(detail)
+            | ApiError::Unauthorized(detail)
+            | ApiError::Forbidden(detail)
+            | ApiError::NotFound(detail)
+            | ApiError::Conflict(detail)
+            | ApiError::PreconditionFailed(detail)
+            | ApiError::PayloadTooLarge(detail)
+            | ApiError::UnsupportedMediaType(detail) => Some(detail.to_owned()),
+            ApiError::Validation(errors) => Some(
+                errors
+                    .iter()
+                    .map(|error| format!("{}: {}", error.field, error.message))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ),
+            // Internal errors are logged where they happen, and should never leak details.
+            ApiError::Internal => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.detail() {
+            Some(detail) => write!(f, "{}: {detail}", self.status()),
+            None => write!(f, "{}", self.status()),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let problem = ProblemDetails::from(&self);
+        let mut response = (
+            self.status(),
+            [(header::CONTENT_TYPE, PROBLEM_CONTENT_TYPE)],
+            Json(problem.clone()),
+        )
+            .into_response();
+        // Stored so `problem_details` can add the request specific fields afterwards.
+        response.extensions_mut().insert(problem);
+
+        response
+    }
+}
+
+/// Problem details body as described in [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Path of the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// The `x-request-id` of the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The fields that are not valid, when the status is `422`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
+}
+
+/// A field of a request that is not valid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the field, e.g. `title`, or `period.end` for nested fields.
+    pub field: String,
+    /// Identifies the kind of error, e.g. `blank` or `length`.
+    pub code: String,
+    pub message: String,
+}
+
+impl From<&ApiError> for ProblemDetails {
+    fn from(error: &ApiError) -> Self {
+        let status = error.status();
+        Self {
+            kind: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: error.detail(),
+            instance: None,
+            request_id: None,
+            errors: match error {
+                ApiError::Validation(errors) => Some(errors.clone()),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Documents that an error response is a [`ProblemDetails`] body.
+impl<'r> ToResponse<'r> for ProblemDetails {
+    fn response() -> (&'r str, RefOr<ResponseDoc>) {
+        let content = ContentBuilder::new()
+            .schema(Some(Ref::from_schema_name("ProblemDetails")))
+            .build();
+        let response = ResponseBuilder::new()
+            .description("The error as problem details")
+            .content(PROBLEM_CONTENT_TYPE, content)
+            .build();
+
+        ("Problem", response.into())
+    }
+}
+
+/// Middleware that completes any [`ProblemDetails`] returned by a handler with
+/// the path of the request as `instance` and the id of the request.
+pub async fn problem_details<B>(request: Request<B>, next: Next<B>) -> Response {
+    let instance = request.uri().path().to_string();
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .map(|id| id.to_string());
+
+    let response = next.run(request).await;
+    let Some(problem) = response.extensions().get::<ProblemDetails>().cloned() else {
+        return response;
+    };
+
+    let problem = ProblemDetails {
+        instance: Some(instance),
+        request_id,
+        ..problem
+    };
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+    );
+
+    (parts, Json(problem)).into_response()
+}
+
+/// Classification of errors returned from the database, which the repositories
+/// use to build their own error types.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum DatabaseError {
+    NotFound,
+    Conflict(String),
+    Validation(String),
+    Other,
+}
+
+impl From<sqlx::Error> for DatabaseError {
+    fn from(error: sqlx::Error) -> Self {
+        // See https://www.postgresql.org/docs/current/errcodes-appendix.html
+        const UNIQUE_VIOLATION: &str = "23505";
+        const FOREIGN_KEY_VIOLATION: &str = "23503";
+        const NOT_NULL_VIOLATION: &str = "23502";
+        const CHECK_VIOLATION: &str = "23514";
+        const STRING_DATA_RIGHT_TRUNCATION: &str = "22001";
+        const NUMERIC_VALUE_OUT_OF_RANGE: &str = "22003";
+
+        // The messages of Postgres name tables and constraints, so they are
+        // only logged, and the client gets a generic message.
+        match error {
+            sqlx::Error::RowNotFound => DatabaseError::NotFound,
+            sqlx::Error::Database(err) => match err.code().as_deref() {
+                Some(UNIQUE_VIOLATION) => {
+                    tracing::warn!("Unique violation: {}", err.message());
+                    DatabaseError::Conflict("A resource with the same values already exists".into())
+                }
+                Some(
+                    code @ (FOREIGN_KEY_VIOLATION
+                    | NOT_NULL_VIOLATION
+                    | CHECK_VIOLATION
+                    | STRING_DATA_RIGHT_TRUNCATION
+                    | NUMERIC_VALUE_OUT_OF_RANGE),
+                ) => {
+                    tracing::warn!("Invalid data: {}", err.message());
+                    let message = match code {
+                        FOREIGN_KEY_VIOLATION => "A referenced resource does not exist",
+                        NOT_NULL_VIOLATION => "A required value is missing",
+                        STRING_DATA_RIGHT_TRUNCATION => "A value is too long",
+                        NUMERIC_VALUE_OUT_OF_RANGE => "A value is out of range",
+                        _ => "A value is not allowed",
+                    };
+                    DatabaseError::Validation(message.to_string())
+                }
+                _ => {
+                    tracing::error!("Database error: {err:?}");
+                    DatabaseError::Other
+                }
+            },
+            err => {
+                tracing::error!("Database error: {err:?}");
+                DatabaseError::Other
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn api_error_status_codes() {
+        assert_eq!(
+            ApiError::BadRequest(String::new()).status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            ApiError::Forbidden(String::new()).status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            ApiError::NotFound(String::new()).status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::Conflict(String::new()).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::Validation(vec![]).status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            ApiError::Internal.status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn problem_details_from_api_error() {
+        let problem = ProblemDetails::from(&ApiError::NotFound("Budget not found".to_string()));
+
+        assert_eq!(problem.kind, "about:blank");
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.status, 404);
+        assert_eq!(problem.detail.as_deref(), Some("Budget not found"));
+    }
+
+    #[test]
+    fn problem_details_lists_the_fields_that_are_not_valid() {
+        let errors = vec![FieldError {
+            field: "title".to_string(),
+            code: "blank".to_string(),
+            message: "Must not be blank".to_string(),
+        }];
+
+        let problem = ProblemDetails::from(&ApiError::Validation(errors.clone()));
+
+        assert_eq!(problem.status, 422);
+        assert_eq!(problem.detail.as_deref(), Some("title: Must not be blank"));
+        assert_eq!(problem.errors, Some(errors));
+        assert_eq!(
+            ProblemDetails::from(&ApiError::Internal).errors,
+            None,
+            "only validation errors list fields"
+        );
+    }
+
+    #[test]
+    fn internal_error_does_not_expose_details() {
+        let problem = ProblemDetails::from(&ApiError::Internal);
+
+        assert_eq!(problem.status, 500);
+        assert_eq!(problem.detail, None);
+    }
+
+    #[test]
+    fn row_not_found_is_classified_as_not_found() {
+        assert_eq!(
+            DatabaseError::from(sqlx::Error::RowNotFound),
+            DatabaseError::NotFound
+        );
+    }
+}
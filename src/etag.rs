@@ -0,0 +1,196 @@
+//! Optimistic concurrency control with entity tags.
+//!
+//! Budgets and items have a version, which is incremented every time they
+//! change. It is sent as the strong `ETag` of the resource, e.g. `"3"`, and a
+//! client that sends it back in `If-Match` only changes the resource if no one
+//! else has changed it in the meantime. With `If-None-Match`, a client only
+//! gets the resource again if it has changed.
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::convert::Infallible;
+
+/// The `ETag` of a version of a resource.
+pub fn etag(version: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{version}\"")).expect("a quoted number is a valid header")
+}
+
+/// The tags of a conditional header, which is either `*` or a list of tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tags {
+    Any,
+    /// The versions of the tags, and whether they are weak. Tags that are not
+    /// versions are left out, as they never match.
+    List(Vec<(i64, bool)>),
+}
+
+impl Tags {
+    /// Parse all the values of the header, or `None` if there are none.
+    fn from_headers(headers: &HeaderMap, name: &HeaderName) -> Option<Tags> {
+        let mut tags = vec![];
+        let mut found = false;
+        for value in headers.get_all(name) {
+            found = true;
+            for tag in value.to_str().unwrap_or_default().split(',') {
+                let tag = tag.trim();
+                if tag == "*" {
+                    return Some(Tags::Any);
+                }
+                let (tag, weak) = match tag.strip_prefix("W/") {
+                    Some(tag) => (tag, true),
+                    None => (tag, false),
+                };
+                let version = tag
+                    .strip_prefix('"')
+                    .and_then(|tag| tag.strip_suffix('"'))
+                    .and_then(|tag| tag.parse().ok());
+                if let Some(version) = version {
+                    tags.push((version, weak));
+                }
+            }
+        }
+
+        found.then_some(Tags::List(tags))
+    }
+}
+
+/// The `If-Match` header of a request, with the versions a resource must have to be changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IfMatch(Option<Vec<i64>>);
+
+impl IfMatch {
+    /// The versions that can be changed, or `None` for any version, i.e.
+    /// without the header or with `*`. Weak tags never match.
+    pub fn versions(&self) -> Option<&[i64]> {
+        self.0.as_deref()
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(IfMatch(
+            match Tags::from_headers(&parts.headers, &header::IF_MATCH) {
+                None | Some(Tags::Any) => None,
+                Some(Tags::List(tags)) => Some(
+                    tags.into_iter()
+                        .filter(|(_, weak)| !weak)
+                        .map(|(version, _)| version)
+                        .collect(),
+                ),
+            },
+        ))
+    }
+}
+
+/// The `If-None-Match` header of a request, with the versions the client already has.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IfNoneMatch(Option<Tags>);
+
+impl IfNoneMatch {
+    /// Whether the client already has `version`, so it is not modified.
+    /// Weak tags also match.
+    pub fn matches(&self, version: i64) -> bool {
+        match &self.0 {
+            None => false,
+            Some(Tags::Any) => true,
+            Some(Tags::List(tags)) => tags.iter().any(|(tag, _)| *tag == version),
+        }
+    }
+
+    /// Respond with `body` and the `ETag` of its `version`, or with only the
+    /// `ETag` and `304 Not Modified` if the client already has that version.
+    pub fn respond(&self, version: i64, body: impl IntoResponse) -> Response {
+        let etag = [(header::ETAG, etag(version))];
+        if self.matches(version) {
+            (StatusCode::NOT_MODIFIED, etag).into_response()
+        } else {
+            (etag, body).into_response()
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfNoneMatch {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(IfNoneMatch(Tags::from_headers(
+            &parts.headers,
+            &header::IF_NONE_MATCH,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::http::Request;
+
+    async fn extract<T: FromRequestParts<(), Rejection = Infallible>>(
+        name: HeaderName,
+        values: &[&str],
+    ) -> T {
+        let mut request = Request::builder();
+        for value in values {
+            request = request.header(&name, *value);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+
+        T::from_request_parts(&mut parts, &()).await.unwrap()
+    }
+
+    #[test]
+    fn etag_is_the_quoted_version() {
+        assert_eq!(etag(3), "\"3\"");
+    }
+
+    #[tokio::test]
+    async fn if_match_only_matches_strong_versions() {
+        let none: IfMatch = extract(header::IF_MATCH, &[]).await;
+        let any: IfMatch = extract(header::IF_MATCH, &["*"]).await;
+        let list: IfMatch = extract(header::IF_MATCH, &["\"1\", W/\"2\"", "\"3\""]).await;
+        let other: IfMatch = extract(header::IF_MATCH, &["\"abc\""]).await;
+
+        assert_eq!(none.versions(), None);
+        assert_eq!(any.versions(), None);
+        assert_eq!(list.versions(), Some(&[1, 3][..]));
+        assert_eq!(other.versions(), Some(&[][..]));
+    }
+
+    #[tokio::test]
+    async fn if_none_match_also_matches_weak_versions() {
+        let none: IfNoneMatch = extract(header::IF_NONE_MATCH, &[]).await;
+        let any: IfNoneMatch = extract(header::IF_NONE_MATCH, &["*"]).await;
+        let list: IfNoneMatch = extract(header::IF_NONE_MATCH, &["W/\"2\", \"4\""]).await;
+
+        assert!(!none.matches(1));
+        assert!(any.matches(1));
+        assert!(list.matches(2));
+        assert!(list.matches(4));
+        assert!(!list.matches(3));
+    }
+
+    #[tokio::test]
+    async fn respond_without_body_when_not_modified() {
+        let if_none_match: IfNoneMatch = extract(header::IF_NONE_MATCH, &["\"2\""]).await;
+
+        let modified = if_none_match.respond(3, "body");
+        let not_modified = if_none_match.respond(2, "body");
+
+        assert_eq!(modified.status(), StatusCode::OK);
+        assert_eq!(modified.headers()[header::ETAG], "\"3\"");
+        assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(not_modified.headers()[header::ETAG], "\"2\"");
+        let body = hyper::body::to_bytes(not_modified.into_body())
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+    }
+}
@@ -1,9 +1,25 @@
 use axum::{http::StatusCode, routing::get, Router};
+use utoipa::OpenApi;
+
+/// Documentation of the routes in [`create_router`].
+#[derive(OpenApi)]
+#[openapi(
+    paths(is_alive),
+    tags((name = "health", description = "Status of the service")),
+)]
+pub struct HealthApi;
 
 pub fn create_router() -> Router {
     Router::new().route("/", get(is_alive))
 }
 
+/// Check that the service is running.
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "health",
+    responses((status = 200, description = "The service is running")),
+)]
 async fn is_alive() -> StatusCode {
     StatusCode::OK
 }
@@ -1,17 +1,33 @@
-use crate::app_state::AppState;
+use crate::{app_state::AppState, error::ApiError};
 use anyhow::Result;
 use axum::{
-    http::{StatusCode, Uri},
-    Router, Server,
+    http::{HeaderName, Uri},
+    middleware, Router, Server,
 };
 use std::net::TcpListener;
-use tower_http::trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer};
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
+};
 use tracing_subscriber::{self, layer::SubscriberExt, util::SubscriberInitExt};
 
 pub mod app_state;
+pub mod audit;
 pub mod auth;
 pub mod budget;
+pub mod category;
+pub mod error;
+pub mod etag;
 mod health_check;
+pub mod money;
+pub mod openapi;
+pub mod pagination;
+pub mod patch;
+pub mod response;
+pub mod template;
+mod text_type;
+pub mod trash;
+pub mod validation;
 
 #[derive(Debug)]
 pub struct App {
@@ -27,9 +43,16 @@ impl App {
         tracing::trace!("Initialize services");
 
         let app_state = AppState::initialize().await?;
-        let router = Self::build_router(app_state);
 
-        Ok(Self { router })
+        Ok(Self::with_state(app_state))
+    }
+
+    /// Create the app from an already initialized state, e.g. one that
+    /// trusts a local identity provider in tests.
+    pub fn with_state(app_state: AppState) -> Self {
+        Self {
+            router: Self::build_router(app_state),
+        }
     }
 
     /// Serve this app on the given [`TcpListener`].
@@ -46,15 +69,24 @@ impl App {
         use tracing::Level;
 
         tracing::trace!("Building app");
+        let request_id_header = HeaderName::from_static(error::REQUEST_ID_HEADER);
         Router::new()
             .nest("/health", health_check::create_router())
-            .nest("/budget", budget::create_router(app_state))
+            .nest("/budget", budget::create_router(app_state.clone()))
+            .nest("/category", category::create_router(app_state.clone()))
+            .nest("/template", template::create_router(app_state.clone()))
+            .nest("/trash", trash::create_router(app_state))
+            .merge(openapi::create_router())
+            .layer(middleware::from_fn(audit::with_request_id))
+            .layer(middleware::from_fn(error::problem_details))
             .layer(
                 TraceLayer::new_for_http()
                     .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
                     .on_request(DefaultOnRequest::new().level(Level::INFO))
                     .on_response(DefaultOnResponse::new().level(Level::INFO)),
             )
+            .layer(PropagateRequestIdLayer::new(request_id_header.clone()))
+            .layer(SetRequestIdLayer::new(request_id_header, MakeRequestUuid))
             .fallback(not_found)
     }
 }
@@ -73,7 +105,7 @@ fn setup_tracing() -> Result<()> {
     Ok(())
 }
 
-async fn not_found(uri: Uri) -> (StatusCode, String) {
+async fn not_found(uri: Uri) -> ApiError {
     tracing::warn!("Path not found {uri}");
-    (StatusCode::NOT_FOUND, format!("No route for '{uri}'"))
+    ApiError::NotFound(format!("No route for '{uri}'"))
 }
@@ -0,0 +1,341 @@
+use crate::text_type::text_type;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt::Display, str::FromStr};
+use utoipa::{
+    openapi::{schema::Type as SchemaType, ObjectBuilder, RefOr, Schema},
+    PartialSchema, ToSchema,
+};
+
+/// An ISO 4217 currency, along with the number of digits in its minor unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency {
+    code: [u8; 3],
+    minor_units: u8,
+}
+
+impl Currency {
+    pub const DKK: Currency = Currency {
+        code: *b"DKK",
+        minor_units: 2,
+    };
+
+    /// The three letter code of the currency, e.g. `DKK`.
+    pub fn code(&self) -> &str {
+        std::str::from_utf8(&self.code).expect("currency codes to be ASCII")
+    }
+
+    /// Number of digits after the decimal separator, e.g. 2 for `EUR` and 0 for `JPY`.
+    pub fn minor_units(&self) -> u8 {
+        self.minor_units
+    }
+}
+
+/// The currency of budgets created without one.
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::DKK
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCurrency(pub String);
+
+impl Display for UnknownCurrency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown currency code '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownCurrency {}
+
+impl FromStr for Currency {
+    type Err = UnknownCurrency;
+
+    /// Parse an active ISO 4217 currency code. Codes for funds and precious
+    /// metals are not supported.
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let minor_units = match code {
+            "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF"
+            | "UGX" | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+            "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+            "CLF" | "UYW" => 4,
+            "AED" | "AFN" | "ALL" | "AMD" | "ANG" | "AOA" | "ARS" | "AUD" | "AWG" | "AZN"
+            | "BAM" | "BBD" | "BDT" | "BGN" | "BMD" | "BND" | "BOB" | "BRL" | "BSD" | "BTN"
+            | "BWP" | "BYN" | "BZD" | "CAD" | "CDF" | "CHF" | "CNY" | "COP" | "CRC" | "CUP"
+            | "CVE" | "CZK" | "DKK" | "DOP" | "DZD" | "EGP" | "ERN" | "ETB" | "EUR" | "FJD"
+            | "FKP" | "GBP" | "GEL" | "GHS" | "GIP" | "GMD" | "GTQ" | "GYD" | "HKD" | "HNL"
+            | "HTG" | "HUF" | "IDR" | "ILS" | "INR" | "IRR" | "JMD" | "KES" | "KGS" | "KHR"
+            | "KPW" | "KYD" | "KZT" | "LAK" | "LBP" | "LKR" | "LRD" | "LSL" | "MAD" | "MDL"
+            | "MGA" | "MKD" | "MMK" | "MNT" | "MOP" | "MRU" | "MUR" | "MVR" | "MWK" | "MXN"
+            | "MYR" | "MZN" | "NAD" | "NGN" | "NIO" | "NOK" | "NPR" | "NZD" | "PAB" | "PEN"
+            | "PGK" | "PHP" | "PKR" | "PLN" | "QAR" | "RON" | "RSD" | "RUB" | "SAR" | "SBD"
+            | "SCR" | "SDG" | "SEK" | "SGD" | "SHP" | "SLE" | "SOS" | "SRD" | "SSP" | "STN"
+            | "SVC" | "SYP" | "SZL" | "THB" | "TJS" | "TMT" | "TOP" | "TRY" | "TTD" | "TWD"
+            | "TZS" | "UAH" | "USD" | "UYU" | "UZS" | "VES" | "WST" | "XCD" | "YER" | "ZAR"
+            | "ZMW" | "ZWL" => 2,
+            _ => return Err(UnknownCurrency(code.to_string())),
+        };
+
+        Ok(Self {
+            code: code
+                .as_bytes()
+                .try_into()
+                .expect("known codes to be 3 letters"),
+            minor_units,
+        })
+    }
+}
+
+impl Serialize for Currency {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        code.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Documented as the currency code it is serialized as.
+impl PartialSchema for Currency {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(SchemaType::String)
+            .description(Some("ISO 4217 currency code"))
+            .pattern(Some("^[A-Z]{3}$"))
+            .examples(["DKK"])
+            .into()
+    }
+}
+
+impl ToSchema for Currency {}
+
+text_type!(Currency, code);
+
+/// An amount of money, stored as an integer number of minor units of its
+/// currency, e.g. cents for `EUR`.
+///
+/// Serialized with both the minor units and the amount formatted as a decimal number:
+/// `{ "minor_units": 12350, "currency": "DKK", "formatted": "123.50" }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "MoneyRepresentation", from = "MoneyRepresentation")]
+pub struct Money {
+    minor_units: i64,
+    currency: Currency,
+}
+
+impl Money {
+    pub fn new(minor_units: i64, currency: Currency) -> Self {
+        Self {
+            minor_units,
+            currency,
+        }
+    }
+
+    pub fn minor_units(&self) -> i64 {
+        self.minor_units
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// The amount as a decimal number with the number of digits used by the
+    /// currency, e.g. `-12.05`.
+    pub fn formatted(&self) -> String {
+        let digits = self.currency.minor_units() as usize;
+        if digits == 0 {
+            return self.minor_units.to_string();
+        }
+
+        let divisor = 10u64.pow(digits as u32);
+        let amount = self.minor_units.unsigned_abs();
+        let sign = if self.minor_units < 0 { "-" } else { "" };
+        format!("{sign}{}.{:0digits$}", amount / divisor, amount % divisor)
+    }
+}
+
+/// Parse a decimal amount, e.g. `-1,234.50`, into minor units of `currency`.
+///
+/// `decimal_separator` is either `.` or `,`, and the other one is taken as a
+/// thousands separator, so `1.234,50` can be parsed with a decimal comma.
+pub fn parse_amount(
+    value: &str,
+    currency: Currency,
+    decimal_separator: char,
+) -> Result<i64, String> {
+    let invalid = || format!("Invalid amount '{value}'");
+    let thousands_separator = if decimal_separator == ',' { '.' } else { ',' };
+    let digits: String = value
+        .trim()
+        .chars()
+        .filter(|c| *c != thousands_separator && *c != ' ')
+        .collect();
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, digits.strip_prefix('+').unwrap_or(&digits)),
+    };
+    let (whole, fraction) = digits.split_once(decimal_separator).unwrap_or((digits, ""));
+    let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if whole.len() + fraction.len() == 0 || !all_digits(whole) || !all_digits(fraction) {
+        return Err(invalid());
+    }
+
+    let minor_units = currency.minor_units() as usize;
+    let (fraction, rest) = fraction.split_at(fraction.len().min(minor_units));
+    if rest.chars().any(|c| c != '0') {
+        return Err(format!(
+            "Amount '{value}' has more than {minor_units} decimals for {currency}"
+        ));
+    }
+    let fraction = format!("{fraction:0<minor_units$}");
+    let amount: i64 = format!("{whole}{fraction}")
+        .parse()
+        .map_err(|_| invalid())?;
+
+    Ok(if negative { -amount } else { amount })
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.formatted(), self.currency)
+    }
+}
+
+/// Documented as the [`MoneyRepresentation`] it is serialized as.
+impl PartialSchema for Money {
+    fn schema() -> RefOr<Schema> {
+        MoneyRepresentation::schema()
+    }
+}
+
+impl ToSchema for Money {
+    fn schemas(schemas: &mut Vec<(String, RefOr<Schema>)>) {
+        MoneyRepresentation::schemas(schemas);
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct MoneyRepresentation {
+    /// The amount in the minor unit of the currency, e.g. cents.
+    minor_units: i64,
+    currency: Currency,
+    /// Only for presentation, the amount is always read from `minor_units`.
+    #[serde(default)]
+    #[schema(read_only)]
+    formatted: String,
+}
+
+impl From<Money> for MoneyRepresentation {
+    fn from(money: Money) -> Self {
+        Self {
+            minor_units: money.minor_units,
+            currency: money.currency,
+            formatted: money.formatted(),
+        }
+    }
+}
+
+impl From<MoneyRepresentation> for Money {
+    fn from(money: MoneyRepresentation) -> Self {
+        Money::new(money.minor_units, money.currency)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn currency(code: &str) -> Currency {
+        code.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_known_currencies() {
+        assert_eq!(currency("DKK"), Currency::DKK);
+        assert_eq!(currency("EUR").minor_units(), 2);
+        assert_eq!(currency("JPY").minor_units(), 0);
+        assert_eq!(currency("KWD").minor_units(), 3);
+        assert_eq!(currency("USD").code(), "USD");
+    }
+
+    #[test]
+    fn parse_rejects_unknown_currencies() {
+        assert_eq!(
+            "ABC".parse::<Currency>(),
+            Err(UnknownCurrency("ABC".to_string()))
+        );
+        assert!("dkk".parse::<Currency>().is_err());
+        assert!("EURO".parse::<Currency>().is_err());
+        assert!("".parse::<Currency>().is_err());
+    }
+
+    #[test]
+    fn parse_amounts_with_decimal_point_or_comma() {
+        let dkk = Currency::DKK;
+        assert_eq!(parse_amount("123.5", dkk, '.'), Ok(12350));
+        assert_eq!(parse_amount("-1,234.05", dkk, '.'), Ok(-123405));
+        assert_eq!(parse_amount("-1.234,05", dkk, ','), Ok(-123405));
+        assert_eq!(parse_amount(" 1 234,5 ", dkk, ','), Ok(123450));
+        assert_eq!(parse_amount("+7", dkk, ','), Ok(700));
+        assert_eq!(parse_amount(",50", dkk, ','), Ok(50));
+        assert_eq!(parse_amount("1500", currency("JPY"), '.'), Ok(1500));
+        assert_eq!(parse_amount("1500.00", currency("JPY"), '.'), Ok(1500));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_amounts() {
+        let dkk = Currency::DKK;
+        for amount in ["", "-", "12a", "1.2.3", "--1", "99999999999999999999"] {
+            assert!(parse_amount(amount, dkk, '.').is_err(), "{amount}");
+        }
+        assert!(parse_amount("1.005", dkk, '.').is_err());
+        assert!(parse_amount("1.5", currency("JPY"), '.').is_err());
+    }
+
+    #[test]
+    fn format_money_with_minor_units_of_currency() {
+        assert_eq!(Money::new(12350, currency("DKK")).formatted(), "123.50");
+        assert_eq!(Money::new(5, currency("EUR")).formatted(), "0.05");
+        assert_eq!(Money::new(-1205, currency("USD")).formatted(), "-12.05");
+        assert_eq!(Money::new(-5, currency("USD")).formatted(), "-0.05");
+        assert_eq!(Money::new(1500, currency("JPY")).formatted(), "1500");
+        assert_eq!(Money::new(1234, currency("KWD")).formatted(), "1.234");
+        assert_eq!(
+            Money::new(i64::MIN, currency("EUR")).formatted(),
+            "-92233720368547758.08"
+        );
+    }
+
+    #[test]
+    fn serialize_money() {
+        let money = Money::new(12350, Currency::DKK);
+
+        let json = serde_json::to_value(money).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({ "minor_units": 12350, "currency": "DKK", "formatted": "123.50" })
+        );
+        assert_eq!(serde_json::from_value::<Money>(json).unwrap(), money);
+    }
+
+    #[test]
+    fn deserialize_rejects_unknown_currency() {
+        let result = serde_json::from_str::<Money>(r#"{ "minor_units": 1, "currency": "XYZ" }"#);
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown currency code 'XYZ'"));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{
+    auth::scope::Scope,
+    budget::BudgetApi,
+    category::CategoryApi,
+    error::{FieldError, ProblemDetails},
+    health_check::HealthApi,
+    template::TemplateApi,
+    trash::TrashApi,
+};
+use axum::{routing::get, Json, Router};
+use utoipa::{
+    openapi::{
+        schema::{SchemaFormat, Type as SchemaType},
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+        KnownFormat, ObjectBuilder, Ref, RefOr, Schema,
+    },
+    Modify, OpenApi, PartialSchema, ToSchema,
+};
+
+/// Name of the security scheme for the bearer tokens from [`auth`](crate::auth).
+const BEARER: &str = "bearer";
+
+/// OpenAPI document of all the routes of the app, served at `/openapi.json`.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Budget API",
+        description = "Budgets with planned items, recurring schedules, and a ledger of actual transactions."
+    ),
+    nest(
+        (path = "/health", api = HealthApi),
+        (path = "/budget", api = BudgetApi),
+        (path = "/category", api = CategoryApi),
+        (path = "/template", api = TemplateApi),
+        (path = "/trash", api = TrashApi),
+    ),
+    components(schemas(ProblemDetails, FieldError), responses(ProblemDetails)),
+    modifiers(&BearerAuth, &JsonBodyErrors, &WithoutLicense),
+)]
+pub struct ApiDoc;
+
+/// Adds the bearer security scheme, and the errors of authenticating every
+/// operation that requires a token.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let scopes = Scope::ALL
+            .iter()
+            .map(|scope| format!("`{}`", scope.default_name()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let scheme = HttpBuilder::new()
+            .scheme(HttpAuthScheme::Bearer)
+            .bearer_format("JWT")
+            .description(Some(format!(
+                "Access token of the identity provider, with the scopes required by the operation: {scopes}"
+            )))
+            .build();
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(BEARER, SecurityScheme::Http(scheme));
+
+        for path in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut path.get,
+                &mut path.put,
+                &mut path.post,
+                &mut path.delete,
+                &mut path.patch,
+            ];
+            for operation in operations.into_iter().flatten() {
+                if operation.security.is_none() {
+                    continue;
+                }
+                for status in ["401", "403", "500"] {
+                    operation
+                        .responses
+                        .responses
+                        .entry(status.to_string())
+                        .or_insert_with(|| RefOr::Ref(Ref::from_response_name("Problem")));
+                }
+            }
+        }
+    }
+}
+
+/// Adds the errors of [`ValidatedJson`](crate::validation::ValidatedJson) to
+/// every operation with a JSON request body.
+struct JsonBodyErrors;
+
+impl Modify for JsonBodyErrors {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for path in openapi.paths.paths.values_mut() {
+            let operations = [&mut path.put, &mut path.post, &mut path.patch];
+            for operation in operations.into_iter().flatten() {
+                let has_json_body = operation
+                    .request_body
+                    .as_ref()
+                    .is_some_and(|body| body.content.contains_key("application/json"));
+                if !has_json_body {
+                    continue;
+                }
+                for status in ["400", "415", "422"] {
+                    operation
+                        .responses
+                        .responses
+                        .entry(status.to_string())
+                        .or_insert_with(|| RefOr::Ref(Ref::from_response_name("Problem")));
+                }
+            }
+        }
+    }
+}
+
+/// The project has no license to name, which the macro otherwise fills in
+/// with an empty name from the manifest.
+struct WithoutLicense;
+
+impl Modify for WithoutLicense {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.license = None;
+    }
+}
+
+/// A file that is downloaded, e.g. an export.
+pub(crate) struct File;
+
+impl PartialSchema for File {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(SchemaType::String)
+            .format(Some(SchemaFormat::KnownFormat(KnownFormat::Binary)))
+            .into()
+    }
+}
+
+impl ToSchema for File {}
+
+pub fn create_router() -> Router {
+    let router = Router::new().route("/openapi.json", get(openapi_json));
+
+    #[cfg(feature = "docs_ui")]
+    let router = router.route("/docs", get(docs_ui));
+
+    router
+}
+
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Documentation of [`ApiDoc`] rendered with [Redoc](https://github.com/Redocly/redoc).
+#[cfg(feature = "docs_ui")]
+async fn docs_ui() -> axum::response::Html<&'static str> {
+    axum::response::Html(
+        r#"<!DOCTYPE html>
+<html>
+  <head>
+    <title>Budget API</title>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+  </head>
+  <body>
+    <redoc spec-url="/openapi.json"></redoc>
+    <script src="https://cdn.redoc.ly/redoc/latest/bundles/redoc.standalone.js"></script>
+  </body>
+</html>"#,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OPENAPI_JSON: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    /// The committed `openapi.json` is what clients are generated from, so any
+    /// change to the API must also be committed there. Run the tests with
+    /// `UPDATE_OPENAPI=1` to update it.
+    #[test]
+    fn openapi_json_is_up_to_date() {
+        let generated = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(OPENAPI_JSON, &generated).unwrap();
+        }
+        let committed = std::fs::read_to_string(OPENAPI_JSON).unwrap_or_default();
+
+        assert!(
+            committed == generated,
+            "openapi.json is out of date, run the tests with UPDATE_OPENAPI=1 to update it"
+        );
+    }
+
+    #[test]
+    fn operations_require_the_scopes_of_their_handlers() {
+        let openapi = ApiDoc::openapi();
+
+        let get_budget = openapi.paths.paths["/budget/{id}"].get.as_ref().unwrap();
+        let merge = openapi.paths.paths["/category/{id}/merge"]
+            .post
+            .as_ref()
+            .unwrap();
+
+        let scopes = |operation: &utoipa::openapi::path::Operation| {
+            serde_json::to_value(operation.security.as_ref().unwrap()).unwrap()
+        };
+        assert_eq!(
+            scopes(get_budget),
+            serde_json::json!([{ "bearer": ["budget:read"] }])
+        );
+        assert_eq!(
+            scopes(merge),
+            serde_json::json!([{ "bearer": ["category:write"] }])
+        );
+        assert!(get_budget.responses.responses.contains_key("401"));
+    }
+
+    #[test]
+    fn all_referenced_components_are_defined() {
+        let openapi = ApiDoc::openapi();
+        let components = openapi.components.as_ref().unwrap();
+        let json = openapi.to_json().unwrap();
+
+        for (prefix, defined) in [
+            (
+                "#/components/schemas/",
+                components.schemas.keys().collect::<Vec<_>>(),
+            ),
+            (
+                "#/components/responses/",
+                components.responses.keys().collect(),
+            ),
+        ] {
+            for reference in json.split(prefix).skip(1) {
+                let name = &reference[..reference.find('"').unwrap()];
+                assert!(
+                    defined.iter().any(|defined| *defined == name),
+                    "{prefix}{name} is not defined"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn operations_with_a_json_body_can_be_rejected_as_not_valid() {
+        let openapi = ApiDoc::openapi();
+
+        let create = openapi.paths.paths["/budget"].post.as_ref().unwrap();
+        let import = openapi.paths.paths["/budget/{id}/import"]
+            .post
+            .as_ref()
+            .unwrap();
+
+        assert!(create.responses.responses.contains_key("422"));
+        assert!(create.responses.responses.contains_key("415"));
+        assert!(!import.responses.responses.contains_key("422"));
+    }
+
+    #[test]
+    fn health_check_does_not_require_a_token() {
+        let openapi = ApiDoc::openapi();
+
+        let health = openapi.paths.paths["/health"].get.as_ref().unwrap();
+
+        assert!(health.security.is_none());
+        assert!(!health.responses.responses.contains_key("401"));
+    }
+}
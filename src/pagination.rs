@@ -0,0 +1,124 @@
+//! Cursor based pagination of lists.
+//!
+//! A cursor points at the last element of a page, and the next page starts
+//! right after it. Cursors are opaque to clients, but are simply the sort key
+//! of the element encoded as base64 JSON.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Number of elements in a page if no limit is requested.
+pub const DEFAULT_LIMIT: u32 = 20;
+/// Largest number of elements that can be requested in a page.
+pub const MAX_LIMIT: u32 = 100;
+
+/// A page of a list, along with the cursor to get the next page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor to pass to get the next page, `None` if this is the last page.
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Create a page from `items` fetched with a limit of one more than `limit`,
+    /// so the extra element tells if there is a next page.
+    pub fn from_overfetched<C: Serialize>(
+        mut items: Vec<T>,
+        limit: u32,
+        cursor: impl Fn(&T) -> C,
+    ) -> Self {
+        let has_next = items.len() > limit as usize;
+        items.truncate(limit as usize);
+        let next_cursor = match has_next {
+            true => items.last().map(|last| encode_cursor(&cursor(last))),
+            false => None,
+        };
+
+        Self { items, next_cursor }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+pub fn encode_cursor<C: Serialize>(cursor: &C) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(cursor).expect("cursors to be serializable"))
+}
+
+/// Decode a cursor created by [`encode_cursor`], returning `None` if it has
+/// been tampered with or belongs to another list.
+pub fn decode_cursor<C: DeserializeOwned>(cursor: &str) -> Option<C> {
+    let json = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// Check that a requested limit is within `1..=MAX_LIMIT`, using [`DEFAULT_LIMIT`] if none is given.
+pub fn check_limit(limit: Option<u32>) -> Result<u32, String> {
+    match limit.unwrap_or(DEFAULT_LIMIT) {
+        limit @ 1..=MAX_LIMIT => Ok(limit),
+        _ => Err(format!("Limit must be between 1 and {MAX_LIMIT}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = ("Budget".to_string(), 42);
+
+        let encoded = encode_cursor(&cursor);
+
+        assert_eq!(decode_cursor::<(String, i32)>(&encoded), Some(cursor));
+        assert_eq!(decode_cursor::<(String, i32)>("not a cursor"), None);
+        assert_eq!(decode_cursor::<(String, i32)>(&encode_cursor(&1)), None);
+    }
+
+    #[test]
+    fn page_has_next_cursor_if_more_items_were_fetched() {
+        let page = Page::from_overfetched(vec![1, 2, 3], 2, |n| *n);
+
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.next_cursor, Some(encode_cursor(&2)));
+    }
+
+    #[test]
+    fn last_page_has_no_next_cursor() {
+        let page = Page::from_overfetched(vec![1, 2], 2, |n| *n);
+
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn limit_must_be_within_bounds() {
+        assert_eq!(check_limit(None), Ok(DEFAULT_LIMIT));
+        assert_eq!(check_limit(Some(1)), Ok(1));
+        assert_eq!(check_limit(Some(MAX_LIMIT)), Ok(MAX_LIMIT));
+        assert!(check_limit(Some(0)).is_err());
+        assert!(check_limit(Some(MAX_LIMIT + 1)).is_err());
+    }
+}
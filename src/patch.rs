@@ -0,0 +1,72 @@
+//! Deserializers for the fields of partial updates, where a field that is left
+//! out keeps its current value. Use them with `#[serde(default, deserialize_with = ...)]`.
+
+use serde::{Deserialize, Deserializer};
+
+/// A field that cannot be cleared: `None` when left out, and `null` is rejected
+/// instead of being taken as leaving the field out.
+pub fn required<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// A field that can be cleared: `None` when left out, `Some(None)` for `null`,
+/// and `Some(Some(value))` for a new value.
+pub fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Patch {
+        #[serde(default, deserialize_with = "required")]
+        name: Option<String>,
+        #[serde(default, deserialize_with = "nullable")]
+        memo: Option<Option<String>>,
+    }
+
+    fn parse(json: &str) -> Result<Patch, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn fields_that_are_left_out_are_none() {
+        assert_eq!(
+            parse("{}").unwrap(),
+            Patch {
+                name: None,
+                memo: None
+            }
+        );
+    }
+
+    #[test]
+    fn null_clears_a_nullable_field() {
+        assert_eq!(
+            parse(r#"{"name": "Rent", "memo": null}"#).unwrap(),
+            Patch {
+                name: Some("Rent".to_string()),
+                memo: Some(None)
+            }
+        );
+        assert_eq!(
+            parse(r#"{"memo": "Monthly"}"#).unwrap().memo,
+            Some(Some("Monthly".to_string()))
+        );
+    }
+
+    #[test]
+    fn null_is_rejected_for_a_required_field() {
+        assert!(parse(r#"{"name": null}"#).is_err());
+    }
+}
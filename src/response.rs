@@ -0,0 +1,127 @@
+//! Responses shared by the handlers, with content negotiation for the clients
+//! that still expect the legacy plain text responses.
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::convert::Infallible;
+use uuid::Uuid;
+
+/// The representation a client prefers in the `Accept` header of a request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Accept {
+    /// JSON, which is also used without an `Accept` header or with `*/*`.
+    #[default]
+    Json,
+    /// Plain text, which is the id of a created resource.
+    Text,
+}
+
+impl Accept {
+    /// Parse the media ranges of an `Accept` header, e.g. `text/plain, application/json;q=0.5`,
+    /// preferring JSON unless plain text has a higher quality.
+    fn parse(value: &str) -> Accept {
+        let mut json = 0.0_f32;
+        let mut text = 0.0_f32;
+        for range in value.split(',') {
+            let mut parts = range.split(';');
+            let media_type = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            match media_type.as_str() {
+                "application/json" | "application/*" => json = json.max(quality),
+                "text/plain" | "text/*" => text = text.max(quality),
+                "*/*" => {
+                    json = json.max(quality);
+                    text = text.max(quality / 2.0);
+                }
+                _ => {}
+            }
+        }
+
+        match text > json {
+            true => Accept::Text,
+            false => Accept::Json,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Accept {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let ranges: Vec<_> = parts
+            .headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect();
+
+        Ok(Accept::parse(&ranges.join(",")))
+    }
+}
+
+/// `201 Created` with the `Location` of a new resource, and the resource as JSON,
+/// or only its id for clients that accept plain text.
+pub fn created(accept: Accept, location: String, id: Uuid, resource: impl Serialize) -> Response {
+    let location = [(header::LOCATION, location)];
+    match accept {
+        Accept::Json => (StatusCode::CREATED, location, Json(resource)).into_response(),
+        Accept::Text => (StatusCode::CREATED, location, id.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_is_preferred_unless_text_has_a_higher_quality() {
+        assert_eq!(Accept::parse(""), Accept::Json);
+        assert_eq!(Accept::parse("*/*"), Accept::Json);
+        assert_eq!(Accept::parse("application/json"), Accept::Json);
+        assert_eq!(Accept::parse("text/plain, application/json"), Accept::Json);
+        assert_eq!(Accept::parse("text/plain"), Accept::Text);
+        assert_eq!(Accept::parse("text/*, */*;q=0.1"), Accept::Text);
+        assert_eq!(
+            Accept::parse("application/json;q=0.5, Text/Plain"),
+            Accept::Text
+        );
+    }
+
+    #[tokio::test]
+    async fn created_has_the_location_and_the_resource_or_its_id() {
+        let id = Uuid::new_v4();
+        let location = format!("/budget/{id}");
+
+        let json = created(
+            Accept::Json,
+            location.clone(),
+            id,
+            serde_json::json!({ "id": id }),
+        );
+        let text = created(
+            Accept::Text,
+            location.clone(),
+            id,
+            serde_json::json!({ "id": id }),
+        );
+
+        assert_eq!(json.status(), StatusCode::CREATED);
+        assert_eq!(json.headers()[header::LOCATION], location.as_str());
+        let body = hyper::body::to_bytes(json.into_body()).await.unwrap();
+        assert_eq!(body, format!(r#"{{"id":"{id}"}}"#));
+        assert_eq!(text.status(), StatusCode::CREATED);
+        assert_eq!(text.headers()[header::LOCATION], location.as_str());
+        let body = hyper::body::to_bytes(text.into_body()).await.unwrap();
+        assert_eq!(body, id.to_string());
+    }
+}
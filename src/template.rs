@@ -0,0 +1,320 @@
+pub mod dto;
+mod model;
+pub(crate) mod repository;
+
+use crate::app_state::AppState;
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+use utoipa::OpenApi;
+
+/// Documentation of the routes in [`create_router`].
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        endpoints::get_all_templates,
+        endpoints::create_template,
+        endpoints::get_template,
+        endpoints::update_template,
+        endpoints::delete_template,
+        endpoints::create_budget_from_template,
+    ),
+    tags((name = "template", description = "Templates that new budgets can be created from")),
+)]
+pub struct TemplateApi;
+
+pub fn create_router(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(endpoints::get_all_templates))
+        .route("/", post(endpoints::create_template))
+        .route("/:id", delete(endpoints::delete_template))
+        .route("/:id", get(endpoints::get_template))
+        .route("/:id", put(endpoints::update_template))
+        .route("/:id/budget", post(endpoints::create_budget_from_template))
+        .with_state(state)
+}
+
+mod endpoints {
+    use super::{dto, repository::TemplateRepository};
+    use crate::{
+        app_state::AppState,
+        auth::scope::{Authorized, BudgetRead, BudgetWrite},
+        budget::{dto::Budget, repository::BudgetRepository},
+        error::{ApiError, ProblemDetails},
+        response::{created, Accept},
+        validation::{OptionalJson, ValidatedJson},
+    };
+    use axum::{
+        debug_handler,
+        extract::{Path, State},
+        http::StatusCode,
+        response::Response,
+        Json,
+    };
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    /// Create a new template.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "",
+        tag = "template",
+        request_body = dto::TemplateRequest,
+        responses(
+            (status = 201, description = "The new template, or only its id with `Accept: text/plain`", content((dto::Template = "application/json"), (String = "text/plain")), headers(("Location" = String, description = "Path of the new template"))),
+            (status = 400, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn create_template(
+        State(repository): State<Arc<TemplateRepository>>,
+        claims: Authorized<BudgetWrite>,
+        accept: Accept,
+        ValidatedJson(payload): ValidatedJson<dto::TemplateRequest>,
+    ) -> Result<Response, ApiError> {
+        tracing::info!("Creating template for user '{}'", claims.user_id());
+
+        let id = repository
+            .create_template(claims.user_id(), &payload)
+            .await?;
+        let template = repository.get_template(claims.user_id(), &id).await?;
+
+        Ok(created(
+            accept,
+            format!("/template/{id}"),
+            id,
+            dto::Template::from(&template),
+        ))
+    }
+
+    /// Get all templates of the user.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "",
+        tag = "template",
+        responses((status = 200, body = Vec<dto::Template>)),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_all_templates(
+        State(repository): State<Arc<TemplateRepository>>,
+        claims: Authorized<BudgetRead>,
+    ) -> Result<Json<Vec<dto::Template>>, ApiError> {
+        tracing::info!("Get all templates for user {}", claims.user_id());
+
+        Ok(Json(
+            repository
+                .get_all_templates(claims.user_id())
+                .await?
+                .iter()
+                .map(|x| x.into())
+                .collect(),
+        ))
+    }
+
+    /// Get a template from its id.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "/{id}",
+        tag = "template",
+        params(("id" = Uuid, Path, description = "Id of the template")),
+        responses(
+            (status = 200, body = dto::Template),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_template(
+        State(repository): State<Arc<TemplateRepository>>,
+        Path(template_id): Path<Uuid>,
+        claims: Authorized<BudgetRead>,
+    ) -> Result<Json<dto::Template>, ApiError> {
+        tracing::info!("Get template {template_id} for user: {}", claims.user_id());
+
+        let template = repository
+            .get_template(claims.user_id(), &template_id)
+            .await?;
+
+        Ok(Json((&template).into()))
+    }
+
+    /// Replace a template along with all its items.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        put,
+        path = "/{id}",
+        tag = "template",
+        params(("id" = Uuid, Path, description = "Id of the template")),
+        request_body = dto::TemplateRequest,
+        responses(
+            (status = 200, body = dto::Template),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn update_template(
+        State(repository): State<Arc<TemplateRepository>>,
+        Path(template_id): Path<Uuid>,
+        claims: Authorized<BudgetWrite>,
+        ValidatedJson(payload): ValidatedJson<dto::TemplateRequest>,
+    ) -> Result<Json<dto::Template>, ApiError> {
+        tracing::info!(
+            "Updating template {template_id} for user '{}'",
+            claims.user_id()
+        );
+
+        repository
+            .update_template(claims.user_id(), &template_id, &payload)
+            .await?;
+        let template = repository
+            .get_template(claims.user_id(), &template_id)
+            .await?;
+
+        Ok(Json((&template).into()))
+    }
+
+    /// Delete a template. Budgets created from it are kept.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        delete,
+        path = "/{id}",
+        tag = "template",
+        params(("id" = Uuid, Path, description = "Id of the template")),
+        responses(
+            (status = 204, description = "The template was deleted"),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn delete_template(
+        State(repository): State<Arc<TemplateRepository>>,
+        Path(template_id): Path<Uuid>,
+        claims: Authorized<BudgetWrite>,
+    ) -> Result<StatusCode, ApiError> {
+        tracing::info!(
+            "Deleting template {template_id} for user '{}'",
+            claims.user_id()
+        );
+
+        repository
+            .delete_template(claims.user_id(), &template_id)
+            .await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Create a new budget with the currency and items of a template.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        post,
+        path = "/{id}/budget",
+        tag = "template",
+        params(("id" = Uuid, Path, description = "Id of the template")),
+        request_body = dto::UseTemplate,
+        responses(
+            (status = 201, description = "The new budget, or only its id with `Accept: text/plain`", content((Budget = "application/json"), (String = "text/plain")), headers(("Location" = String, description = "Path of the new budget"))),
+            (status = 400, response = ProblemDetails),
+            (status = 404, response = ProblemDetails),
+        ),
+        security(("bearer" = ["budget:write"])),
+    )]
+    pub async fn create_budget_from_template(
+        State(repository): State<Arc<TemplateRepository>>,
+        State(budget_repository): State<Arc<BudgetRepository>>,
+        Path(template_id): Path<Uuid>,
+        claims: Authorized<BudgetWrite>,
+        accept: Accept,
+        OptionalJson(payload): OptionalJson<dto::UseTemplate>,
+    ) -> Result<Response, ApiError> {
+        tracing::info!(
+            "Creating budget from template {template_id} for user '{}'",
+            claims.user_id()
+        );
+
+        let request = payload.unwrap_or_default();
+        let id = repository
+            .create_budget(claims.user_id(), &template_id, &request)
+            .await?;
+        let budget = budget_repository.get_budget(claims.user_id(), &id).await?;
+
+        Ok(created(
+            accept,
+            format!("/budget/{id}"),
+            id,
+            Budget::from(&budget),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::auth::test_utils::TestIdentityProvider;
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use sqlx::postgres::PgPoolOptions;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    const TEMPLATE_ID: &str = "9a3c5e7f-1b2d-4e6f-8a0c-2d4f6b8d0e01";
+
+    /// Send a request to `uri` with a token that has all scopes except
+    /// `required`, and check that it is rejected.
+    async fn assert_requires_scope(method: Method, uri: &str, required: &str) {
+        let identity_provider = TestIdentityProvider::spawn().await;
+        // Requests rejected because of missing scopes never reach the database.
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let router = create_router(AppState::new(
+            Arc::new(pool),
+            Arc::new(identity_provider.jwks_repository().await),
+        ));
+        let scope = ["budget:read", "budget:write"]
+            .into_iter()
+            .filter(|s| *s != required)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let token = identity_provider.token("Alice").scope(&scope).sign();
+        let request = Request::builder()
+            .method(method.clone())
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"name":"Name"}"#))
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{method} {uri}");
+    }
+
+    #[tokio::test]
+    async fn reading_templates_requires_budget_read() {
+        assert_requires_scope(Method::GET, "/", "budget:read").await;
+        assert_requires_scope(Method::GET, &format!("/{TEMPLATE_ID}"), "budget:read").await;
+    }
+
+    #[tokio::test]
+    async fn changing_templates_requires_budget_write() {
+        assert_requires_scope(Method::POST, "/", "budget:write").await;
+        assert_requires_scope(Method::PUT, &format!("/{TEMPLATE_ID}"), "budget:write").await;
+        assert_requires_scope(Method::DELETE, &format!("/{TEMPLATE_ID}"), "budget:write").await;
+    }
+
+    #[tokio::test]
+    async fn create_budget_from_template_requires_budget_write() {
+        assert_requires_scope(
+            Method::POST,
+            &format!("/{TEMPLATE_ID}/budget"),
+            "budget:write",
+        )
+        .await;
+    }
+}
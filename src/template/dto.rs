@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::model;
+use crate::{
+    budget::{dto::period_order, period::Period},
+    money::{Currency, Money},
+    validation::{self, MAX_AMOUNT, MAX_NAME_LENGTH, MIN_AMOUNT},
+};
+
+/// Most items a template can have.
+const MAX_TEMPLATE_ITEMS: u64 = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Template {
+    pub id: Uuid,
+    pub name: String,
+    /// Currency of the budgets created from the template.
+    pub currency: Currency,
+    pub items: Vec<TemplateItem>,
+    pub created_at: DateTime<Utc>,
+    pub modified_at: DateTime<Utc>,
+}
+
+impl From<&model::Template> for Template {
+    fn from(from: &model::Template) -> Self {
+        Self {
+            id: from.id,
+            name: from.name.to_owned(),
+            currency: from.currency,
+            items: from
+                .items
+                .iter()
+                .map(|item| TemplateItem {
+                    id: item.id,
+                    category_id: item.category_id,
+                    category: item.category.to_owned(),
+                    name: item.name.to_owned(),
+                    amount: Money::new(item.amount, item.currency.unwrap_or(from.currency)),
+                })
+                .collect(),
+            created_at: DateTime::from_naive_utc_and_offset(from.created_at, Utc),
+            modified_at: DateTime::from_naive_utc_and_offset(from.modified_at, Utc),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TemplateItem {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    /// Name of the category.
+    pub category: String,
+    pub name: String,
+    pub amount: Money,
+}
+
+/// Used both to create a template, and to replace it along with all its items.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct TemplateRequest {
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub name: String,
+    /// Currency of the budgets created from the template. Defaults to `DKK`.
+    #[serde(default)]
+    pub currency: Currency,
+    /// Items added to every budget created from the template, in this order.
+    #[serde(default)]
+    #[validate(length(max = MAX_TEMPLATE_ITEMS), nested)]
+    #[schema(max_items = 500)]
+    pub items: Vec<TemplateItemRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct TemplateItemRequest {
+    /// Id of one of the user's categories.
+    pub category_id: Uuid,
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub name: String,
+    /// Amount in the minor unit of the currency, e.g. cents for `EUR`.
+    #[validate(range(min = MIN_AMOUNT, max = MAX_AMOUNT))]
+    #[schema(minimum = -1_000_000_000_000i64, maximum = 1_000_000_000_000i64)]
+    pub amount: i64,
+    /// Currency of the amount. Defaults to the currency of the template.
+    #[serde(default)]
+    pub currency: Option<Currency>,
+}
+
+/// Options for creating a budget from a template.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct UseTemplate {
+    /// Title of the new budget. Defaults to the name of the template.
+    #[serde(default)]
+    #[validate(length(max = MAX_NAME_LENGTH), custom(function = "validation::name"))]
+    #[schema(max_length = 100)]
+    pub title: Option<String>,
+    /// Dates covered by the new budget, if it is for a specific period.
+    #[serde(default)]
+    #[validate(custom(function = "period_order"))]
+    pub period: Option<Period>,
+}
@@ -0,0 +1,20 @@
+INSERT INTO category (id, user_id, name, kind)
+VALUES
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Alice', 'Income', 'income'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Alice', 'Home', 'expense'),
+    ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04', 'Bob', 'Food', 'expense')
+;
+
+INSERT INTO template (id, user_id, name, currency)
+VALUES
+    ('9a3c5e7f-1b2d-4e6f-8a0c-2d4f6b8d0e01', 'Alice', 'Monthly', 'DKK'),
+    ('9a3c5e7f-1b2d-4e6f-8a0c-2d4f6b8d0e02', 'Bob', 'Groceries', 'EUR')
+;
+
+INSERT INTO template_item (template_id, position, category_id, name, amount, currency)
+VALUES
+    ('9a3c5e7f-1b2d-4e6f-8a0c-2d4f6b8d0e01', 0, '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01', 'Paycheck', 2000000, NULL),
+    ('9a3c5e7f-1b2d-4e6f-8a0c-2d4f6b8d0e01', 1, '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Rent', -800000, NULL),
+    ('9a3c5e7f-1b2d-4e6f-8a0c-2d4f6b8d0e01', 2, '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02', 'Holiday home', -50000, 'EUR'),
+    ('9a3c5e7f-1b2d-4e6f-8a0c-2d4f6b8d0e02', 0, '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04', 'Supermarket', -30000, NULL)
+;
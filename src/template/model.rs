@@ -0,0 +1,29 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::money::Currency;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub id: Uuid,
+    pub user_id: String,
+    pub name: String,
+    pub currency: Currency,
+    pub created_at: NaiveDateTime,
+    pub modified_at: NaiveDateTime,
+    /// The items of the template, in the order they were given.
+    pub items: Vec<TemplateItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateItem {
+    pub id: Uuid,
+    pub template_id: Uuid,
+    pub category_id: Uuid,
+    /// Name of the category.
+    pub category: String,
+    pub name: String,
+    pub amount: i64,
+    /// Currency of the amount, or `None` for the currency of the budget.
+    pub currency: Option<Currency>,
+}
@@ -0,0 +1,510 @@
+use std::{collections::HashMap, sync::Arc};
+
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use super::{dto, model};
+use crate::{
+    audit,
+    error::{ApiError, DatabaseError},
+    money::Currency,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TemplateRepositoryError {
+    Database,
+    NotFound,
+    Conflict(String),
+    Validation(String),
+}
+
+impl From<sqlx::Error> for TemplateRepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        match DatabaseError::from(error) {
+            DatabaseError::NotFound => TemplateRepositoryError::NotFound,
+            DatabaseError::Conflict(message) => TemplateRepositoryError::Conflict(message),
+            DatabaseError::Validation(message) => TemplateRepositoryError::Validation(message),
+            DatabaseError::Other => TemplateRepositoryError::Database,
+        }
+    }
+}
+
+impl From<TemplateRepositoryError> for ApiError {
+    fn from(error: TemplateRepositoryError) -> Self {
+        match error {
+            TemplateRepositoryError::Database => ApiError::Internal,
+            TemplateRepositoryError::NotFound => {
+                ApiError::NotFound("Template not found".to_string())
+            }
+            TemplateRepositoryError::Conflict(message) => ApiError::Conflict(message),
+            TemplateRepositoryError::Validation(message) => ApiError::BadRequest(message),
+        }
+    }
+}
+
+/// Repository to access the budget templates of a user.
+#[derive(Debug)]
+pub struct TemplateRepository {
+    db_pool: Arc<PgPool>,
+}
+
+impl TemplateRepository {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Create a new template with its items for the user, returning its id.
+    pub async fn create_template(
+        &self,
+        user_id: &str,
+        request: &dto::TemplateRequest,
+    ) -> Result<Uuid, TemplateRepositoryError> {
+        let mut transaction = self.db_pool.begin().await?;
+        check_categories(&mut transaction, user_id, &request.items).await?;
+        let id = sqlx::query_scalar!(
+            "INSERT INTO template (user_id, name, currency) VALUES ($1, $2, $3) RETURNING id",
+            user_id,
+            request.name,
+            request.currency.code()
+        )
+        .fetch_one(&mut transaction)
+        .await?;
+        insert_items(&mut transaction, &id, &request.items).await?;
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    /// Get all the templates of the user, sorted by name.
+    pub async fn get_all_templates(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<model::Template>, TemplateRepositoryError> {
+        self.get_templates(user_id, None).await
+    }
+
+    /// Get a template of the user from its id.
+    pub async fn get_template(
+        &self,
+        user_id: &str,
+        template_id: &Uuid,
+    ) -> Result<model::Template, TemplateRepositoryError> {
+        self.get_templates(user_id, Some(template_id))
+            .await?
+            .pop()
+            .ok_or(TemplateRepositoryError::NotFound)
+    }
+
+    /// Replace the name, currency, and all the items of a template.
+    pub async fn update_template(
+        &self,
+        user_id: &str,
+        template_id: &Uuid,
+        request: &dto::TemplateRequest,
+    ) -> Result<(), TemplateRepositoryError> {
+        let mut transaction = self.db_pool.begin().await?;
+        sqlx::query_scalar!(
+            "SELECT id FROM template WHERE id = $1 AND user_id = $2 FOR UPDATE",
+            template_id,
+            user_id
+        )
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or(TemplateRepositoryError::NotFound)?;
+        check_categories(&mut transaction, user_id, &request.items).await?;
+
+        sqlx::query!(
+            "UPDATE template SET name = $1, currency = $2 WHERE id = $3",
+            request.name,
+            request.currency.code(),
+            template_id
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM template_item WHERE template_id = $1",
+            template_id
+        )
+        .execute(&mut transaction)
+        .await?;
+        insert_items(&mut transaction, template_id, &request.items).await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Delete a template and its items. Budgets created from it are kept.
+    pub async fn delete_template(
+        &self,
+        user_id: &str,
+        template_id: &Uuid,
+    ) -> Result<(), TemplateRepositoryError> {
+        let query = sqlx::query!(
+            "DELETE FROM template WHERE id = $1 AND user_id = $2",
+            template_id,
+            user_id
+        );
+
+        match query.execute(self.db_pool.as_ref()).await?.rows_affected() {
+            0 => Err(TemplateRepositoryError::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Create a budget for the user with the currency and a copy of the items
+    /// of a template, returning the id of the new budget.
+    pub async fn create_budget(
+        &self,
+        user_id: &str,
+        template_id: &Uuid,
+        request: &dto::UseTemplate,
+    ) -> Result<Uuid, TemplateRepositoryError> {
+        if let Some(period) = &request.period {
+            period
+                .check()
+                .map_err(TemplateRepositoryError::Validation)?;
+        }
+
+        let mut transaction = audit::begin(&self.db_pool, user_id).await?;
+        let budget_id = sqlx::query_scalar!(
+            r#"INSERT INTO budget (user_id, title, currency, period_start, period_end, recurrence)
+SELECT user_id, COALESCE($3, name), currency, $4, $5, $6 FROM template
+WHERE id = $1 AND user_id = $2
+RETURNING id"#,
+            template_id,
+            user_id,
+            request.title,
+            request.period.map(|p| p.start),
+            request.period.map(|p| p.end),
+            request
+                .period
+                .and_then(|p| p.recurrence)
+                .map(|r| r.as_str())
+        )
+        .fetch_optional(&mut transaction)
+        .await?
+        .ok_or(TemplateRepositoryError::NotFound)?;
+
+        // Items without a currency get the currency of the budget when inserted.
+        sqlx::query!(
+            r#"INSERT INTO item (budget_id, category_id, name, amount, currency)
+SELECT $2, category_id, name, amount, currency FROM template_item
+WHERE template_id = $1
+ORDER BY position"#,
+            template_id,
+            budget_id
+        )
+        .execute(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(budget_id)
+    }
+
+    /// Get the templates of the user with their items, or only the one with `template_id`.
+    async fn get_templates(
+        &self,
+        user_id: &str,
+        template_id: Option<&Uuid>,
+    ) -> Result<Vec<model::Template>, TemplateRepositoryError> {
+        let templates = sqlx::query!(
+            r#"SELECT id, user_id, name, currency as "currency: Currency", created_at, modified_at
+FROM template
+WHERE user_id = $1 AND ($2::uuid IS NULL OR id = $2)
+ORDER BY lower(name), id"#,
+            user_id,
+            template_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let ids: Vec<_> = templates.iter().map(|t| t.id).collect();
+        let mut items = HashMap::<Uuid, Vec<model::TemplateItem>>::new();
+        for item in sqlx::query_as!(
+            model::TemplateItem,
+            r#"SELECT i.id, i.template_id, i.category_id, c.name as category, i.name, i.amount,
+    i.currency as "currency: Currency"
+FROM template_item AS i
+JOIN category AS c ON c.id = i.category_id
+WHERE i.template_id = ANY($1)
+ORDER BY i.template_id, i.position"#,
+            &ids
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?
+        {
+            items.entry(item.template_id).or_default().push(item);
+        }
+
+        Ok(templates
+            .into_iter()
+            .map(|t| model::Template {
+                items: items.remove(&t.id).unwrap_or_default(),
+                id: t.id,
+                user_id: t.user_id,
+                name: t.name,
+                currency: t.currency,
+                created_at: t.created_at,
+                modified_at: t.modified_at,
+            })
+            .collect())
+    }
+}
+
+/// Check that all the items are in categories of the user.
+async fn check_categories(
+    transaction: &mut Transaction<'static, Postgres>,
+    user_id: &str,
+    items: &[dto::TemplateItemRequest],
+) -> Result<(), TemplateRepositoryError> {
+    let ids: Vec<_> = items.iter().map(|item| item.category_id).collect();
+    let unknown = sqlx::query_scalar!(
+        r#"SELECT id as "id!" FROM unnest($1::uuid[]) AS id
+WHERE id NOT IN (SELECT id FROM category WHERE user_id = $2)
+LIMIT 1"#,
+        &ids,
+        user_id
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    match unknown {
+        None => Ok(()),
+        Some(category_id) => Err(TemplateRepositoryError::Validation(format!(
+            "Unknown category '{category_id}'"
+        ))),
+    }
+}
+
+/// Insert the items of a template, keeping their order.
+async fn insert_items(
+    transaction: &mut Transaction<'static, Postgres>,
+    template_id: &Uuid,
+    items: &[dto::TemplateItemRequest],
+) -> Result<(), TemplateRepositoryError> {
+    for (position, item) in items.iter().enumerate() {
+        sqlx::query!(
+            r#"INSERT INTO template_item (template_id, position, category_id, name, amount, currency)
+VALUES ($1, $2, $3, $4, $5, $6)"#,
+            template_id,
+            position as i32,
+            item.category_id,
+            item.name,
+            item.amount,
+            item.currency.as_ref().map(Currency::code)
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        budget::{period::Period, repository::BudgetRepository},
+        category::repository::{CategoryRepository, CategoryRepositoryError},
+    };
+
+    const MONTHLY: &str = "9a3c5e7f-1b2d-4e6f-8a0c-2d4f6b8d0e01";
+    const INCOME: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a01";
+    const HOME: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a02";
+    const BOBS_FOOD: &str = "3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a04";
+
+    fn id(id: &str) -> Uuid {
+        Uuid::parse_str(id).unwrap()
+    }
+
+    fn item(category_id: &str, name: &str, amount: i64) -> dto::TemplateItemRequest {
+        dto::TemplateItemRequest {
+            category_id: id(category_id),
+            name: name.to_string(),
+            amount,
+            currency: None,
+        }
+    }
+
+    #[sqlx::test(fixtures("templates"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn create_and_get_template_with_items_in_order(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TemplateRepository::new(Arc::new(pool));
+        let request = dto::TemplateRequest {
+            name: "Apartment".to_string(),
+            currency: "EUR".parse().unwrap(),
+            items: vec![item(HOME, "Rent", -90000), item(INCOME, "Salary", 300000)],
+        };
+
+        let template_id = repo.create_template("Alice", &request).await.unwrap();
+        let template = repo.get_template("Alice", &template_id).await.unwrap();
+        let all = repo.get_all_templates("Alice").await.unwrap();
+
+        assert_eq!(template.name, "Apartment");
+        assert_eq!(template.currency.code(), "EUR");
+        let items: Vec<_> = template
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.category.as_str(), i.amount))
+            .collect();
+        assert_eq!(
+            items,
+            [("Rent", "Home", -90000), ("Salary", "Income", 300000)]
+        );
+        let names: Vec<_> = all.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["Apartment", "Monthly"]);
+        assert_eq!(all[1].items.len(), 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("templates"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn templates_only_use_categories_of_the_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TemplateRepository::new(Arc::new(pool));
+        let request = dto::TemplateRequest {
+            name: "Groceries".to_string(),
+            currency: Currency::DKK,
+            items: vec![item(HOME, "Rent", -1), item(BOBS_FOOD, "Supermarket", -1)],
+        };
+
+        let created = repo.create_template("Alice", &request).await;
+        let updated = repo.update_template("Alice", &id(MONTHLY), &request).await;
+
+        assert_eq!(
+            created,
+            Err(TemplateRepositoryError::Validation(format!(
+                "Unknown category '{BOBS_FOOD}'"
+            )))
+        );
+        assert!(matches!(
+            updated,
+            Err(TemplateRepositoryError::Validation(_))
+        ));
+        let template = repo.get_template("Alice", &id(MONTHLY)).await.unwrap();
+        assert_eq!(template.items.len(), 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("templates"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn update_replaces_all_items(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TemplateRepository::new(Arc::new(pool));
+        let request = dto::TemplateRequest {
+            name: "Yearly".to_string(),
+            currency: Currency::DKK,
+            items: vec![item(HOME, "Insurance", -120000)],
+        };
+
+        repo.update_template("Alice", &id(MONTHLY), &request)
+            .await
+            .unwrap();
+        let other_user = repo.update_template("Bob", &id(MONTHLY), &request).await;
+
+        let template = repo.get_template("Alice", &id(MONTHLY)).await.unwrap();
+        assert_eq!(template.name, "Yearly");
+        let names: Vec<_> = template.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["Insurance"]);
+        assert_eq!(other_user, Err(TemplateRepositoryError::NotFound));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("templates"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn delete_template_of_the_user(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TemplateRepository::new(Arc::new(pool));
+
+        let other_user = repo.delete_template("Bob", &id(MONTHLY)).await;
+        repo.delete_template("Alice", &id(MONTHLY)).await.unwrap();
+        let again = repo.delete_template("Alice", &id(MONTHLY)).await;
+
+        assert_eq!(other_user, Err(TemplateRepositoryError::NotFound));
+        assert_eq!(again, Err(TemplateRepositoryError::NotFound));
+        assert_eq!(
+            repo.get_template("Alice", &id(MONTHLY)).await,
+            Err(TemplateRepositoryError::NotFound)
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("templates"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn create_budget_from_template(pool: PgPool) -> sqlx::Result<()> {
+        let pool = Arc::new(pool);
+        let repo = TemplateRepository::new(pool.clone());
+        let budgets = BudgetRepository::new(pool);
+        let period = Period {
+            start: "2026-11-01".parse().unwrap(),
+            end: "2026-11-30".parse().unwrap(),
+            recurrence: None,
+        };
+
+        let named = repo
+            .create_budget("Alice", &id(MONTHLY), &dto::UseTemplate::default())
+            .await
+            .unwrap();
+        let november = repo
+            .create_budget(
+                "Alice",
+                &id(MONTHLY),
+                &dto::UseTemplate {
+                    title: Some("November".to_string()),
+                    period: Some(period),
+                },
+            )
+            .await
+            .unwrap();
+        let other_user = repo
+            .create_budget("Bob", &id(MONTHLY), &dto::UseTemplate::default())
+            .await;
+
+        let named = budgets.get_budget("Alice", &named).await.unwrap();
+        assert_eq!(named.title, "Monthly");
+        assert_eq!(named.currency, Currency::DKK);
+        let mut items: Vec<_> = named
+            .items
+            .iter()
+            .map(|i| (i.name.as_str(), i.amount, i.currency.code()))
+            .collect();
+        items.sort();
+        assert_eq!(
+            items,
+            [
+                ("Holiday home", -50000, "EUR"),
+                ("Paycheck", 2000000, "DKK"),
+                ("Rent", -800000, "DKK")
+            ]
+        );
+        let november = budgets.get_budget("Alice", &november).await.unwrap();
+        assert_eq!(november.title, "November");
+        assert_eq!(november.period(), Some(period));
+        assert_eq!(november.items.len(), 3);
+        assert_eq!(other_user, Err(TemplateRepositoryError::NotFound));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("templates"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn categories_used_by_templates_are_merged_not_deleted(pool: PgPool) -> sqlx::Result<()> {
+        let pool = Arc::new(pool);
+        let repo = TemplateRepository::new(pool.clone());
+        let categories = CategoryRepository::new(pool);
+
+        let deleted = categories.delete_category("Alice", &id(HOME)).await;
+        categories
+            .merge_categories("Alice", &id(HOME), &id(INCOME))
+            .await
+            .unwrap();
+
+        assert!(matches!(deleted, Err(CategoryRepositoryError::Conflict(_))));
+        let template = repo.get_template("Alice", &id(MONTHLY)).await.unwrap();
+        assert!(template.items.iter().all(|i| i.category == "Income"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+//! Storing enums and other values with a string representation in `TEXT` columns.
+
+/// Implement the sqlx traits to store a type as text in Postgres. The type is
+/// encoded with its `as_str` method, or the given method, and decoded with
+/// [`std::str::FromStr`].
+macro_rules! text_type {
+    ($type:ty) => {
+        $crate::text_type::text_type!($type, as_str);
+    };
+    ($type:ty, $to_str:ident) => {
+        impl sqlx::Type<sqlx::Postgres> for $type {
+            fn type_info() -> sqlx::postgres::PgTypeInfo {
+                <&str as sqlx::Type<sqlx::Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+                <&str as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+            }
+        }
+
+        impl<'r> sqlx::Decode<'r, sqlx::Postgres> for $type {
+            fn decode(
+                value: sqlx::postgres::PgValueRef<'r>,
+            ) -> Result<Self, sqlx::error::BoxDynError> {
+                Ok(<&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?.parse()?)
+            }
+        }
+
+        impl sqlx::Encode<'_, sqlx::Postgres> for $type {
+            fn encode_by_ref(
+                &self,
+                buf: &mut sqlx::postgres::PgArgumentBuffer,
+            ) -> sqlx::encode::IsNull {
+                <&str as sqlx::Encode<sqlx::Postgres>>::encode(self.$to_str(), buf)
+            }
+        }
+    };
+}
+
+pub(crate) use text_type;
@@ -0,0 +1,67 @@
+pub mod dto;
+mod model;
+pub(crate) mod repository;
+
+use crate::app_state::AppState;
+use axum::{routing::get, Router};
+use utoipa::OpenApi;
+
+/// Days that budgets and items stay in the trash before they are purged,
+/// unless `TRASH_RETENTION_DAYS` is set.
+const DEFAULT_RETENTION_DAYS: u32 = 30;
+
+/// Read the number of days to keep budgets and items in the trash from
+/// `TRASH_RETENTION_DAYS`. Defaults to 30 days.
+pub fn retention_days_from_env() -> u32 {
+    std::env::var("TRASH_RETENTION_DAYS")
+        .map(|days| {
+            days.parse()
+                .expect("variable 'TRASH_RETENTION_DAYS' to be a number of days")
+        })
+        .unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+/// Documentation of the routes in [`create_router`].
+#[derive(OpenApi)]
+#[openapi(
+    paths(endpoints::get_trash),
+    tags((name = "trash", description = "Deleted budgets and items, until they are purged")),
+)]
+pub struct TrashApi;
+
+pub fn create_router(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(endpoints::get_trash))
+        .with_state(state)
+}
+
+mod endpoints {
+    use super::{dto, repository::TrashRepository};
+    use crate::{
+        app_state::AppState,
+        auth::scope::{Authorized, BudgetRead},
+        error::ApiError,
+    };
+    use axum::{debug_handler, extract::State, Json};
+    use std::sync::Arc;
+
+    /// Get the deleted budgets and items that the user can restore.
+    #[debug_handler(state = AppState)]
+    #[utoipa::path(
+        get,
+        path = "",
+        tag = "trash",
+        responses((status = 200, body = dto::Trash)),
+        security(("bearer" = ["budget:read"])),
+    )]
+    pub async fn get_trash(
+        State(repository): State<Arc<TrashRepository>>,
+        claims: Authorized<BudgetRead>,
+    ) -> Result<Json<dto::Trash>, ApiError> {
+        tracing::info!("Get trash for user {}", claims.user_id());
+
+        let trash = repository.get_trash(claims.user_id()).await?;
+
+        Ok(Json((&trash).into()))
+    }
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::model;
+use crate::money::{Currency, Money};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrashedBudget {
+    pub id: Uuid,
+    pub title: String,
+    pub currency: Currency,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl From<&model::TrashedBudget> for TrashedBudget {
+    fn from(from: &model::TrashedBudget) -> Self {
+        Self {
+            id: from.id,
+            title: from.title.to_owned(),
+            currency: from.currency,
+            deleted_at: DateTime::from_naive_utc_and_offset(from.deleted_at, Utc),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TrashedItem {
+    pub id: Uuid,
+    pub budget_id: Uuid,
+    /// Title of the budget the item is restored to.
+    pub budget_title: String,
+    pub name: String,
+    pub amount: Money,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl From<&model::TrashedItem> for TrashedItem {
+    fn from(from: &model::TrashedItem) -> Self {
+        Self {
+            id: from.id,
+            budget_id: from.budget_id,
+            budget_title: from.budget_title.to_owned(),
+            name: from.name.to_owned(),
+            amount: Money::new(from.amount, from.currency),
+            deleted_at: DateTime::from_naive_utc_and_offset(from.deleted_at, Utc),
+        }
+    }
+}
+
+/// The budgets and items a user can restore, most recently deleted first.
+/// Budgets are only listed for their owners, and items for owners and editors.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Trash {
+    pub budgets: Vec<TrashedBudget>,
+    /// Items deleted from budgets that are not in the trash themselves.
+    pub items: Vec<TrashedItem>,
+}
+
+impl From<&model::Trash> for Trash {
+    fn from(from: &model::Trash) -> Self {
+        Self {
+            budgets: from.budgets.iter().map(|x| x.into()).collect(),
+            items: from.items.iter().map(|x| x.into()).collect(),
+        }
+    }
+}
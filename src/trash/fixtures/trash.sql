@@ -0,0 +1,24 @@
+-- A budget of Alice in the trash, and a budget with items in the trash that
+-- Carol can edit and Bob can view.
+INSERT INTO budget (id, user_id, title, deleted_at)
+VALUES
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Alice', 'Household', NULL),
+    ('0c5f3a4e-7d1b-4f0a-9c6e-2b8d1e4f5a01', 'Alice', 'Old holiday', current_timestamp - interval '40 days')
+;
+
+INSERT INTO budget_member (budget_id, user_id, role, invited_by, accepted_at)
+VALUES
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Bob', 'viewer', 'Alice', '2024-01-01 12:00:00'),
+    ('b8d6ff4e-c12f-416b-a611-8ad0c90669fe', 'Carol', 'editor', 'Alice', '2024-01-01 12:00:00')
+;
+
+INSERT INTO category (id, user_id, name, kind)
+VALUES ('3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Alice', 'Food', 'expense');
+
+INSERT INTO item (id, budget_id, category_id, name, amount, deleted_at)
+VALUES
+    ('5e666f18-de95-4513-abd8-1f09ed5ff98f', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Groceries', -300000, NULL),
+    ('c4af1e7a-4dfd-4338-ad31-caee4848a69b', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Bakery', -20000, current_timestamp - interval '1 day'),
+    ('d831821b-1b50-41fc-a01e-19a1243c334a', 'b8d6ff4e-c12f-416b-a611-8ad0c90669fe', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Restaurants', -50000, current_timestamp - interval '40 days'),
+    ('e2a9b7c1-3f4d-4e5a-8b6c-7d8e9f0a1b02', '0c5f3a4e-7d1b-4f0a-9c6e-2b8d1e4f5a01', '3f0e1b7a-58c2-4d8e-9a0b-6c1d2e3f4a03', 'Hotel', -400000, NULL)
+;
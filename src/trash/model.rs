@@ -0,0 +1,37 @@
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::money::Currency;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedBudget {
+    pub id: Uuid,
+    pub title: String,
+    pub currency: Currency,
+    pub deleted_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedItem {
+    pub id: Uuid,
+    pub budget_id: Uuid,
+    pub budget_title: String,
+    pub name: String,
+    pub amount: i64,
+    pub currency: Currency,
+    pub deleted_at: NaiveDateTime,
+}
+
+/// What a user can restore from the trash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trash {
+    pub budgets: Vec<TrashedBudget>,
+    pub items: Vec<TrashedItem>,
+}
+
+/// The number of budgets and items that were permanently deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Purged {
+    pub budgets: u64,
+    pub items: u64,
+}
@@ -0,0 +1,194 @@
+use std::{sync::Arc, time::Duration};
+
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+
+use super::model;
+use crate::{audit, error::ApiError, money::Currency};
+
+/// How often budgets and items past the retention period are purged.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The actor of purges in the audit log, as they are not made by a user.
+const PURGE_ACTOR: &str = "trash";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TrashRepositoryError {
+    Database,
+}
+
+impl From<sqlx::Error> for TrashRepositoryError {
+    fn from(error: sqlx::Error) -> Self {
+        tracing::error!("Database error: {error:?}");
+        TrashRepositoryError::Database
+    }
+}
+
+impl From<TrashRepositoryError> for ApiError {
+    fn from(error: TrashRepositoryError) -> Self {
+        match error {
+            TrashRepositoryError::Database => ApiError::Internal,
+        }
+    }
+}
+
+/// Repository to list and purge the budgets and items in the trash. They are
+/// moved to the trash and restored by the budget and item repositories.
+#[derive(Debug)]
+pub struct TrashRepository {
+    db_pool: Arc<PgPool>,
+}
+
+impl TrashRepository {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Get the budgets in the trash that the user owns, and the items in the
+    /// trash of the budgets the user can edit.
+    pub async fn get_trash(&self, user_id: &str) -> Result<model::Trash, TrashRepositoryError> {
+        let budgets = sqlx::query_as!(
+            model::TrashedBudget,
+            r#"SELECT id, title, currency as "currency: Currency", deleted_at as "deleted_at!"
+FROM budget
+WHERE id IN (SELECT budget_id FROM trashed_budget_access WHERE user_id = $1 AND role = 'owner')
+ORDER BY deleted_at DESC, id"#,
+            user_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        let items = sqlx::query_as!(
+            model::TrashedItem,
+            r#"SELECT i.id, i.budget_id, b.title as budget_title, i.name, i.amount,
+    i.currency as "currency: Currency", i.deleted_at as "deleted_at!"
+FROM item AS i
+JOIN budget AS b ON b.id = i.budget_id
+WHERE i.deleted_at IS NOT NULL
+    AND i.budget_id IN (SELECT budget_id FROM budget_access WHERE user_id = $1 AND role IN ('owner', 'editor'))
+ORDER BY i.deleted_at DESC, i.id"#,
+            user_id
+        )
+        .fetch_all(self.db_pool.as_ref())
+        .await?;
+
+        Ok(model::Trash { budgets, items })
+    }
+
+    /// Permanently delete the budgets and items that have been in the trash for
+    /// more than `retention_days`. The items and transactions of a purged
+    /// budget are deleted with it.
+    pub async fn purge(&self, retention_days: u32) -> Result<model::Purged, TrashRepositoryError> {
+        let retention_days = i32::try_from(retention_days).unwrap_or(i32::MAX);
+        let mut transaction = audit::begin(&self.db_pool, PURGE_ACTOR).await?;
+
+        let budgets = sqlx::query!(
+            "DELETE FROM budget WHERE deleted_at < current_timestamp - make_interval(days => $1)",
+            retention_days
+        )
+        .execute(&mut transaction)
+        .await?
+        .rows_affected();
+        let items = sqlx::query!(
+            "DELETE FROM item WHERE deleted_at < current_timestamp - make_interval(days => $1)",
+            retention_days
+        )
+        .execute(&mut transaction)
+        .await?
+        .rows_affected();
+
+        transaction.commit().await?;
+
+        Ok(model::Purged { budgets, items })
+    }
+
+    /// Purge the trash every hour in the background, starting now, for as long
+    /// as the repository is in use.
+    pub fn spawn_purge(self: &Arc<Self>, retention_days: u32) -> JoinHandle<()> {
+        let repository = Arc::downgrade(self);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PURGE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(repository) = repository.upgrade() else {
+                    break;
+                };
+
+                match repository.purge(retention_days).await {
+                    Ok(purged) => tracing::info!(
+                        "Purged {} budgets and {} items from the trash",
+                        purged.budgets,
+                        purged.items
+                    ),
+                    Err(err) => tracing::error!("Unable to purge the trash: {err:?}"),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use uuid::Uuid;
+
+    const HOUSEHOLD: &str = "b8d6ff4e-c12f-416b-a611-8ad0c90669fe";
+    const OLD_HOLIDAY: &str = "0c5f3a4e-7d1b-4f0a-9c6e-2b8d1e4f5a01";
+
+    fn id(id: &str) -> Uuid {
+        Uuid::parse_str(id).unwrap()
+    }
+
+    #[sqlx::test(fixtures("trash"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn trash_lists_what_the_user_can_restore(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TrashRepository::new(Arc::new(pool));
+
+        let owner = repo.get_trash("Alice").await.unwrap();
+        let editor = repo.get_trash("Carol").await.unwrap();
+        let viewer = repo.get_trash("Bob").await.unwrap();
+
+        assert_eq!(owner.budgets.len(), 1);
+        assert_eq!(owner.budgets[0].id, id(OLD_HOLIDAY));
+        let names: Vec<_> = owner.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["Bakery", "Restaurants"]);
+        assert!(owner.items.iter().all(|i| i.budget_id == id(HOUSEHOLD)));
+        assert!(editor.budgets.is_empty());
+        assert_eq!(editor.items.len(), 2);
+        assert!(viewer.budgets.is_empty());
+        assert!(viewer.items.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("trash"))]
+    #[cfg_attr(not(feature = "db_test"), ignore)]
+    async fn purge_deletes_what_is_past_the_retention(pool: PgPool) -> sqlx::Result<()> {
+        let repo = TrashRepository::new(Arc::new(pool.clone()));
+
+        let purged = repo.purge(30).await.unwrap();
+
+        assert_eq!(
+            purged,
+            model::Purged {
+                budgets: 1,
+                items: 1
+            }
+        );
+        let items = sqlx::query_scalar!("SELECT name FROM item ORDER BY name")
+            .fetch_all(&pool)
+            .await?;
+        assert_eq!(items, ["Bakery", "Groceries"]);
+        let events = sqlx::query!("SELECT entity, action, actor FROM audit_event ORDER BY id")
+            .fetch_all(&pool)
+            .await?;
+        assert!(!events.is_empty());
+        assert!(events
+            .iter()
+            .all(|event| event.action == "delete" && event.actor == PURGE_ACTOR));
+        assert_eq!(repo.purge(30).await.unwrap(), model::Purged::default());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,403 @@
+use crate::error::{ApiError, FieldError};
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::FromRequest,
+    http::{header, HeaderMap, Request, StatusCode},
+    BoxError,
+};
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+/// Most characters in a name or title.
+pub const MAX_NAME_LENGTH: u64 = 100;
+/// Most characters in free text, e.g. the memo of a transaction.
+pub const MAX_TEXT_LENGTH: u64 = 1000;
+/// Largest amount in the minor unit of a currency, i.e. 10 billion in the major unit.
+pub const MAX_AMOUNT: i64 = 1_000_000_000_000;
+/// Smallest amount in the minor unit of a currency.
+pub const MIN_AMOUNT: i64 = -MAX_AMOUNT;
+
+/// JSON body that has been deserialized, and validated with its [`Validate`] rules.
+///
+/// Rejects the request with problem details like the rest of the API:
+/// `415` without a JSON content type, `400` for malformed JSON, and `422` with
+/// the [`FieldError`]s when the body does not match `T` or is not valid.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        match json_body(request, state).await? {
+            Some(value) => Ok(ValidatedJson(value)),
+            None => Err(missing_json_content_type()),
+        }
+    }
+}
+
+/// Like [`ValidatedJson`], but the body can be left out entirely.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptionalJson<T>(pub Option<T>);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for OptionalJson<T>
+where
+    T: DeserializeOwned + Validate,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(OptionalJson(json_body(request, state).await?))
+    }
+}
+
+/// The validated body, or `None` if the request has neither a body nor a content type.
+async fn json_body<T, S, B>(request: Request<B>, state: &S) -> Result<Option<T>, ApiError>
+where
+    T: DeserializeOwned + Validate,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    let has_content_type = request.headers().contains_key(header::CONTENT_TYPE);
+    let json_content_type = is_json_content_type(request.headers());
+    let bytes = Bytes::from_request(request, state)
+        .await
+        .map_err(|rejection| match rejection.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => ApiError::PayloadTooLarge(rejection.body_text()),
+            _ => ApiError::BadRequest(rejection.body_text()),
+        })?;
+
+    if !has_content_type && bytes.is_empty() {
+        return Ok(None);
+    }
+    if !json_content_type {
+        return Err(missing_json_content_type());
+    }
+
+    let value = deserialize::<T>(&bytes)?;
+    value
+        .validate()
+        .map_err(|errors| ApiError::Validation(field_errors(&errors)))?;
+
+    Ok(Some(value))
+}
+
+fn missing_json_content_type() -> ApiError {
+    ApiError::UnsupportedMediaType(
+        "Expected a JSON body with the header 'Content-Type: application/json'".to_string(),
+    )
+}
+
+/// Either `application/json`, or a type with a `+json` suffix.
+fn is_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+/// Deserialize `bytes`, reporting values of the wrong type as errors of their field.
+fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ApiError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+        let path = error.path().to_string();
+        let error = error.into_inner();
+        match error.classify() {
+            Category::Data => ApiError::Validation(vec![data_error(&path, &error)]),
+            Category::Syntax | Category::Eof | Category::Io => {
+                ApiError::BadRequest(format!("Malformed JSON: {error}"))
+            }
+        }
+    })?;
+    deserializer
+        .end()
+        .map_err(|error| ApiError::BadRequest(format!("Malformed JSON: {error}")))?;
+
+    Ok(value)
+}
+
+fn data_error(path: &str, error: &serde_json::Error) -> FieldError {
+    let message = error.to_string();
+    // The position is already given by the path.
+    let message = message
+        .rsplit_once(" at line ")
+        .map_or(message.as_str(), |(message, _)| message);
+
+    // Missing fields are reported by the struct that contains them.
+    if let Some(name) = message
+        .strip_prefix("missing field `")
+        .and_then(|name| name.strip_suffix('`'))
+    {
+        return FieldError {
+            field: match path {
+                "." => name.to_string(),
+                path => format!("{path}.{name}"),
+            },
+            code: "required".to_string(),
+            message: "Is required".to_string(),
+        };
+    }
+
+    let mut chars = message.chars();
+    FieldError {
+        field: path.to_string(),
+        code: "invalid".to_string(),
+        message: chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// Flatten the errors of a struct and its nested fields, ordered by field.
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    fn collect(errors: &ValidationErrors, prefix: &str, fields: &mut Vec<FieldError>) {
+        for (field, kind) in errors.errors() {
+            let path = format!("{prefix}{field}");
+            match kind {
+                ValidationErrorsKind::Field(errors) => {
+                    fields.extend(errors.iter().map(|error| FieldError {
+                        field: path.clone(),
+                        code: error.code.to_string(),
+                        message: message(error),
+                    }))
+                }
+                ValidationErrorsKind::Struct(errors) => {
+                    collect(errors, &format!("{path}."), fields)
+                }
+                ValidationErrorsKind::List(list) => {
+                    for (index, errors) in list {
+                        collect(errors, &format!("{path}[{index}]."), fields)
+                    }
+                }
+            }
+        }
+    }
+
+    let mut fields = vec![];
+    collect(errors, "", &mut fields);
+    fields.sort_by(|a, b| a.field.cmp(&b.field));
+
+    fields
+}
+
+/// Message of an error, written from its parameters for the built-in validators.
+fn message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    let param = |name: &str| error.params.get(name).map(|value| value.to_string());
+
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("length", None, Some(max)) => format!("Must be at most {max} characters"),
+        ("length", Some(min), None) => format!("Must be at least {min} characters"),
+        ("length", Some(min), Some(max)) => format!("Must be from {min} to {max} characters"),
+        ("range", Some(min), Some(max)) => format!("Must be from {min} to {max}"),
+        (code, _, _) => format!("Is not valid ({code})"),
+    }
+}
+
+/// A name or title: not blank, and on a single line.
+pub fn name(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError::new("blank").with_message("Must not be blank".into()));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(ValidationError::new("control_characters")
+            .with_message("Must be a single line without control characters".into()));
+    }
+
+    Ok(())
+}
+
+/// Free text, which can have line breaks and tabs, but no other control characters.
+pub fn text(value: &str) -> Result<(), ValidationError> {
+    if value
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+    {
+        return Err(ValidationError::new("control_characters")
+            .with_message("Must not contain control characters".into()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::{body::Body, http::Method};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Payload {
+        #[validate(length(max = 5), custom(function = "name"))]
+        title: String,
+        #[validate(range(min = 0, max = 10))]
+        amount: i64,
+        #[serde(default)]
+        #[validate(custom(function = "text"))]
+        memo: Option<String>,
+    }
+
+    async fn extract(
+        content_type: Option<&str>,
+        body: &'static str,
+    ) -> Result<Option<Payload>, ApiError> {
+        let mut request = axum::http::Request::builder().method(Method::POST);
+        if let Some(content_type) = content_type {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+        let request = request.body(Body::from(body)).unwrap();
+
+        OptionalJson::<Payload>::from_request(request, &())
+            .await
+            .map(|json| json.0)
+    }
+
+    fn field(field: &str, code: &str, message: &str) -> FieldError {
+        FieldError {
+            field: field.to_string(),
+            code: code.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn valid_body_is_extracted() {
+        let body = r#"{"title": "Food", "amount": 10, "memo": "Weekly\nshopping"}"#;
+
+        let request = extract(Some("application/json"), body).await.unwrap();
+
+        assert_eq!(request.unwrap().title, "Food");
+    }
+
+    #[tokio::test]
+    async fn invalid_fields_are_listed_in_order() {
+        let body = r#"{"title": " ", "amount": 11, "memo": "\u0000"}"#;
+
+        let result = extract(Some("application/json"), body).await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            ApiError::Validation(vec![
+                field("amount", "range", "Must be from 0 to 10"),
+                field(
+                    "memo",
+                    "control_characters",
+                    "Must not contain control characters"
+                ),
+                field("title", "blank", "Must not be blank"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn long_names_and_line_breaks_are_rejected() {
+        let body = r#"{"title": "Groceries\n", "amount": 0}"#;
+
+        let result = extract(Some("application/json"), body).await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            ApiError::Validation(vec![
+                field("title", "length", "Must be at most 5 characters"),
+                field(
+                    "title",
+                    "control_characters",
+                    "Must be a single line without control characters"
+                ),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_types_and_missing_fields_are_field_errors() {
+        let wrong_type = extract(Some("application/json"), r#"{"title": 1, "amount": 0}"#).await;
+        let missing = extract(Some("application/json"), r#"{"title": "Food"}"#).await;
+
+        assert_eq!(
+            wrong_type.unwrap_err(),
+            ApiError::Validation(vec![field(
+                "title",
+                "invalid",
+                "Invalid type: integer `1`, expected a string"
+            )])
+        );
+        assert_eq!(
+            missing.unwrap_err(),
+            ApiError::Validation(vec![field("amount", "required", "Is required")])
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_json_is_a_bad_request() {
+        for body in [r#"{"title": "#, r#"{"title": "Food", "amount": 0} {}"#] {
+            let result = extract(Some("application/json"), body).await;
+
+            assert!(
+                matches!(result, Err(ApiError::BadRequest(ref detail)) if detail.starts_with("Malformed JSON")),
+                "{body}: {result:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn body_must_be_json() {
+        let body = r#"{"title": "Food", "amount": 0}"#;
+
+        assert!(extract(Some("application/problem+json"), body)
+            .await
+            .is_ok());
+        assert!(extract(Some("application/json; charset=utf-8"), body)
+            .await
+            .is_ok());
+        assert!(matches!(
+            extract(Some("text/plain"), body).await,
+            Err(ApiError::UnsupportedMediaType(_))
+        ));
+        assert!(matches!(
+            extract(None, body).await,
+            Err(ApiError::UnsupportedMediaType(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn body_can_be_left_out_only_when_optional() {
+        let optional = extract(None, "").await;
+        let required =
+            ValidatedJson::<Payload>::from_request(axum::http::Request::new(Body::empty()), &())
+                .await;
+
+        assert!(matches!(optional, Ok(None)));
+        assert!(matches!(required, Err(ApiError::UnsupportedMediaType(_))));
+    }
+}
@@ -0,0 +1,58 @@
+use hyper::StatusCode;
+
+mod common;
+
+#[tokio::test]
+async fn request_without_token_is_unauthorized() {
+    let app = common::spawn_app().await.expect("Failed to spawn our app.");
+
+    let response = app.anonymous_client().get_all_budgets().await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn invalid_tokens_are_unauthorized() {
+    let app = common::spawn_app().await.expect("Failed to spawn our app.");
+    let identity_provider = app.identity_provider();
+
+    let tokens = [
+        ("expired", identity_provider.token("Alice").expired()),
+        (
+            "wrong audience",
+            identity_provider
+                .token("Alice")
+                .audience("https://other.example.com/"),
+        ),
+        (
+            "wrong issuer",
+            identity_provider
+                .token("Alice")
+                .issuer("https://evil.example.com/"),
+        ),
+        (
+            "bad signature",
+            identity_provider.token("Alice").untrusted_signature(),
+        ),
+    ];
+
+    for (case, token) in tokens {
+        let response = app.client_with_token(token.sign()).get_all_budgets().await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{case}");
+    }
+}
+
+#[tokio::test]
+async fn token_without_required_scope_is_forbidden() {
+    let app = common::spawn_app().await.expect("Failed to spawn our app.");
+    let token = app
+        .identity_provider()
+        .token("Alice")
+        .scope("budget:write")
+        .sign();
+
+    let response = app.client_with_token(token).get_all_budgets().await;
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
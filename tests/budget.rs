@@ -0,0 +1,1164 @@
+use budget_api::{
+    audit::{dto::AuditEvent, Action, Entity},
+    budget::{
+        dto::{
+            AddItemToBudgetRequest, Budget, BudgetWithItems, CreateBudget, DuplicateBudget, Item,
+            NextPeriod, PatchBudget, PatchItem, RevertBudget, UpdateBudget,
+        },
+        period::{Period, Recurrence},
+        schedule::Schedule,
+    },
+    category::dto::CategoryRequest,
+    error::ProblemDetails,
+    money::Currency,
+    pagination::Page,
+    trash::dto::Trash,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+
+use common::{ApiClient, TestApp};
+
+async fn create_budget(client: &ApiClient, title: &str) -> Uuid {
+    let response = client
+        .create_budget(&CreateBudget {
+            title: title.to_string(),
+            currency: Currency::default(),
+            period: None,
+        })
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    response.json::<Budget>().await.unwrap().id
+}
+
+async fn create_category(client: &ApiClient, name: &str) -> Uuid {
+    let response = client
+        .create_category(&CategoryRequest {
+            name: name.to_string(),
+            color: None,
+            icon: None,
+            kind: Default::default(),
+            parent_id: None,
+        })
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response.text().await.unwrap().parse().unwrap()
+}
+
+async fn add_item(
+    client: &ApiClient,
+    budget_id: Uuid,
+    category_id: Uuid,
+    name: &str,
+    amount: i64,
+) -> Uuid {
+    let response = client
+        .add_item(budget_id, &item(category_id, name, amount))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    response.json::<Item>().await.unwrap().id
+}
+
+async fn get_budget(client: &ApiClient, budget_id: Uuid) -> BudgetWithItems {
+    let response = client.get_budget(budget_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response.json().await.unwrap()
+}
+
+fn item(category_id: Uuid, name: &str, amount: i64) -> AddItemToBudgetRequest {
+    AddItemToBudgetRequest {
+        category_id,
+        name: name.to_string(),
+        amount,
+        currency: None,
+        schedule: None,
+    }
+}
+
+async fn spawn_app(pool: PgPool) -> TestApp {
+    common::spawn_app_with_pool(pool)
+        .await
+        .expect("Failed to spawn our app.")
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn create_and_get_budget(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+
+    let id = create_budget(&client, "Groceries").await;
+    let budget = get_budget(&client, id).await;
+
+    assert_eq!(budget.id, id);
+    assert_eq!(budget.title, "Groceries");
+    assert_eq!(budget.user_id, "Alice");
+    assert_eq!(budget.currency, Currency::DKK);
+    assert!(budget.items.is_empty());
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn items_are_returned_with_currency_and_formatted_amount(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let response = client
+        .create_budget(&CreateBudget {
+            title: "Holiday".to_string(),
+            currency: "EUR".parse().unwrap(),
+            period: None,
+        })
+        .await;
+    let budget_id = response.json::<Budget>().await.unwrap().id;
+    let travel = create_category(&client, "Travel").await;
+    add_item(&client, budget_id, travel, "Hotel", 123_450).await;
+    let mut yen = item(travel, "Sushi", 5000);
+    yen.currency = Some("JPY".parse().unwrap());
+    client.add_item(budget_id, &yen).await;
+
+    let response = client.get_budget(budget_id).await;
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["currency"], "EUR");
+    let amounts: Vec<_> = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["amount"].clone())
+        .collect();
+    assert!(amounts.contains(&serde_json::json!({
+        "minor_units": 123_450, "currency": "EUR", "formatted": "1234.50"
+    })));
+    assert!(amounts.contains(&serde_json::json!({
+        "minor_units": 5000, "currency": "JPY", "formatted": "5000"
+    })));
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn unknown_currency_is_rejected(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Holiday").await;
+    let food = create_category(&client, "Food").await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/budget/{budget_id}/item", app.address()))
+        .bearer_auth(app.identity_provider().token("Alice").sign())
+        .json(&serde_json::json!({
+            "category_id": food, "name": "Bread", "amount": 100, "currency": "XYZ"
+        }))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(response.status().is_client_error());
+    assert!(get_budget(&client, budget_id).await.items.is_empty());
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn patch_item_and_budget_with_some_fields(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Holiday").await;
+    let food = create_category(&client, "Food").await;
+    let item_id = add_item(&client, budget_id, food, "Bread", -2_500).await;
+
+    let item = client
+        .patch_item(
+            budget_id,
+            item_id,
+            &PatchItem {
+                amount: Some(-3_000),
+                ..Default::default()
+            },
+        )
+        .await;
+    let budget = client
+        .patch_budget(
+            budget_id,
+            &PatchBudget {
+                title: Some("Summer holiday".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+
+    assert_eq!(item.status(), StatusCode::OK);
+    let item: Item = item.json().await.unwrap();
+    assert_eq!(item.name, "Bread");
+    assert_eq!(item.amount.minor_units(), -3_000);
+    assert_eq!(budget.status(), StatusCode::OK);
+    let budget: BudgetWithItems = budget.json().await.unwrap();
+    assert_eq!(budget.title, "Summer holiday");
+    assert_eq!(budget.items.len(), 1);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn patch_with_null_clears_only_nullable_fields(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Holiday").await;
+    let food = create_category(&client, "Food").await;
+    let item_id = add_item(&client, budget_id, food, "Bread", -2_500).await;
+    let patch = |body: serde_json::Value| {
+        reqwest::Client::new()
+            .patch(format!(
+                "{}/budget/{budget_id}/item/{item_id}",
+                app.address()
+            ))
+            .bearer_auth(app.identity_provider().token("Alice").sign())
+            .json(&body)
+            .send()
+    };
+
+    let recurring = patch(serde_json::json!({
+        "schedule": { "start": "2024-01-01", "rule": "FREQ=WEEKLY" }
+    }))
+    .await
+    .unwrap();
+    let one_off = patch(serde_json::json!({ "schedule": null }))
+        .await
+        .unwrap();
+    let without_name = patch(serde_json::json!({ "name": null })).await.unwrap();
+
+    assert_eq!(recurring.status(), StatusCode::OK);
+    let recurring: Item = recurring.json().await.unwrap();
+    assert!(recurring.schedule.is_some());
+    assert_eq!(one_off.status(), StatusCode::OK);
+    let one_off: Item = one_off.json().await.unwrap();
+    assert!(one_off.schedule.is_none());
+    assert_eq!(one_off.name, "Bread");
+    assert_eq!(without_name.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: ProblemDetails = without_name.json().await.unwrap();
+    assert_eq!(problem.errors.unwrap()[0].field, "name");
+}
+
+fn etag(response: &reqwest::Response) -> String {
+    response.headers()["etag"].to_str().unwrap().to_string()
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn etag_changes_when_an_item_of_the_budget_changes(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Holiday").await;
+    let food = create_category(&client, "Food").await;
+    let item_id = add_item(&client, budget_id, food, "Bread", -2_500).await;
+    let budget = client.get_budget(budget_id).await;
+    let item = client.get_item(budget_id, item_id).await;
+    let budget_etag = etag(&budget);
+    let item_etag = etag(&item);
+
+    let unchanged = client
+        .with_header("If-None-Match", &budget_etag)
+        .get_budget(budget_id)
+        .await;
+    let patched = client
+        .with_header("If-Match", &item_etag)
+        .patch_item(
+            budget_id,
+            item_id,
+            &PatchItem {
+                amount: Some(-3_000),
+                ..Default::default()
+            },
+        )
+        .await;
+    let changed = client
+        .with_header("If-None-Match", &budget_etag)
+        .get_budget(budget_id)
+        .await;
+
+    assert_eq!(item.status(), StatusCode::OK);
+    let item: Item = item.json().await.unwrap();
+    assert_eq!(item_etag, format!("\"{}\"", item.version));
+    assert_eq!(unchanged.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(etag(&unchanged), budget_etag);
+    assert_eq!(patched.status(), StatusCode::OK);
+    assert_ne!(etag(&patched), item_etag);
+    assert_eq!(changed.status(), StatusCode::OK);
+    assert_ne!(etag(&changed), budget_etag);
+    let budget: BudgetWithItems = changed.json().await.unwrap();
+    assert_eq!(budget.items[0].amount.minor_units(), -3_000);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn changes_with_a_stale_etag_are_rejected(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Holiday").await;
+    let food = create_category(&client, "Food").await;
+    let item_id = add_item(&client, budget_id, food, "Bread", -2_500).await;
+    let budget_etag = etag(&client.get_budget(budget_id).await);
+    let item_etag = etag(&client.get_item(budget_id, item_id).await);
+    let rename = PatchItem {
+        name: Some("Rye bread".to_string()),
+        ..Default::default()
+    };
+    client.patch_item(budget_id, item_id, &rename).await;
+
+    let stale_item = client.with_header("If-Match", &item_etag);
+    let stale_budget = client.with_header("If-Match", &budget_etag);
+    let patch_item = stale_item.patch_item(budget_id, item_id, &rename).await;
+    let delete_item = stale_item.delete_item(budget_id, item_id).await;
+    let update_budget = stale_budget
+        .update_budget(
+            budget_id,
+            &UpdateBudget {
+                title: "Summer holiday".to_string(),
+                currency: None,
+                period: None,
+            },
+        )
+        .await;
+    let delete_budget = stale_budget.delete_budget(budget_id).await;
+    let any_version = client
+        .with_header("If-Match", "*")
+        .delete_item(budget_id, item_id)
+        .await;
+
+    for response in [patch_item, delete_item, update_budget, delete_budget] {
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        let problem: ProblemDetails = response.json().await.unwrap();
+        assert_eq!(problem.status, 412);
+    }
+    assert_eq!(any_version.status(), StatusCode::NO_CONTENT);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn changes_are_listed_in_the_history_of_the_budget(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Groceries").await;
+    let food = create_category(&client, "Food").await;
+    let item_id = add_item(&client, budget_id, food, "Bread", 20).await;
+
+    let response = client
+        .with_header("x-request-id", "change-bread")
+        .patch_item(
+            budget_id,
+            item_id,
+            &PatchItem {
+                amount: Some(25),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = client.get_history(budget_id).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let history: Page<AuditEvent> = response.json().await.unwrap();
+    let changes: Vec<_> = history
+        .items
+        .iter()
+        .map(|e| (e.entity, e.action, e.entity_id))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (Entity::Item, Action::Update, item_id),
+            (Entity::Item, Action::Create, item_id),
+            (Entity::Budget, Action::Create, budget_id),
+        ]
+    );
+    let update = &history.items[0];
+    assert_eq!(update.actor, "Alice");
+    assert_eq!(update.request_id.as_deref(), Some("change-bread"));
+    assert_eq!(update.before, Some(serde_json::json!({ "amount": 20 })));
+    assert_eq!(update.after, Some(serde_json::json!({ "amount": 25 })));
+    assert_eq!(
+        app.client("Bob").get_history(budget_id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn budget_can_be_viewed_and_reverted_as_it_was(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Groceries").await;
+    let food = create_category(&client, "Food").await;
+    let bread = add_item(&client, budget_id, food, "Bread", 20).await;
+    let history: Page<AuditEvent> = client.get_history(budget_id).await.json().await.unwrap();
+    let as_of = history.items[0].created_at;
+
+    let response = client
+        .patch_item(
+            budget_id,
+            bread,
+            &PatchItem {
+                amount: Some(25),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    add_item(&client, budget_id, food, "Milk", 10).await;
+
+    let response = client.get_budget_as_of(budget_id, as_of).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("etag").is_none());
+    let past: BudgetWithItems = response.json().await.unwrap();
+    assert_eq!(past.items.len(), 1);
+    assert_eq!(past.items[0].amount.minor_units(), 20);
+    assert_eq!(get_budget(&client, budget_id).await.items.len(), 2);
+
+    let response = client
+        .revert_budget(budget_id, &RevertBudget { as_of })
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("etag").is_some());
+    let reverted: BudgetWithItems = response.json().await.unwrap();
+    assert_eq!(reverted.items.len(), 1);
+    assert_eq!(reverted.items[0].amount.minor_units(), 20);
+    let history: Page<AuditEvent> = client.get_history(budget_id).await.json().await.unwrap();
+    let latest: Vec<_> = history.items[..2].iter().map(|e| e.action).collect();
+    assert_eq!(latest, [Action::Delete, Action::Update]);
+    assert_eq!(
+        app.client("Bob")
+            .revert_budget(budget_id, &RevertBudget { as_of })
+            .await
+            .status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn invalid_fields_are_listed_in_the_problem(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Holiday").await;
+    let food = create_category(&client, "Food").await;
+
+    let budget = client
+        .create_budget(&CreateBudget {
+            title: "  ".to_string(),
+            currency: Currency::default(),
+            period: Some(Period {
+                start: "2024-02-01".parse().unwrap(),
+                end: "2024-01-01".parse().unwrap(),
+                recurrence: None,
+            }),
+        })
+        .await;
+    let item = client
+        .add_item(
+            budget_id,
+            &AddItemToBudgetRequest {
+                category_id: food,
+                name: "x".repeat(101),
+                amount: 1_000_000_000_001,
+                currency: None,
+                schedule: None,
+            },
+        )
+        .await;
+
+    assert_eq!(budget.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(budget.headers()["content-type"], "application/problem+json");
+    let problem: ProblemDetails = budget.json().await.unwrap();
+    let fields: Vec<_> = problem
+        .errors
+        .unwrap()
+        .into_iter()
+        .map(|error| (error.field, error.code))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("period".to_string(), "period_order".to_string()),
+            ("title".to_string(), "blank".to_string()),
+        ]
+    );
+    assert_eq!(item.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: ProblemDetails = item.json().await.unwrap();
+    let fields: Vec<_> = problem
+        .errors
+        .unwrap()
+        .into_iter()
+        .map(|error| error.field)
+        .collect();
+    assert_eq!(fields, vec!["amount", "name"]);
+    assert!(get_budget(&client, budget_id).await.items.is_empty());
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn malformed_bodies_are_rejected_as_problems(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+
+    let malformed = client
+        .post_raw("/budget", "application/json", r#"{"title": "#)
+        .await;
+    let wrong_type = client
+        .post_raw("/budget", "application/json", r#"{"title": 5}"#)
+        .await;
+    let not_json = client
+        .post_raw("/budget", "text/plain", r#"{"title": "Holiday"}"#)
+        .await;
+
+    assert_eq!(malformed.status(), StatusCode::BAD_REQUEST);
+    let problem: ProblemDetails = malformed.json().await.unwrap();
+    assert!(problem.detail.unwrap().starts_with("Malformed JSON"));
+    assert_eq!(wrong_type.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: ProblemDetails = wrong_type.json().await.unwrap();
+    assert_eq!(problem.errors.unwrap()[0].field, "title");
+    assert_eq!(not_json.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let problem: ProblemDetails = not_json.json().await.unwrap();
+    assert_eq!(problem.instance.as_deref(), Some("/budget"));
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn get_all_budgets_only_returns_own_budgets(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let alice = app.client("Alice");
+    create_budget(&alice, "Budget A").await;
+    create_budget(&alice, "Budget B").await;
+    create_budget(&app.client("Bob"), "Budget C").await;
+
+    let response = alice.get_all_budgets().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let page: Page<Budget> = response.json().await.unwrap();
+    assert_eq!(page.items.len(), 2);
+    assert!(page.items.iter().all(|b| b.user_id == "Alice"));
+    assert_eq!(page.next_cursor, None);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn list_budgets_in_pages(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    for title in [
+        "Groceries 2023",
+        "Holiday",
+        "Groceries 2024",
+        "groceries 2025",
+    ] {
+        create_budget(&client, title).await;
+    }
+
+    let mut query = vec![("search", "groceries"), ("sort", "title"), ("limit", "2")];
+    let response = client.list_budgets(&query).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let first: Page<Budget> = response.json().await.unwrap();
+    let cursor = first.next_cursor.expect("a second page");
+    query.push(("cursor", &cursor));
+    let second: Page<Budget> = client.list_budgets(&query).await.json().await.unwrap();
+
+    let titles: Vec<_> = first
+        .items
+        .iter()
+        .chain(second.items.iter())
+        .map(|b| b.title.as_str())
+        .collect();
+    assert_eq!(titles.len(), 3);
+    assert!(titles
+        .iter()
+        .all(|t| t.to_lowercase().starts_with("groceries")));
+    assert_eq!(second.next_cursor, None);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn list_budgets_newest_first_by_default(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    create_budget(&client, "Old").await;
+    create_budget(&client, "New").await;
+
+    let page: Page<Budget> = client.list_budgets(&[]).await.json().await.unwrap();
+
+    let titles: Vec<_> = page.items.iter().map(|b| b.title.as_str()).collect();
+    assert_eq!(titles, vec!["New", "Old"]);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn list_budgets_rejects_invalid_cursor_and_limit(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+
+    let response = client.list_budgets(&[("cursor", "garbage")]).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let problem: ProblemDetails = response.json().await.unwrap();
+    assert_eq!(problem.detail.as_deref(), Some("Invalid cursor"));
+
+    let response = client.list_budgets(&[("limit", "1000")]).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn update_budget_title(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let id = create_budget(&client, "Groceries").await;
+
+    let response = client
+        .update_budget(
+            id,
+            &UpdateBudget {
+                title: "Food".to_string(),
+                currency: None,
+                period: None,
+            },
+        )
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(get_budget(&client, id).await.title, "Food");
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn create_next_period_with_rollover(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let response = client
+        .create_budget(&CreateBudget {
+            title: "January".to_string(),
+            currency: Currency::DKK,
+            period: Some(Period {
+                start: "2024-01-01".parse().unwrap(),
+                end: "2024-01-31".parse().unwrap(),
+                recurrence: Some(Recurrence::Monthly),
+            }),
+        })
+        .await;
+    let budget_id = response.json::<Budget>().await.unwrap().id;
+    let food = create_category(&client, "Food").await;
+    add_item(&client, budget_id, food, "Groceries", -300_000).await;
+    let request = NextPeriod {
+        title: Some("February".to_string()),
+        rollover: true,
+    };
+
+    let response = client.create_next_period(budget_id, &request).await;
+    let again = client.create_next_period(budget_id, &request).await;
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let next = get_budget(&client, response.json::<Budget>().await.unwrap().id).await;
+    assert_eq!(next.title, "February");
+    assert_eq!(next.previous_id, Some(budget_id));
+    let period = next.period.expect("a period");
+    assert_eq!(period.start.to_string(), "2024-02-01");
+    assert_eq!(period.end.to_string(), "2024-02-29");
+    let mut items: Vec<_> = next
+        .items
+        .iter()
+        .map(|i| (i.name.as_str(), i.amount.minor_units()))
+        .collect();
+    items.sort();
+    assert_eq!(items, vec![("Groceries", -300_000), ("Rollover", -300_000)]);
+    assert_eq!(again.status(), StatusCode::CONFLICT);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn budget_without_period_has_no_next_period(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Groceries").await;
+
+    let response = client
+        .create_next_period(budget_id, &NextPeriod::default())
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn duplicate_budget_with_scaled_amounts(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "2026").await;
+    let food = create_category(&client, "Food").await;
+    add_item(&client, budget_id, food, "Groceries", -25).await;
+    add_item(&client, budget_id, food, "Bonus", 1_000).await;
+    let request = DuplicateBudget {
+        title: "2027".to_string(),
+        scale: Some(110),
+    };
+
+    let response = client.duplicate_budget(budget_id, &request).await;
+    let others = app
+        .client("Bob")
+        .duplicate_budget(budget_id, &request)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let copy = get_budget(&client, response.json::<Budget>().await.unwrap().id).await;
+    assert_eq!(copy.title, "2027");
+    let mut items: Vec<_> = copy
+        .items
+        .iter()
+        .map(|i| (i.name.as_str(), i.amount.minor_units()))
+        .collect();
+    items.sort();
+    assert_eq!(items, vec![("Bonus", 1_100), ("Groceries", -28)]);
+    let original = get_budget(&client, budget_id).await;
+    assert_eq!(original.title, "2026");
+    assert_eq!(original.items.len(), 2);
+    assert_eq!(others.status(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn duplicate_budget_with_scaled_amounts_out_of_range(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "2026").await;
+    let home = create_category(&client, "Home").await;
+    add_item(&client, budget_id, home, "House", -600_000_000_000).await;
+    let duplicate = |scale| DuplicateBudget {
+        title: "2027".to_string(),
+        scale: Some(scale),
+    };
+
+    let too_large = client.duplicate_budget(budget_id, &duplicate(200)).await;
+    let within_range = client.duplicate_budget(budget_id, &duplicate(150)).await;
+
+    assert_eq!(too_large.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: ProblemDetails = too_large.json().await.unwrap();
+    let error = &problem.errors.unwrap()[0];
+    assert_eq!(
+        (error.field.as_str(), error.code.as_str()),
+        ("scale", "range")
+    );
+    assert_eq!(within_range.status(), StatusCode::CREATED);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn created_resources_have_a_location(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let food = create_category(&client, "Food").await;
+    let request = CreateBudget {
+        title: "Holiday".to_string(),
+        currency: Currency::default(),
+        period: None,
+    };
+
+    let budget = client.create_budget(&request).await;
+    let location = budget.headers()["location"].to_str().unwrap().to_string();
+    let budget: Budget = budget.json().await.unwrap();
+    let item = client
+        .add_item(budget.id, &item(food, "Bread", -2_500))
+        .await;
+    let item_location = item.headers()["location"].to_str().unwrap().to_string();
+    let item: Item = item.json().await.unwrap();
+    let legacy = client
+        .with_header("Accept", "text/plain")
+        .create_budget(&request)
+        .await;
+
+    assert_eq!(location, format!("/budget/{}", budget.id));
+    assert_eq!(budget.title, "Holiday");
+    assert_eq!(
+        item_location,
+        format!("/budget/{}/item/{}", budget.id, item.id)
+    );
+    assert_eq!(item.name, "Bread");
+    let found = client.get(&item_location).await;
+    assert_eq!(found.status(), StatusCode::OK);
+    assert_eq!(legacy.status(), StatusCode::CREATED);
+    let legacy_location = legacy.headers()["location"].to_str().unwrap().to_string();
+    let id: Uuid = legacy.text().await.unwrap().parse().unwrap();
+    assert_eq!(legacy_location, format!("/budget/{id}"));
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn delete_budget(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let id = create_budget(&client, "Groceries").await;
+
+    let response = client.delete_budget(id).await;
+
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(client.get_budget(id).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn deleted_budgets_and_items_can_be_restored_from_the_trash(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let food = create_category(&client, "Food").await;
+    let groceries = create_budget(&client, "Groceries").await;
+    let bread = add_item(&client, groceries, food, "Bread", 20).await;
+    let holiday = create_budget(&client, "Holiday").await;
+
+    assert_eq!(
+        client.delete_item(groceries, bread).await.status(),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        client.delete_budget(holiday).await.status(),
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        client.get_item(groceries, bread).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        client.get_budget(holiday).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let trash: Trash = client.get_trash().await.json().await.unwrap();
+    assert_eq!(trash.budgets.len(), 1);
+    assert_eq!(trash.budgets[0].id, holiday);
+    assert_eq!(trash.items.len(), 1);
+    assert_eq!(trash.items[0].id, bread);
+    assert_eq!(trash.items[0].budget_title, "Groceries");
+
+    let response = client.restore_item(groceries, bread).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json::<Item>().await.unwrap().name, "Bread");
+    let response = client.restore_budget(holiday).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json::<BudgetWithItems>().await.unwrap().title,
+        "Holiday"
+    );
+
+    assert_eq!(get_budget(&client, groceries).await.items.len(), 1);
+    assert_eq!(get_budget(&client, holiday).await.title, "Holiday");
+    let trash: Trash = client.get_trash().await.json().await.unwrap();
+    assert!(trash.budgets.is_empty());
+    assert!(trash.items.is_empty());
+    assert_eq!(
+        client.restore_budget(holiday).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn unknown_budget_is_not_found_with_problem_details(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let id = Uuid::new_v4();
+
+    let response = app.client("Alice").get_budget(id).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/problem+json"
+    );
+    let problem: ProblemDetails = response.json().await.unwrap();
+    assert_eq!(problem.status, 404);
+    assert_eq!(problem.instance, Some(format!("/budget/{id}")));
+    assert!(problem.request_id.is_some());
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn add_update_and_delete_item(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Groceries").await;
+    let food = create_category(&client, "Food").await;
+
+    let item_id = add_item(&client, budget_id, food, "Bread", 20).await;
+    let items = get_budget(&client, budget_id).await.items;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, item_id);
+    assert_eq!(items[0].amount.minor_units(), 20);
+    assert_eq!(items[0].category_id, food);
+    assert_eq!(items[0].category, "Food");
+
+    let response = client
+        .update_item(budget_id, item_id, &item(food, "Bread", 25))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.json::<Item>().await.unwrap().amount.minor_units(),
+        25
+    );
+    assert_eq!(
+        get_budget(&client, budget_id).await.items[0]
+            .amount
+            .minor_units(),
+        25
+    );
+
+    let response = client.delete_item(budget_id, item_id).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(get_budget(&client, budget_id).await.items.is_empty());
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn budgets_of_other_users_are_not_accessible(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let alice = app.client("Alice");
+    let bob = app.client("Bob");
+    let budget_id = create_budget(&alice, "Groceries").await;
+    let food = create_category(&alice, "Food").await;
+    let item_id = add_item(&alice, budget_id, food, "Bread", 20).await;
+
+    let title = UpdateBudget {
+        title: "Mine now".to_string(),
+        currency: None,
+        period: None,
+    };
+    assert_eq!(
+        bob.get_budget(budget_id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        bob.update_budget(budget_id, &title).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        bob.delete_budget(budget_id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        bob.add_item(budget_id, &item(food, "Cake", 5))
+            .await
+            .status(),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        bob.update_item(budget_id, item_id, &item(food, "Cake", 5))
+            .await
+            .status(),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        bob.delete_item(budget_id, item_id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    // Nothing was changed by the other user.
+    let budget = get_budget(&alice, budget_id).await;
+    assert_eq!(budget.title, "Groceries");
+    assert_eq!(budget.items.len(), 1);
+    assert_eq!(budget.items[0].name, "Bread");
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn recurring_items_are_expanded_and_normalised(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Home").await;
+    let home = create_category(&client, "Home").await;
+    let mut insurance = item(home, "Insurance", -120_000);
+    insurance.schedule = Some(Schedule {
+        start: "2024-03-15".parse().unwrap(),
+        rule: "FREQ=YEARLY".parse().unwrap(),
+    });
+    let response = client.add_item(budget_id, &insurance).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let budget = get_budget(&client, budget_id).await;
+    let summary: serde_json::Value = client
+        .get_budget_summary(budget_id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let response = client
+        .get_occurrences(budget_id, &[("from", "2024-01-01"), ("to", "2024-12-31")])
+        .await;
+    let without_range = client.get_occurrences(budget_id, &[]).await;
+
+    let item = &budget.items[0];
+    assert_eq!(item.schedule, insurance.schedule);
+    assert_eq!(item.monthly_amount.unwrap().minor_units(), -10_000);
+    assert_eq!(item.annual_amount.unwrap().minor_units(), -120_000);
+    assert_eq!(summary["recurring"][0]["category"], "Home");
+    assert_eq!(summary["recurring"][0]["monthly"]["formatted"], "-100.00");
+    assert_eq!(response.status(), StatusCode::OK);
+    let occurrences: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        occurrences,
+        serde_json::json!([{
+            "date": "2024-03-15",
+            "item_id": item.id,
+            "name": "Insurance",
+            "category_id": home,
+            "category": "Home",
+            "amount": { "minor_units": -120_000, "currency": "DKK", "formatted": "-1200.00" }
+        }])
+    );
+    assert_eq!(without_range.status(), StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn import_items_from_danish_csv(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Monthly").await;
+    let home = create_category(&client, "Bolig").await;
+    create_category(&client, "Mad").await;
+    let csv = "Kategori;Navn;Beløb\nBolig;Husleje;-8.500,00\nMad;Indkøb;-2.000\n";
+    let options = [
+        ("delimiter", ";"),
+        ("decimal_separator", ","),
+        ("category", "Kategori"),
+        ("name", "Navn"),
+        ("amount", "Beløb"),
+    ];
+
+    let dry_run = client
+        .import_items(
+            budget_id,
+            csv,
+            &[&options[..], &[("dry_run", "true")]].concat(),
+        )
+        .await;
+    let items_after_dry_run = get_budget(&client, budget_id).await.items.len();
+    let response = client.import_items(budget_id, csv, &options).await;
+
+    assert_eq!(dry_run.status(), StatusCode::OK);
+    let dry_run: serde_json::Value = dry_run.json().await.unwrap();
+    assert_eq!(dry_run["created"], 0);
+    assert_eq!(dry_run["rows"][0]["category_id"], home.to_string());
+    assert_eq!(dry_run["rows"][0]["amount"]["minor_units"], -850_000);
+    assert_eq!(items_after_dry_run, 0);
+    assert_eq!(response.status(), StatusCode::OK);
+    let result: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(result["created"], 2);
+    let budget = get_budget(&client, budget_id).await;
+    assert_eq!(budget.items.len(), 2);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn import_with_invalid_rows_creates_nothing(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Monthly").await;
+    create_category(&client, "Home").await;
+    let csv = "category,name,amount\nHome,Rent,-8500\nTravel,Hotel,-1200\nHome,Heat,abc\n";
+
+    let dry_run = client
+        .import_items(budget_id, csv, &[("dry_run", "true")])
+        .await;
+    let response = client.import_items(budget_id, csv, &[]).await;
+
+    assert_eq!(dry_run.status(), StatusCode::OK);
+    let dry_run: serde_json::Value = dry_run.json().await.unwrap();
+    assert_eq!(dry_run["rows"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        dry_run["errors"],
+        serde_json::json!([
+            { "line": 3, "message": "Unknown category 'Travel'" },
+            { "line": 4, "message": "Invalid amount 'abc'" },
+        ])
+    );
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(get_budget(&client, budget_id).await.items.is_empty());
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn export_budget_as_csv_json_and_xlsx(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Monthly").await;
+    let home = create_category(&client, "Home").await;
+    add_item(&client, budget_id, home, "Rent", -850_000).await;
+
+    let csv = client.export_budget(budget_id, "csv").await;
+    let json = client.export_budget(budget_id, "json").await;
+    let xlsx = client.export_budget(budget_id, "xlsx").await;
+    let unknown = client.export_budget(budget_id, "pdf").await;
+
+    assert_eq!(csv.status(), StatusCode::OK);
+    assert_eq!(
+        csv.headers()["content-disposition"],
+        format!("attachment; filename=\"budget-{budget_id}.csv\"").as_str()
+    );
+    let csv = csv.text().await.unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("budget_id,budget,item_id,category_id,category,name,amount,currency,schedule_start,schedule_rule,created_at,modified_at")
+    );
+    assert!(lines.next().unwrap().contains(",Home,Rent,-8500.00,DKK,"));
+    assert_eq!(json.status(), StatusCode::OK);
+    let json: BudgetWithItems = json.json().await.unwrap();
+    assert_eq!(json.items.len(), 1);
+    assert_eq!(xlsx.status(), StatusCode::OK);
+    assert!(xlsx.bytes().await.unwrap().starts_with(b"PK"));
+    assert_eq!(unknown.status(), StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn export_all_budgets_as_archive(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    create_budget(&client, "January").await;
+    create_budget(&client, "February").await;
+    create_budget(&app.client("Bob"), "Bob's").await;
+
+    let response = client.export_all_budgets("csv").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/zip");
+    let archive = response.bytes().await.unwrap();
+    let archive = zip::ZipArchive::new(std::io::Cursor::new(archive)).unwrap();
+    assert_eq!(archive.len(), 2);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn get_budget_summary(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget_id = create_budget(&client, "Monthly").await;
+    let food = create_category(&client, "Food").await;
+    add_item(&client, budget_id, food, "Paycheck", 2_000_000).await;
+    add_item(&client, budget_id, food, "Groceries", -500_000).await;
+
+    let response = client.get_budget_summary(budget_id).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let summary: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(summary["currency"], "DKK");
+    assert_eq!(summary["totals"][0]["income"]["formatted"], "20000.00");
+    assert_eq!(summary["totals"][0]["expenses"]["formatted"], "-5000.00");
+    assert_eq!(summary["totals"][0]["balance"]["minor_units"], 1_500_000);
+    assert_eq!(summary["categories"][0]["category"], "Food");
+    assert_eq!(summary["categories"][0]["item_count"], 2);
+    assert_eq!(summary["categories"][0]["percentage"], 100.0);
+    assert_eq!(summary["largest_items"][0]["name"], "Paycheck");
+    assert_eq!(
+        app.client("Bob")
+            .get_budget_summary(budget_id)
+            .await
+            .status(),
+        StatusCode::NOT_FOUND
+    );
+}
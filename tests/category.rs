@@ -0,0 +1,297 @@
+use budget_api::{
+    audit::{dto::AuditEvent, Action, Entity},
+    budget::dto::{AddItemToBudgetRequest, Budget, BudgetWithItems, CreateBudget},
+    category::dto::{Category, CategoryRequest, MergeResult},
+    error::ProblemDetails,
+    money::Currency,
+    pagination::Page,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+
+use common::{ApiClient, TestApp};
+
+fn named(name: &str) -> CategoryRequest {
+    CategoryRequest {
+        name: name.to_string(),
+        color: None,
+        icon: None,
+        kind: Default::default(),
+        parent_id: None,
+    }
+}
+
+async fn create_category(client: &ApiClient, request: &CategoryRequest) -> Uuid {
+    let response = client.create_category(request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response.text().await.unwrap().parse().unwrap()
+}
+
+async fn get_category(client: &ApiClient, category_id: Uuid) -> Category {
+    let response = client.get_category(category_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response.json().await.unwrap()
+}
+
+/// Create a budget with an item in each of `categories`.
+async fn create_budget_with_items(client: &ApiClient, categories: &[Uuid]) -> Uuid {
+    let response = client
+        .create_budget(&CreateBudget {
+            title: "Monthly".to_string(),
+            currency: Currency::default(),
+            period: None,
+        })
+        .await;
+    let budget_id = response.json::<Budget>().await.unwrap().id;
+    for category_id in categories {
+        let item = AddItemToBudgetRequest {
+            category_id: *category_id,
+            name: "Item".to_string(),
+            amount: -100,
+            currency: None,
+            schedule: None,
+        };
+        assert_eq!(
+            client.add_item(budget_id, &item).await.status(),
+            StatusCode::CREATED
+        );
+    }
+
+    budget_id
+}
+
+async fn spawn_app(pool: PgPool) -> TestApp {
+    common::spawn_app_with_pool(pool)
+        .await
+        .expect("Failed to spawn our app.")
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn create_and_get_category(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let request = CategoryRequest {
+        color: Some("#1e90ff".to_string()),
+        icon: Some("cart".to_string()),
+        ..named("Groceries")
+    };
+
+    let id = create_category(&client, &request).await;
+    let category = get_category(&client, id).await;
+
+    assert_eq!(category.id, id);
+    assert_eq!(category.name, "Groceries");
+    assert_eq!(category.color.as_deref(), Some("#1e90ff"));
+    assert_eq!(category.icon.as_deref(), Some("cart"));
+    assert_eq!(category.parent_id, None);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn get_all_categories_only_returns_own_categories(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let alice = app.client("Alice");
+    create_category(&alice, &named("Rent")).await;
+    create_category(&alice, &named("food")).await;
+    create_category(&app.client("Bob"), &named("Travel")).await;
+
+    let response = alice.get_all_categories().await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let categories: Vec<Category> = response.json().await.unwrap();
+    let names: Vec<_> = categories.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["food", "Rent"]);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn duplicate_category_name_is_a_conflict(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    create_category(&client, &named("Food")).await;
+
+    let response = client.create_category(&named(" FOOD ")).await;
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let problem: ProblemDetails = response.json().await.unwrap();
+    assert_eq!(
+        problem.detail.as_deref(),
+        Some("A category named ' FOOD ' already exists")
+    );
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn invalid_color_is_rejected(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let request = CategoryRequest {
+        color: Some("blue".to_string()),
+        ..named("Food")
+    };
+
+    let response = client.create_category(&request).await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: ProblemDetails = response.json().await.unwrap();
+    let errors = problem.errors.unwrap();
+    assert_eq!(errors[0].field, "color");
+    assert_eq!(errors[0].code, "color");
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn renamed_category_is_shown_on_items(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let food = create_category(&client, &named("Food")).await;
+    let budget_id = create_budget_with_items(&client, &[food]).await;
+
+    let response = client.update_category(food, &named("Groceries")).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let budget: BudgetWithItems = client.get_budget(budget_id).await.json().await.unwrap();
+    assert_eq!(budget.items[0].category_id, food);
+    assert_eq!(budget.items[0].category, "Groceries");
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn subcategory_cannot_be_its_own_ancestor(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let food = create_category(&client, &named("Food")).await;
+    let groceries = CategoryRequest {
+        parent_id: Some(food),
+        ..named("Groceries")
+    };
+    let groceries = create_category(&client, &groceries).await;
+
+    let response = client
+        .update_category(
+            food,
+            &CategoryRequest {
+                parent_id: Some(groceries),
+                ..named("Food")
+            },
+        )
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(get_category(&client, food).await.parent_id, None);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn category_with_items_cannot_be_deleted(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let food = create_category(&client, &named("Food")).await;
+    let unused = create_category(&client, &named("Unused")).await;
+    create_budget_with_items(&client, &[food]).await;
+
+    assert_eq!(
+        client.delete_category(food).await.status(),
+        StatusCode::CONFLICT
+    );
+    assert_eq!(
+        client.delete_category(unused).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        client.get_category(unused).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn merge_category_moves_items(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let food = create_category(&client, &named("Food")).await;
+    let groceries = create_category(&client, &named("Groceries")).await;
+    let budget_id = create_budget_with_items(&client, &[food, groceries, groceries]).await;
+
+    let response = client.merge_category(groceries, food).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let result: MergeResult = response.json().await.unwrap();
+    assert_eq!(result.moved_items, 2);
+    assert_eq!(
+        client.get_category(groceries).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    let budget: BudgetWithItems = client.get_budget(budget_id).await.json().await.unwrap();
+    assert!(budget.items.iter().all(|item| item.category_id == food));
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn moved_items_are_listed_in_the_history_of_their_budget(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let food = create_category(&client, &named("Food")).await;
+    let groceries = create_category(&client, &named("Groceries")).await;
+    let budget_id = create_budget_with_items(&client, &[food, groceries, groceries]).await;
+
+    let response = client.merge_category(groceries, food).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = client.get_history(budget_id).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let history: Page<AuditEvent> = response.json().await.unwrap();
+    let moved: Vec<_> = history
+        .items
+        .iter()
+        .filter(|e| e.entity == Entity::Item && e.action == Action::Update)
+        .collect();
+    assert_eq!(moved.len(), 2);
+    for event in moved {
+        assert_eq!(event.actor, "Alice");
+        assert_eq!(
+            event.before,
+            Some(serde_json::json!({ "category_id": groceries }))
+        );
+        assert_eq!(
+            event.after,
+            Some(serde_json::json!({ "category_id": food }))
+        );
+    }
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn categories_of_other_users_are_not_accessible(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let alice = app.client("Alice");
+    let bob = app.client("Bob");
+    let food = create_category(&alice, &named("Food")).await;
+    let bobs_food = create_category(&bob, &named("Food")).await;
+
+    assert_eq!(bob.get_category(food).await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        bob.update_category(food, &named("Mine now")).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        bob.delete_category(food).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        bob.merge_category(food, bobs_food).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        bob.merge_category(bobs_food, food).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    assert_eq!(get_category(&alice, food).await.name, "Food");
+}
@@ -0,0 +1,424 @@
+use budget_api::{
+    budget::dto::{
+        AddItemToBudgetRequest, CreateBudget, DuplicateBudget, InviteMember, NextPeriod,
+        PatchBudget, PatchItem, RevertBudget, TransactionRequest, UpdateBudget,
+    },
+    category::dto::{CategoryRequest, MergeCategory},
+    template::dto::{TemplateRequest, UseTemplate},
+};
+use chrono::{DateTime, Utc};
+use reqwest::{Method, RequestBuilder, Response};
+use uuid::Uuid;
+
+/// HTTP client with a method for each of the budget, item, transaction, member, category, and
+/// template routes.
+///
+/// The responses are returned as is, so tests can assert on both the status
+/// code and the body.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    client: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    headers: Vec<(&'static str, String)>,
+}
+
+impl ApiClient {
+    pub fn new(address: &str, token: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: address.to_string(),
+            token,
+            headers: vec![],
+        }
+    }
+
+    /// A client that also sends `name: value` with every request, e.g. `If-Match`.
+    pub fn with_header(&self, name: &'static str, value: &str) -> Self {
+        let mut client = self.clone();
+        client.headers.push((name, value.to_string()));
+        client
+    }
+
+    pub async fn get_all_budgets(&self) -> Response {
+        self.send(self.request(Method::GET, "/budget")).await
+    }
+
+    /// List budgets with query parameters, e.g. `[("sort", "title")]`.
+    pub async fn list_budgets(&self, query: &[(&str, &str)]) -> Response {
+        self.send(self.request(Method::GET, "/budget").query(query))
+            .await
+    }
+
+    pub async fn create_budget(&self, request: &CreateBudget) -> Response {
+        self.send(self.request(Method::POST, "/budget").json(request))
+            .await
+    }
+
+    pub async fn get_budget(&self, budget_id: Uuid) -> Response {
+        self.send(self.request(Method::GET, &format!("/budget/{budget_id}")))
+            .await
+    }
+
+    pub async fn get_budget_as_of(&self, budget_id: Uuid, as_of: DateTime<Utc>) -> Response {
+        self.send(
+            self.request(Method::GET, &format!("/budget/{budget_id}"))
+                .query(&[("as_of", as_of.to_rfc3339())]),
+        )
+        .await
+    }
+
+    pub async fn revert_budget(&self, budget_id: Uuid, request: &RevertBudget) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/budget/{budget_id}/revert"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn get_budget_summary(&self, budget_id: Uuid) -> Response {
+        self.send(self.request(Method::GET, &format!("/budget/{budget_id}/summary")))
+            .await
+    }
+
+    pub async fn get_history(&self, budget_id: Uuid) -> Response {
+        self.send(self.request(Method::GET, &format!("/budget/{budget_id}/history")))
+            .await
+    }
+
+    pub async fn create_next_period(&self, budget_id: Uuid, request: &NextPeriod) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/budget/{budget_id}/next"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn duplicate_budget(&self, budget_id: Uuid, request: &DuplicateBudget) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/budget/{budget_id}/duplicate"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn update_budget(&self, budget_id: Uuid, request: &UpdateBudget) -> Response {
+        self.send(
+            self.request(Method::PUT, &format!("/budget/{budget_id}"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn patch_budget(&self, budget_id: Uuid, request: &PatchBudget) -> Response {
+        self.send(
+            self.request(Method::PATCH, &format!("/budget/{budget_id}"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn delete_budget(&self, budget_id: Uuid) -> Response {
+        self.send(self.request(Method::DELETE, &format!("/budget/{budget_id}")))
+            .await
+    }
+
+    pub async fn restore_budget(&self, budget_id: Uuid) -> Response {
+        self.send(self.request(Method::POST, &format!("/budget/{budget_id}/restore")))
+            .await
+    }
+
+    pub async fn add_item(&self, budget_id: Uuid, request: &AddItemToBudgetRequest) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/budget/{budget_id}/item"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn get_item(&self, budget_id: Uuid, item_id: Uuid) -> Response {
+        self.send(self.request(Method::GET, &format!("/budget/{budget_id}/item/{item_id}")))
+            .await
+    }
+
+    pub async fn update_item(
+        &self,
+        budget_id: Uuid,
+        item_id: Uuid,
+        request: &AddItemToBudgetRequest,
+    ) -> Response {
+        self.send(
+            self.request(Method::PUT, &format!("/budget/{budget_id}/item/{item_id}"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn patch_item(
+        &self,
+        budget_id: Uuid,
+        item_id: Uuid,
+        request: &PatchItem,
+    ) -> Response {
+        self.send(
+            self.request(
+                Method::PATCH,
+                &format!("/budget/{budget_id}/item/{item_id}"),
+            )
+            .json(request),
+        )
+        .await
+    }
+
+    pub async fn delete_item(&self, budget_id: Uuid, item_id: Uuid) -> Response {
+        self.send(self.request(
+            Method::DELETE,
+            &format!("/budget/{budget_id}/item/{item_id}"),
+        ))
+        .await
+    }
+
+    pub async fn restore_item(&self, budget_id: Uuid, item_id: Uuid) -> Response {
+        self.send(self.request(
+            Method::POST,
+            &format!("/budget/{budget_id}/item/{item_id}/restore"),
+        ))
+        .await
+    }
+
+    /// Import items from `csv` with query parameters, e.g. `[("dry_run", "true")]`.
+    pub async fn import_items(
+        &self,
+        budget_id: Uuid,
+        csv: &str,
+        query: &[(&str, &str)],
+    ) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/budget/{budget_id}/import"))
+                .header("content-type", "text/csv")
+                .query(query)
+                .body(csv.to_string()),
+        )
+        .await
+    }
+
+    pub async fn export_budget(&self, budget_id: Uuid, format: &str) -> Response {
+        self.send(
+            self.request(Method::GET, &format!("/budget/{budget_id}/export"))
+                .query(&[("format", format)]),
+        )
+        .await
+    }
+
+    pub async fn export_all_budgets(&self, format: &str) -> Response {
+        self.send(
+            self.request(Method::GET, "/budget/export")
+                .query(&[("format", format)]),
+        )
+        .await
+    }
+
+    pub async fn get_budget_progress(&self, budget_id: Uuid) -> Response {
+        self.send(self.request(Method::GET, &format!("/budget/{budget_id}/progress")))
+            .await
+    }
+
+    /// Expand the recurring items of a budget with query parameters, e.g. `[("from", "2024-01-01")]`.
+    pub async fn get_occurrences(&self, budget_id: Uuid, query: &[(&str, &str)]) -> Response {
+        self.send(
+            self.request(Method::GET, &format!("/budget/{budget_id}/occurrences"))
+                .query(query),
+        )
+        .await
+    }
+
+    pub async fn get_transactions(&self, budget_id: Uuid, query: &[(&str, &str)]) -> Response {
+        self.send(
+            self.request(Method::GET, &format!("/budget/{budget_id}/transaction"))
+                .query(query),
+        )
+        .await
+    }
+
+    pub async fn get_transaction(&self, budget_id: Uuid, transaction_id: Uuid) -> Response {
+        self.send(self.request(
+            Method::GET,
+            &format!("/budget/{budget_id}/transaction/{transaction_id}"),
+        ))
+        .await
+    }
+
+    pub async fn add_transaction(&self, budget_id: Uuid, request: &TransactionRequest) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/budget/{budget_id}/transaction"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn update_transaction(
+        &self,
+        budget_id: Uuid,
+        transaction_id: Uuid,
+        request: &TransactionRequest,
+    ) -> Response {
+        self.send(
+            self.request(
+                Method::PUT,
+                &format!("/budget/{budget_id}/transaction/{transaction_id}"),
+            )
+            .json(request),
+        )
+        .await
+    }
+
+    pub async fn delete_transaction(&self, budget_id: Uuid, transaction_id: Uuid) -> Response {
+        self.send(self.request(
+            Method::DELETE,
+            &format!("/budget/{budget_id}/transaction/{transaction_id}"),
+        ))
+        .await
+    }
+
+    pub async fn get_members(&self, budget_id: Uuid) -> Response {
+        self.send(self.request(Method::GET, &format!("/budget/{budget_id}/member")))
+            .await
+    }
+
+    pub async fn invite_member(&self, budget_id: Uuid, request: &InviteMember) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/budget/{budget_id}/member"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn remove_member(&self, budget_id: Uuid, user_id: &str) -> Response {
+        self.send(self.request(
+            Method::DELETE,
+            &format!("/budget/{budget_id}/member/{user_id}"),
+        ))
+        .await
+    }
+
+    pub async fn accept_invitation(&self, budget_id: Uuid) -> Response {
+        self.send(self.request(Method::POST, &format!("/budget/{budget_id}/accept")))
+            .await
+    }
+
+    pub async fn get_invitations(&self) -> Response {
+        self.send(self.request(Method::GET, "/budget/invitation"))
+            .await
+    }
+
+    pub async fn get_trash(&self) -> Response {
+        self.send(self.request(Method::GET, "/trash")).await
+    }
+
+    pub async fn get_all_categories(&self) -> Response {
+        self.send(self.request(Method::GET, "/category")).await
+    }
+
+    pub async fn create_category(&self, request: &CategoryRequest) -> Response {
+        self.send(self.request(Method::POST, "/category").json(request))
+            .await
+    }
+
+    pub async fn get_category(&self, category_id: Uuid) -> Response {
+        self.send(self.request(Method::GET, &format!("/category/{category_id}")))
+            .await
+    }
+
+    pub async fn update_category(&self, category_id: Uuid, request: &CategoryRequest) -> Response {
+        self.send(
+            self.request(Method::PUT, &format!("/category/{category_id}"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn delete_category(&self, category_id: Uuid) -> Response {
+        self.send(self.request(Method::DELETE, &format!("/category/{category_id}")))
+            .await
+    }
+
+    pub async fn merge_category(&self, category_id: Uuid, into: Uuid) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/category/{category_id}/merge"))
+                .json(&MergeCategory { into }),
+        )
+        .await
+    }
+
+    pub async fn get_all_templates(&self) -> Response {
+        self.send(self.request(Method::GET, "/template")).await
+    }
+
+    pub async fn create_template(&self, request: &TemplateRequest) -> Response {
+        self.send(self.request(Method::POST, "/template").json(request))
+            .await
+    }
+
+    pub async fn get_template(&self, template_id: Uuid) -> Response {
+        self.send(self.request(Method::GET, &format!("/template/{template_id}")))
+            .await
+    }
+
+    pub async fn update_template(&self, template_id: Uuid, request: &TemplateRequest) -> Response {
+        self.send(
+            self.request(Method::PUT, &format!("/template/{template_id}"))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn delete_template(&self, template_id: Uuid) -> Response {
+        self.send(self.request(Method::DELETE, &format!("/template/{template_id}")))
+            .await
+    }
+
+    pub async fn create_budget_from_template(
+        &self,
+        template_id: Uuid,
+        request: &UseTemplate,
+    ) -> Response {
+        self.send(
+            self.request(Method::POST, &format!("/template/{template_id}/budget"))
+                .json(request),
+        )
+        .await
+    }
+
+    /// Get a path as is, e.g. the `Location` of a created resource.
+    pub async fn get(&self, path: &str) -> Response {
+        self.send(self.request(Method::GET, path)).await
+    }
+
+    /// Post a body as is, e.g. to test how malformed JSON is rejected.
+    pub async fn post_raw(&self, path: &str, content_type: &str, body: &'static str) -> Response {
+        self.send(
+            self.request(Method::POST, path)
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .body(body),
+        )
+        .await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let mut request = self
+            .client
+            .request(method, format!("{}{path}", self.base_url));
+        for (name, value) in &self.headers {
+            request = request.header(*name, value);
+        }
+
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Response {
+        request.send().await.expect("Failed to execute request.")
+    }
+}
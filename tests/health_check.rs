@@ -19,3 +19,24 @@ async fn health_check_works() {
     assert_eq!(response.status(), StatusCode::OK);
     assert_eq!(Some(0), response.content_length());
 }
+
+#[tokio::test]
+async fn unknown_route_returns_problem_details() {
+    // Arrange
+    let app = common::spawn_app().await.expect("Failed to spawn our app.");
+    let client = reqwest::Client::new();
+
+    // Act
+    let response = client
+        .get(format!("{}/unknown", app.address()))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/problem+json"
+    );
+}
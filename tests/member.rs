@@ -0,0 +1,173 @@
+use budget_api::{
+    budget::{
+        dto::{
+            AddItemToBudgetRequest, Budget, BudgetWithItems, CreateBudget, Invitation,
+            InviteMember, Member,
+        },
+        member::Role,
+    },
+    category::dto::CategoryRequest,
+    money::Currency,
+    pagination::Page,
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+
+use common::{ApiClient, TestApp};
+
+async fn create_budget(client: &ApiClient, title: &str) -> Uuid {
+    let response = client
+        .create_budget(&CreateBudget {
+            title: title.to_string(),
+            currency: Currency::default(),
+            period: None,
+        })
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    response.json::<Budget>().await.unwrap().id
+}
+
+async fn create_category(client: &ApiClient, name: &str) -> Uuid {
+    let response = client
+        .create_category(&CategoryRequest {
+            name: name.to_string(),
+            color: None,
+            icon: None,
+            kind: Default::default(),
+            parent_id: None,
+        })
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response.text().await.unwrap().parse().unwrap()
+}
+
+fn item(category_id: Uuid, name: &str) -> AddItemToBudgetRequest {
+    AddItemToBudgetRequest {
+        category_id,
+        name: name.to_string(),
+        amount: -100,
+        currency: None,
+        schedule: None,
+    }
+}
+
+/// Invite a user to a budget, and accept the invitation as them.
+async fn share(app: &TestApp, budget_id: Uuid, user_id: &str, role: Role) {
+    let request = InviteMember {
+        user_id: user_id.to_string(),
+        role,
+    };
+    let response = app.client("Alice").invite_member(budget_id, &request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.client(user_id).accept_invitation(budget_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn spawn_app(pool: PgPool) -> TestApp {
+    common::spawn_app_with_pool(pool)
+        .await
+        .expect("Failed to spawn our app.")
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn invited_user_gets_access_once_accepted(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let alice = app.client("Alice");
+    let bob = app.client("Bob");
+    let budget_id = create_budget(&alice, "Household").await;
+
+    let response = alice
+        .invite_member(
+            budget_id,
+            &InviteMember {
+                user_id: "Bob".to_string(),
+                role: Role::Editor,
+            },
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let invitations: Vec<Invitation> = bob.get_invitations().await.json().await.unwrap();
+    let before = bob.get_budget(budget_id).await;
+    let response = bob.accept_invitation(budget_id).await;
+
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0].budget_id, budget_id);
+    assert_eq!(invitations[0].title, "Household");
+    assert_eq!(before.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.status(), StatusCode::OK);
+    let budget: BudgetWithItems = bob.get_budget(budget_id).await.json().await.unwrap();
+    assert_eq!(budget.title, "Household");
+    let page: Page<Budget> = bob.get_all_budgets().await.json().await.unwrap();
+    assert_eq!(page.items.len(), 1);
+    let members: Vec<Member> = bob.get_members(budget_id).await.json().await.unwrap();
+    let roles: Vec<_> = members
+        .iter()
+        .map(|m| (m.user_id.as_str(), m.role))
+        .collect();
+    assert_eq!(roles, vec![("Alice", Role::Owner), ("Bob", Role::Editor)]);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn roles_limit_what_members_can_do(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let alice = app.client("Alice");
+    let budget_id = create_budget(&alice, "Household").await;
+    share(&app, budget_id, "Bob", Role::Viewer).await;
+    share(&app, budget_id, "Carol", Role::Editor).await;
+    let bob = app.client("Bob");
+    let carol = app.client("Carol");
+    let alices_food = create_category(&alice, "Food").await;
+    let carols_food = create_category(&carol, "Food").await;
+
+    let by_viewer = bob.add_item(budget_id, &item(alices_food, "Bread")).await;
+    let by_editor = carol.add_item(budget_id, &item(alices_food, "Bread")).await;
+    let in_own_category = carol.add_item(budget_id, &item(carols_food, "Milk")).await;
+    let delete_by_editor = carol.delete_budget(budget_id).await;
+    let invite_by_editor = carol
+        .invite_member(
+            budget_id,
+            &InviteMember {
+                user_id: "Dave".to_string(),
+                role: Role::Owner,
+            },
+        )
+        .await;
+
+    assert_eq!(by_viewer.status(), StatusCode::FORBIDDEN);
+    assert_eq!(by_editor.status(), StatusCode::CREATED);
+    assert_eq!(in_own_category.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(delete_by_editor.status(), StatusCode::FORBIDDEN);
+    assert_eq!(invite_by_editor.status(), StatusCode::FORBIDDEN);
+    assert_eq!(bob.get_budget(budget_id).await.status(), StatusCode::OK);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn revoked_member_loses_access(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let alice = app.client("Alice");
+    let bob = app.client("Bob");
+    let budget_id = create_budget(&alice, "Household").await;
+    share(&app, budget_id, "Bob", Role::Editor).await;
+
+    let response = alice.remove_member(budget_id, "Bob").await;
+    let again = alice.remove_member(budget_id, "Bob").await;
+
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(again.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        bob.get_budget(budget_id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        bob.get_members(budget_id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
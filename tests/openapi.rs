@@ -0,0 +1,98 @@
+use hyper::StatusCode;
+use reqwest::Method;
+
+mod common;
+
+#[tokio::test]
+async fn openapi_document_is_served() {
+    // Arrange
+    let app = common::spawn_app().await.expect("Failed to spawn our app.");
+    let client = reqwest::Client::new();
+
+    // Act
+    let response = client
+        .get(format!("{}/openapi.json", app.address()))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let openapi: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(openapi["openapi"], "3.1.0");
+    assert_eq!(
+        openapi["components"]["securitySchemes"]["bearer"]["scheme"],
+        "bearer"
+    );
+}
+
+/// Every operation in the document must be routed by the app, so the
+/// document cannot drift from the routers.
+#[tokio::test]
+async fn documented_operations_are_routed() {
+    // Arrange
+    let app = common::spawn_app().await.expect("Failed to spawn our app.");
+    let client = reqwest::Client::new();
+    let openapi: serde_json::Value = client
+        .get(format!("{}/openapi.json", app.address()))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    for (path, item) in openapi["paths"].as_object().unwrap() {
+        // Any value for the path parameters, as the request is rejected before it is used.
+        let uri = path
+            .split('/')
+            .map(|segment| match segment.starts_with('{') {
+                true => "00000000-0000-0000-0000-000000000000",
+                false => segment,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        for (method, operation) in item.as_object().unwrap() {
+            let Ok(method) = method.to_uppercase().parse::<Method>() else {
+                continue;
+            };
+
+            // Act
+            let response = client
+                .request(method.clone(), format!("{}{uri}", app.address()))
+                .send()
+                .await
+                .unwrap();
+
+            // Assert
+            let expected = match operation.get("security") {
+                Some(_) => StatusCode::UNAUTHORIZED,
+                None => StatusCode::OK,
+            };
+            assert_eq!(response.status(), expected, "{method} {path}");
+        }
+    }
+}
+
+#[cfg(feature = "docs_ui")]
+#[tokio::test]
+async fn documentation_is_served() {
+    // Arrange
+    let app = common::spawn_app().await.expect("Failed to spawn our app.");
+    let client = reqwest::Client::new();
+
+    // Act
+    let response = client
+        .get(format!("{}/docs", app.address()))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains(r#"spec-url="/openapi.json""#));
+}
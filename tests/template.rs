@@ -0,0 +1,178 @@
+use budget_api::{
+    budget::dto::{Budget, BudgetWithItems},
+    category::dto::CategoryRequest,
+    error::ProblemDetails,
+    money::Currency,
+    template::dto::{Template, TemplateItemRequest, TemplateRequest, UseTemplate},
+};
+use hyper::StatusCode;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+
+use common::{ApiClient, TestApp};
+
+async fn create_category(client: &ApiClient, name: &str) -> Uuid {
+    let response = client
+        .create_category(&CategoryRequest {
+            name: name.to_string(),
+            color: None,
+            icon: None,
+            kind: Default::default(),
+            parent_id: None,
+        })
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response.text().await.unwrap().parse().unwrap()
+}
+
+fn item(category_id: Uuid, name: &str, amount: i64) -> TemplateItemRequest {
+    TemplateItemRequest {
+        category_id,
+        name: name.to_string(),
+        amount,
+        currency: None,
+    }
+}
+
+async fn spawn_app(pool: PgPool) -> TestApp {
+    common::spawn_app_with_pool(pool)
+        .await
+        .expect("Failed to spawn our app.")
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn create_update_and_delete_template(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let home = create_category(&client, "Home").await;
+    let request = TemplateRequest {
+        name: "Monthly".to_string(),
+        currency: Currency::default(),
+        items: vec![item(home, "Rent", -800_000)],
+    };
+
+    let response = client.create_template(&request).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let location = response.headers()["location"].to_str().unwrap().to_string();
+    let template: Template = response.json().await.unwrap();
+    assert_eq!(location, format!("/template/{}", template.id));
+    let update = TemplateRequest {
+        name: "Apartment".to_string(),
+        items: vec![
+            item(home, "Rent", -900_000),
+            item(home, "Electricity", -50_000),
+        ],
+        ..request
+    };
+    let updated = client.update_template(template.id, &update).await;
+    let all = client.get_all_templates().await;
+    let others = app.client("Bob").get_template(template.id).await;
+    let deleted = client.delete_template(template.id).await;
+    let gone = client.get(&location).await;
+
+    assert_eq!(updated.status(), StatusCode::OK);
+    let updated: Template = updated.json().await.unwrap();
+    assert_eq!(updated.name, "Apartment");
+    let items: Vec<_> = updated
+        .items
+        .iter()
+        .map(|i| (i.name.as_str(), i.category.as_str(), i.amount.minor_units()))
+        .collect();
+    assert_eq!(
+        items,
+        [("Rent", "Home", -900_000), ("Electricity", "Home", -50_000)]
+    );
+    let all: Vec<Template> = all.json().await.unwrap();
+    assert_eq!(all.len(), 1);
+    assert_eq!(others.status(), StatusCode::NOT_FOUND);
+    assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+    assert_eq!(gone.status(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn budget_is_created_from_template(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let home = create_category(&client, "Home").await;
+    let response = client
+        .create_template(&TemplateRequest {
+            name: "Monthly".to_string(),
+            currency: Currency::default(),
+            items: vec![
+                item(home, "Rent", -800_000),
+                item(home, "Internet", -30_000),
+            ],
+        })
+        .await;
+    let template: Template = response.json().await.unwrap();
+
+    let response = client
+        .create_budget_from_template(
+            template.id,
+            &UseTemplate {
+                title: Some("November".to_string()),
+                period: None,
+            },
+        )
+        .await;
+    let others = app
+        .client("Bob")
+        .create_budget_from_template(template.id, &UseTemplate::default())
+        .await;
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let location = response.headers()["location"].to_str().unwrap().to_string();
+    let budget: Budget = response.json().await.unwrap();
+    assert_eq!(location, format!("/budget/{}", budget.id));
+    let budget: BudgetWithItems = client.get(&location).await.json().await.unwrap();
+    assert_eq!(budget.title, "November");
+    let mut items: Vec<_> = budget
+        .items
+        .iter()
+        .map(|i| (i.name.as_str(), i.amount.minor_units()))
+        .collect();
+    items.sort();
+    assert_eq!(items, [("Internet", -30_000), ("Rent", -800_000)]);
+    assert_eq!(others.status(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn invalid_items_of_template_are_listed_in_the_problem(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let home = create_category(&client, "Home").await;
+
+    let invalid = client
+        .create_template(&TemplateRequest {
+            name: "Monthly".to_string(),
+            currency: Currency::default(),
+            items: vec![item(home, "Rent", -1), item(home, " ", 1_000_000_000_001)],
+        })
+        .await;
+    let unknown_category = client
+        .create_template(&TemplateRequest {
+            name: "Monthly".to_string(),
+            currency: Currency::default(),
+            items: vec![item(Uuid::new_v4(), "Rent", -1)],
+        })
+        .await;
+
+    assert_eq!(invalid.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: ProblemDetails = invalid.json().await.unwrap();
+    let fields: Vec<_> = problem
+        .errors
+        .unwrap()
+        .into_iter()
+        .map(|error| error.field)
+        .collect();
+    assert_eq!(fields, vec!["items[1].amount", "items[1].name"]);
+    assert_eq!(unknown_category.status(), StatusCode::BAD_REQUEST);
+    let all: Vec<Template> = client.get_all_templates().await.json().await.unwrap();
+    assert!(all.is_empty());
+}
@@ -0,0 +1,225 @@
+use budget_api::{
+    budget::dto::{
+        self, AddItemToBudgetRequest, BudgetProgress, CreateBudget, Transaction, TransactionRequest,
+    },
+    category::dto::CategoryRequest,
+    money::Currency,
+};
+use chrono::NaiveDate;
+use hyper::StatusCode;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+mod common;
+
+use common::{ApiClient, TestApp};
+
+/// Ids of a budget with a single planned grocery expense.
+struct Budget {
+    id: Uuid,
+    category_id: Uuid,
+    item_id: Uuid,
+}
+
+async fn create_budget_with_groceries(client: &ApiClient, planned: i64) -> Budget {
+    let response = client
+        .create_budget(&CreateBudget {
+            title: "Monthly".to_string(),
+            currency: Currency::default(),
+            period: None,
+        })
+        .await;
+    let id = response.json::<dto::Budget>().await.unwrap().id;
+    let response = client
+        .create_category(&CategoryRequest {
+            name: "Food".to_string(),
+            color: None,
+            icon: None,
+            kind: Default::default(),
+            parent_id: None,
+        })
+        .await;
+    let category_id = response.text().await.unwrap().parse().unwrap();
+    let response = client
+        .add_item(
+            id,
+            &AddItemToBudgetRequest {
+                category_id,
+                name: "Groceries".to_string(),
+                amount: planned,
+                currency: None,
+                schedule: None,
+            },
+        )
+        .await;
+    let item_id = response.json::<dto::Item>().await.unwrap().id;
+
+    Budget {
+        id,
+        category_id,
+        item_id,
+    }
+}
+
+fn transaction(date: &str, amount: i64, item_id: Option<Uuid>) -> TransactionRequest {
+    TransactionRequest {
+        date: date.parse().unwrap(),
+        amount,
+        currency: None,
+        payee: Some("Supermarket".to_string()),
+        memo: None,
+        item_id,
+        category_id: None,
+    }
+}
+
+async fn add_transaction(
+    client: &ApiClient,
+    budget_id: Uuid,
+    request: &TransactionRequest,
+) -> Uuid {
+    let response = client.add_transaction(budget_id, request).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let location = response.headers()["location"].to_str().unwrap().to_string();
+    let transaction: Transaction = response.json().await.unwrap();
+    assert_eq!(
+        location,
+        format!("/budget/{budget_id}/transaction/{}", transaction.id)
+    );
+
+    transaction.id
+}
+
+async fn spawn_app(pool: PgPool) -> TestApp {
+    common::spawn_app_with_pool(pool)
+        .await
+        .expect("Failed to spawn our app.")
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn record_update_and_delete_transaction(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget = create_budget_with_groceries(&client, -300_000).await;
+
+    let id = add_transaction(
+        &client,
+        budget.id,
+        &transaction("2024-01-05", -20_000, Some(budget.item_id)),
+    )
+    .await;
+    let response = client.get_transaction(budget.id, id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let recorded: Transaction = response.json().await.unwrap();
+    assert_eq!(recorded.date, NaiveDate::from_ymd_opt(2024, 1, 5).unwrap());
+    assert_eq!(recorded.amount.minor_units(), -20_000);
+    assert_eq!(recorded.amount.currency(), Currency::DKK);
+    assert_eq!(recorded.category_id, Some(budget.category_id));
+    assert_eq!(recorded.payee.as_deref(), Some("Supermarket"));
+
+    let response = client
+        .update_transaction(budget.id, id, &transaction("2024-01-06", -25_000, None))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let updated: Transaction = response.json().await.unwrap();
+    assert_eq!(updated.amount.minor_units(), -25_000);
+    assert_eq!(updated.item_id, None);
+
+    let response = client.delete_transaction(budget.id, id).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        client.get_transaction(budget.id, id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn list_transactions_within_dates(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget = create_budget_with_groceries(&client, -300_000).await;
+    for date in ["2023-12-31", "2024-01-01", "2024-01-31", "2024-02-01"] {
+        add_transaction(&client, budget.id, &transaction(date, -100, None)).await;
+    }
+
+    let response = client
+        .get_transactions(budget.id, &[("from", "2024-01-01"), ("to", "2024-01-31")])
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let transactions: Vec<Transaction> = response.json().await.unwrap();
+    let dates: Vec<_> = transactions.iter().map(|t| t.date.to_string()).collect();
+    assert_eq!(dates, vec!["2024-01-31", "2024-01-01"]);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn progress_shows_remaining_and_overspent(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = app.client("Alice");
+    let budget = create_budget_with_groceries(&client, -300_000).await;
+    add_transaction(
+        &client,
+        budget.id,
+        &transaction("2024-01-05", -200_000, Some(budget.item_id)),
+    )
+    .await;
+
+    let progress: BudgetProgress = client
+        .get_budget_progress(budget.id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(progress.items[0].progress.remaining.minor_units(), 100_000);
+    assert_eq!(progress.items[0].progress.overspent.minor_units(), 0);
+
+    add_transaction(
+        &client,
+        budget.id,
+        &transaction("2024-01-19", -150_000, Some(budget.item_id)),
+    )
+    .await;
+
+    let response = client.get_budget_progress(budget.id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let item = &body["items"][0];
+    assert_eq!(item["planned"]["minor_units"], -300_000);
+    assert_eq!(item["actual"]["minor_units"], -350_000);
+    assert_eq!(item["remaining"]["minor_units"], -50_000);
+    assert_eq!(item["overspent"]["formatted"], "500.00");
+    assert_eq!(body["categories"][0]["category"], "Food");
+    assert_eq!(body["categories"][0]["overspent"]["minor_units"], 50_000);
+}
+
+#[sqlx::test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+async fn transactions_of_other_users_are_not_accessible(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let alice = app.client("Alice");
+    let bob = app.client("Bob");
+    let budget = create_budget_with_groceries(&alice, -300_000).await;
+    let id = add_transaction(&alice, budget.id, &transaction("2024-01-05", -100, None)).await;
+
+    assert_eq!(
+        bob.get_transactions(budget.id, &[]).await.status(),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        bob.add_transaction(budget.id, &transaction("2024-01-05", -100, None))
+            .await
+            .status(),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        bob.delete_transaction(budget.id, id).await.status(),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        bob.get_budget_progress(budget.id).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}